@@ -0,0 +1,766 @@
+// 批量文件识别队列：按队列顺序处理多个文件，云端与本地分别限制并发，支持暂停、继续与调整顺序；
+// 队列保存在应用数据目录，重启后运行中的条目重新排队（云端条目接回原来的文件识别任务，不重复上传）
+use crate::app_state;
+use crate::asr::config::AsrProviderConfig;
+use crate::asr::events::AsrResultEvent;
+use crate::asr::file_job::{self, FileJob};
+use crate::asr::provider::{cloud, LocalAsrProvider};
+use crate::asr::AsrProvider;
+use anyhow::Context;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+
+/// 单个条目状态变化时推送
+pub const BATCH_ITEM_EVENT: &str = "batch-asr-item";
+/// 队列整体进度变化时推送
+pub const BATCH_PROGRESS_EVENT: &str = "batch-asr-progress";
+
+const DEFAULT_CLOUD_CONCURRENCY: usize = 2;
+/// whisper-cli 会占满 CPU，本地默认逐个处理
+const DEFAULT_LOCAL_CONCURRENCY: usize = 1;
+
+/// 以目录加入时收录的音视频扩展名
+const MEDIA_EXTENSIONS: &[&str] = &[
+    "wav", "mp3", "m4a", "aac", "flac", "ogg", "opus", "wma", "mp4", "mkv", "mov", "avi", "webm",
+    "flv",
+];
+
+/// 输出格式，决定输出文件名 <文件名>.srt / <文件名>.json
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    Srt,
+    Json,
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Cloud,
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+/// 每种 Provider 同时处理的文件数
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Concurrency {
+    #[serde(default = "default_cloud_concurrency")]
+    pub cloud: usize,
+    #[serde(default = "default_local_concurrency")]
+    pub local: usize,
+}
+
+impl Default for Concurrency {
+    fn default() -> Self {
+        Self {
+            cloud: DEFAULT_CLOUD_CONCURRENCY,
+            local: DEFAULT_LOCAL_CONCURRENCY,
+        }
+    }
+}
+
+impl Concurrency {
+    fn limit(&self, kind: ProviderKind) -> usize {
+        match kind {
+            ProviderKind::Cloud => self.cloud,
+            ProviderKind::Local => self.local,
+        }
+        .max(1)
+    }
+}
+
+fn default_cloud_concurrency() -> usize {
+    DEFAULT_CLOUD_CONCURRENCY
+}
+
+fn default_local_concurrency() -> usize {
+    DEFAULT_LOCAL_CONCURRENCY
+}
+
+/// 队列中的一个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub id: String,
+    pub input_path: String,
+    pub output_path: String,
+    pub provider: ProviderKind,
+    /// 加入队列时的配置快照；密钥不写入队列文件，运行时取自当前配置
+    #[serde(serialize_with = "serialize_without_secrets")]
+    pub config: AsrProviderConfig,
+    pub status: ItemStatus,
+    /// 云端条目对应的文件识别任务（可用于 cancel_file_job 和关联 file-asr-progress）
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// 识别出的句子数
+    #[serde(default)]
+    pub sentences: usize,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+fn serialize_without_secrets<S: serde::Serializer>(
+    config: &AsrProviderConfig,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    config.without_secrets().serialize(serializer)
+}
+
+/// batch-asr-item 事件负载（不含配置）
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemProgress {
+    pub item_id: String,
+    pub input_path: String,
+    pub output_path: String,
+    pub provider: ProviderKind,
+    pub status: ItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub sentences: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&BatchItem> for BatchItemProgress {
+    fn from(item: &BatchItem) -> Self {
+        Self {
+            item_id: item.id.clone(),
+            input_path: item.input_path.clone(),
+            output_path: item.output_path.clone(),
+            provider: item.provider,
+            status: item.status,
+            job_id: item.job_id.clone(),
+            sentences: item.sentences,
+            error: item.error.clone(),
+        }
+    }
+}
+
+/// batch-asr-progress 事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub paused: bool,
+    pub total: usize,
+    pub pending: usize,
+    pub running: usize,
+    pub done: usize,
+    pub failed: usize,
+}
+
+/// get_batch_queue 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSnapshot {
+    pub concurrency: Concurrency,
+    pub progress: BatchProgress,
+    pub items: Vec<BatchItemProgress>,
+}
+
+/// 持久化的队列；items 的顺序即处理顺序
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchQueue {
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub concurrency: Concurrency,
+    #[serde(default)]
+    pub items: Vec<BatchItem>,
+}
+
+impl BatchQueue {
+    const fn new() -> Self {
+        Self {
+            paused: false,
+            concurrency: Concurrency {
+                cloud: DEFAULT_CLOUD_CONCURRENCY,
+                local: DEFAULT_LOCAL_CONCURRENCY,
+            },
+            items: Vec::new(),
+        }
+    }
+
+    /// 加入队列；输出文件与队列中的条目（含已完成的）或磁盘上已有的文件重名时追加序号（a.srt、a_2.srt）
+    fn enqueue(
+        &mut self,
+        inputs: Vec<PathBuf>,
+        output_dir: Option<&Path>,
+        format: OutputFormat,
+        config: &AsrProviderConfig,
+    ) -> Vec<BatchItem> {
+        let mut taken: HashSet<PathBuf> = self
+            .items
+            .iter()
+            .map(|i| PathBuf::from(&i.output_path))
+            .collect();
+        let provider = match config {
+            AsrProviderConfig::Cloud(_) => ProviderKind::Cloud,
+            AsrProviderConfig::Local(_) => ProviderKind::Local,
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let added: Vec<BatchItem> = inputs
+            .into_iter()
+            .map(|input| {
+                let output = output_path_for(&input, output_dir, format, &taken);
+                taken.insert(output.clone());
+                BatchItem {
+                    id: Uuid::new_v4().to_string(),
+                    input_path: input.to_string_lossy().to_string(),
+                    output_path: output.to_string_lossy().to_string(),
+                    provider,
+                    config: config.clone(),
+                    status: ItemStatus::Pending,
+                    job_id: None,
+                    sentences: 0,
+                    error: None,
+                    created_at: now,
+                    updated_at: now,
+                }
+            })
+            .collect();
+        self.items.extend(added.iter().cloned());
+        added
+    }
+
+    /// 按队列顺序取出在并发上限内可以开始的条目，标记为运行中；
+    /// 尚未收到前端配置的密钥时（cloud_ready 为 false）云端条目继续等待
+    fn start_runnable(&mut self, cloud_ready: bool) -> Vec<BatchItem> {
+        if self.paused {
+            return Vec::new();
+        }
+        let mut running: BTreeMap<ProviderKind, usize> = BTreeMap::new();
+        for item in self
+            .items
+            .iter()
+            .filter(|i| i.status == ItemStatus::Running)
+        {
+            *running.entry(item.provider).or_default() += 1;
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut started = Vec::new();
+        for item in self.items.iter_mut() {
+            if item.status != ItemStatus::Pending
+                || (item.provider == ProviderKind::Cloud && !cloud_ready)
+            {
+                continue;
+            }
+            let count = running.entry(item.provider).or_default();
+            if *count >= self.concurrency.limit(item.provider) {
+                continue;
+            }
+            *count += 1;
+            item.status = ItemStatus::Running;
+            item.error = None;
+            item.updated_at = now;
+            started.push(item.clone());
+        }
+        started
+    }
+
+    /// 按给定的条目 ID 顺序重排，未列出的条目保持原有相对顺序排在后面
+    fn reorder(&mut self, item_ids: &[String]) {
+        self.items.sort_by_key(|item| {
+            item_ids
+                .iter()
+                .position(|id| *id == item.id)
+                .unwrap_or(item_ids.len())
+        });
+    }
+
+    fn progress(&self) -> BatchProgress {
+        let count = |status| self.items.iter().filter(|i| i.status == status).count();
+        BatchProgress {
+            paused: self.paused,
+            total: self.items.len(),
+            pending: count(ItemStatus::Pending),
+            running: count(ItemStatus::Running),
+            done: count(ItemStatus::Done),
+            failed: count(ItemStatus::Failed),
+        }
+    }
+}
+
+/// 展开输入：文件原样加入；目录加入其中的音视频文件；含 * ? [ 的按通配符匹配。结果去重并保持顺序
+pub fn expand_inputs(inputs: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(path)
+                .with_context(|| format!("无法读取目录: {}", input))?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && is_media(p))
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        } else if input.contains(['*', '?', '[']) {
+            let matches = glob::glob(input).with_context(|| format!("无效的通配符: {}", input))?;
+            files.extend(matches.filter_map(Result::ok).filter(|p| p.is_file()));
+        } else if path.is_file() {
+            files.push(path.to_path_buf());
+        } else {
+            anyhow::bail!("文件不存在: {}", input);
+        }
+    }
+    let mut seen = HashSet::new();
+    files.retain(|p| seen.insert(p.clone()));
+    Ok(files)
+}
+
+fn is_media(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MEDIA_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// 输出路径：<输出目录或输入文件所在目录>/<文件名>.<扩展名>，不覆盖 taken 中的路径和已存在的文件
+fn output_path_for(
+    input: &Path,
+    output_dir: Option<&Path>,
+    format: OutputFormat,
+    taken: &HashSet<PathBuf>,
+) -> PathBuf {
+    let dir = output_dir
+        .or_else(|| input.parent())
+        .unwrap_or(Path::new(""));
+    let stem = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let ext = format.extension();
+    let mut path = dir.join(format!("{}.{}", stem, ext));
+    let mut n = 2;
+    while taken.contains(&path) || path.exists() {
+        path = dir.join(format!("{}_{}.{}", stem, n, ext));
+        n += 1;
+    }
+    path
+}
+
+// ── 全局队列 ────────────────────────────────────────────────────────────────
+
+static QUEUE: Mutex<BatchQueue> = Mutex::new(BatchQueue::new());
+
+static QUEUE_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// 重启后由队列接回的云端文件识别任务（条目 ID → 任务）
+static RESUMABLE: Mutex<BTreeMap<String, FileJob>> = Mutex::new(BTreeMap::new());
+
+/// 读取保存的队列并继续处理（应用启动时调用）；
+/// 运行中的云端条目若仍有未完成的文件识别任务，则从 jobs 中取出，由队列恢复
+pub fn init(path: PathBuf, jobs: &mut Vec<FileJob>) {
+    let mut queue = match std::fs::read(&path) {
+        Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|err| {
+            warn!("⚠️ 批量识别队列无法解析，已重置: {}", err);
+            BatchQueue::new()
+        }),
+        Err(_) => BatchQueue::new(),
+    };
+
+    let mut resumable = lock_resumable();
+    for item in queue
+        .items
+        .iter_mut()
+        .filter(|i| i.status == ItemStatus::Running)
+    {
+        item.status = ItemStatus::Pending;
+        let job = item
+            .job_id
+            .as_ref()
+            .and_then(|id| jobs.iter().position(|j| &j.id == id))
+            .map(|pos| jobs.remove(pos));
+        if let Some(job) = job {
+            resumable.insert(item.id.clone(), job);
+        }
+    }
+    drop(resumable);
+
+    let progress = queue.progress();
+    if progress.pending > 0 {
+        info!(
+            "♻️ 恢复批量识别队列: {} 个待处理{}",
+            progress.pending,
+            if queue.paused { "（已暂停）" } else { "" }
+        );
+    }
+    let _ = QUEUE_PATH.set(path);
+    *lock_queue() = queue;
+    schedule();
+}
+
+/// 加入文件、目录或通配符，返回新加入的条目
+pub fn enqueue(
+    inputs: &[String],
+    output_dir: Option<&Path>,
+    format: OutputFormat,
+    config: AsrProviderConfig,
+) -> anyhow::Result<Vec<BatchItemProgress>> {
+    let files = expand_inputs(inputs)?;
+    if files.is_empty() {
+        anyhow::bail!("没有找到可识别的文件");
+    }
+    if let AsrProviderConfig::Cloud(config) = &config {
+        file_job::set_credentials(config);
+    }
+    let added: Vec<BatchItemProgress> = {
+        let mut queue = lock_queue();
+        let added = queue.enqueue(files, output_dir, format, &config);
+        save(&queue);
+        added.iter().map(BatchItemProgress::from).collect()
+    };
+    info!("📥 批量识别加入 {} 个文件", added.len());
+    for item in &added {
+        emit(BATCH_ITEM_EVENT, item);
+    }
+    schedule();
+    Ok(added)
+}
+
+/// 暂停：不再开始新的条目，已在运行的条目继续完成
+pub fn pause() {
+    modify(|queue| queue.paused = true);
+    info!("⏸️ 批量识别已暂停");
+}
+
+pub fn resume() {
+    modify(|queue| queue.paused = false);
+    info!("▶️ 批量识别继续");
+    schedule();
+}
+
+pub fn reorder(item_ids: &[String]) {
+    modify(|queue| queue.reorder(item_ids));
+    let snapshot = snapshot();
+    for item in &snapshot.items {
+        emit(BATCH_ITEM_EVENT, item);
+    }
+}
+
+pub fn set_concurrency(concurrency: Concurrency) {
+    modify(|queue| queue.concurrency = concurrency);
+    schedule();
+}
+
+/// 移除已完成和失败的条目
+pub fn clear_finished() {
+    modify(|queue| {
+        queue
+            .items
+            .retain(|i| matches!(i.status, ItemStatus::Pending | ItemStatus::Running))
+    });
+}
+
+pub fn snapshot() -> BatchSnapshot {
+    let queue = lock_queue();
+    BatchSnapshot {
+        concurrency: queue.concurrency,
+        progress: queue.progress(),
+        items: queue.items.iter().map(BatchItemProgress::from).collect(),
+    }
+}
+
+/// 修改队列：保存并推送整体进度
+fn modify(f: impl FnOnce(&mut BatchQueue)) {
+    let progress = {
+        let mut queue = lock_queue();
+        f(&mut queue);
+        save(&queue);
+        queue.progress()
+    };
+    emit(BATCH_PROGRESS_EVENT, &progress);
+}
+
+/// 修改单个条目：保存并推送条目状态与整体进度
+fn update_item(item_id: &str, f: impl FnOnce(&mut BatchItem)) {
+    let (item, progress) = {
+        let mut queue = lock_queue();
+        let Some(item) = queue.items.iter_mut().find(|i| i.id == item_id) else {
+            return;
+        };
+        f(item);
+        item.updated_at = chrono::Utc::now().timestamp_millis();
+        let item = BatchItemProgress::from(&*item);
+        save(&queue);
+        (item, queue.progress())
+    };
+    emit(BATCH_ITEM_EVENT, &item);
+    emit(BATCH_PROGRESS_EVENT, &progress);
+}
+
+/// 在并发上限内开始新的条目
+pub fn schedule() {
+    let cloud_ready = file_job::has_credentials();
+    let (started, progress) = {
+        let mut queue = lock_queue();
+        let started = queue.start_runnable(cloud_ready);
+        if !started.is_empty() {
+            save(&queue);
+        }
+        (started, queue.progress())
+    };
+    for item in started {
+        emit(BATCH_ITEM_EVENT, &BatchItemProgress::from(&item));
+        tauri::async_runtime::spawn(run_item(item));
+    }
+    emit(BATCH_PROGRESS_EVENT, &progress);
+}
+
+async fn run_item(item: BatchItem) {
+    info!(
+        "🎬 批量识别开始: {} → {}",
+        item.input_path, item.output_path
+    );
+    let result = recognize(&item).await;
+    update_item(&item.id, |i| match &result {
+        Ok(events) => {
+            i.status = ItemStatus::Done;
+            i.sentences = events.len();
+        }
+        Err(err) => {
+            warn!("批量识别失败 {}: {:#}", i.input_path, err);
+            i.status = ItemStatus::Failed;
+            i.error = Some(err.to_string());
+        }
+    });
+    schedule();
+}
+
+async fn recognize(item: &BatchItem) -> anyhow::Result<Vec<AsrResultEvent>> {
+    let input = Path::new(&item.input_path);
+    let output = Path::new(&item.output_path);
+    if let Some(dir) = output.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("无法创建输出目录 {}", dir.display()))?;
+    }
+
+    match &item.config {
+        AsrProviderConfig::Cloud(config) => {
+            // 与 CloudAsrProvider::recognize_file 相同，以文件识别任务执行，并记下任务 ID 以便重启后接回
+            // 队列文件和任务记录都不含密钥，统一取自当前配置
+            let mut config = (**config).clone();
+            if !file_job::restore_credentials(&mut config) {
+                anyhow::bail!("尚未收到云端识别配置");
+            }
+            let resumed = lock_resumable().remove(&item.id);
+            let job = match resumed {
+                Some(mut job) => {
+                    job.config.restore_secrets(&config);
+                    job
+                }
+                None => FileJob::new(input, output, config),
+            };
+            update_item(&item.id, |i| i.job_id = Some(job.id.clone()));
+            cloud::run_job(job).await
+        }
+        AsrProviderConfig::Local(config) => {
+            LocalAsrProvider::new(config.clone())
+                .recognize_file(input, output)
+                .await
+        }
+    }
+}
+
+/// 先写临时文件再重命名，避免中途退出留下半个 JSON
+fn save(queue: &BatchQueue) {
+    let Some(path) = QUEUE_PATH.get() else {
+        return;
+    };
+    let tmp = path.with_extension("json.tmp");
+    let result = serde_json::to_vec_pretty(queue)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| Ok(std::fs::write(&tmp, data)?))
+        .and_then(|_| Ok(std::fs::rename(&tmp, path)?));
+    if let Err(err) = result {
+        warn!("⚠️ 保存批量识别队列失败: {}", err);
+    }
+}
+
+fn emit<T: Serialize>(event: &str, payload: &T) {
+    if let Err(err) = app_state::emit_event(event, payload) {
+        warn!("⚠️ 推送批量识别进度失败: {}", err);
+    }
+}
+
+fn lock_queue() -> std::sync::MutexGuard<'static, BatchQueue> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn lock_resumable() -> std::sync::MutexGuard<'static, BTreeMap<String, FileJob>> {
+    RESUMABLE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::config::LocalAsrConfig;
+
+    #[test]
+    fn test_expand_inputs_and_output_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let lectures = dir.path().join("lectures");
+        std::fs::create_dir_all(lectures.join("extra")).unwrap();
+        for name in ["a.mp4", "a.wav", "b.MP3", "notes.txt", "extra/c.m4a"] {
+            std::fs::write(lectures.join(name), b"").unwrap();
+        }
+
+        let inputs = [
+            lectures.to_string_lossy().to_string(),
+            format!("{}/extra/*.m4a", lectures.display()),
+            lectures.join("a.mp4").to_string_lossy().to_string(),
+        ];
+        let files = expand_inputs(&inputs).unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|p| p.strip_prefix(&lectures).unwrap().display().to_string())
+            .collect();
+        assert_eq!(names, ["a.mp4", "a.wav", "b.MP3", "extra/c.m4a"]);
+        assert!(expand_inputs(&["/no/such/file.wav".to_string()]).is_err());
+
+        let mut queue = BatchQueue::new();
+        let config = AsrProviderConfig::Local(LocalAsrConfig::default());
+        let out = dir.path().join("out");
+        let items = queue.enqueue(files, Some(&out), OutputFormat::Json, &config);
+        let outputs: Vec<String> = items
+            .iter()
+            .map(|i| {
+                i.output_path
+                    .strip_prefix(&*out.to_string_lossy())
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        let sep = std::path::MAIN_SEPARATOR;
+        assert_eq!(
+            outputs,
+            [
+                format!("{sep}a.json"),
+                format!("{sep}a_2.json"),
+                format!("{sep}b.json"),
+                format!("{sep}c.json"),
+            ]
+        );
+
+        // 不指定输出目录时写在输入文件旁边
+        let items = queue.enqueue(
+            vec![lectures.join("b.MP3")],
+            None,
+            OutputFormat::Srt,
+            &config,
+        );
+        assert_eq!(
+            items[0].output_path,
+            lectures.join("b.srt").to_string_lossy()
+        );
+
+        // 已完成条目的输出和磁盘上已有的文件都不会被覆盖
+        queue.items[2].status = ItemStatus::Done;
+        std::fs::write(lectures.join("a.srt"), "").unwrap();
+        let items = queue.enqueue(
+            vec![lectures.join("b.MP3"), lectures.join("a.wav")],
+            None,
+            OutputFormat::Srt,
+            &config,
+        );
+        assert_eq!(
+            items[0].output_path,
+            lectures.join("b_2.srt").to_string_lossy()
+        );
+        assert_eq!(
+            items[1].output_path,
+            lectures.join("a_2.srt").to_string_lossy()
+        );
+        let items = queue.enqueue(
+            vec![lectures.join("b.MP3")],
+            Some(&out),
+            OutputFormat::Json,
+            &config,
+        );
+        assert_eq!(items[0].output_path, out.join("b_2.json").to_string_lossy());
+    }
+
+    #[test]
+    fn test_start_runnable_respects_limits_pause_and_order() {
+        let mut queue = BatchQueue::new();
+        let cloud = AsrProviderConfig::Cloud(Box::default());
+        let local = AsrProviderConfig::Local(LocalAsrConfig::default());
+        let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        queue.enqueue(
+            paths(&["/c1.mp4", "/c2.mp4", "/c3.mp4"]),
+            None,
+            OutputFormat::Srt,
+            &cloud,
+        );
+        queue.enqueue(
+            paths(&["/l1.wav", "/l2.wav"]),
+            None,
+            OutputFormat::Srt,
+            &local,
+        );
+
+        // 把 c3、l2 调到最前
+        let ids: Vec<String> = queue.items.iter().map(|i| i.id.clone()).collect();
+        queue.reorder(&[ids[2].clone(), ids[4].clone()]);
+
+        queue.paused = true;
+        assert!(queue.start_runnable(true).is_empty());
+        queue.paused = false;
+
+        // 还没有云端密钥时只开始本地条目
+        let started: Vec<String> = queue
+            .start_runnable(false)
+            .into_iter()
+            .map(|i| i.input_path)
+            .collect();
+        assert_eq!(started, ["/l2.wav"]);
+
+        let started: Vec<String> = queue
+            .start_runnable(true)
+            .into_iter()
+            .map(|i| i.input_path)
+            .collect();
+        assert_eq!(started, ["/c3.mp4", "/c1.mp4"]);
+        assert!(queue.start_runnable(true).is_empty());
+
+        queue.items[0].status = ItemStatus::Done;
+        let started: Vec<String> = queue
+            .start_runnable(true)
+            .into_iter()
+            .map(|i| i.input_path)
+            .collect();
+        assert_eq!(started, ["/c2.mp4"]);
+
+        let progress = queue.progress();
+        assert_eq!(
+            (
+                progress.total,
+                progress.pending,
+                progress.running,
+                progress.done
+            ),
+            (5, 1, 3, 1)
+        );
+    }
+}
@@ -7,7 +7,7 @@ pub enum AsrProviderConfig {
     #[serde(rename = "local")]
     Local(LocalAsrConfig),
     #[serde(rename = "cloud")]
-    Cloud(CloudAsrConfig),
+    Cloud(Box<CloudAsrConfig>),
 }
 
 /// 云端 Provider 配置
@@ -16,21 +16,121 @@ pub struct CloudAsrConfig {
     /// 流式识别模型（Gummy 或 Paraformer）
     #[serde(default)]
     pub streaming: CloudStreamingConfig,
-    /// OSS 上传配置（文件识别使用）
+    /// 文件识别时存放音视频的对象存储
+    #[serde(default)]
+    pub storage: StorageBackend,
+    /// OSS 上传配置（storage = oss）
     #[serde(default)]
     pub oss: OssConfig,
+    /// S3 兼容存储配置（storage = s3）
+    #[serde(default)]
+    pub s3: S3Config,
+    /// 本地 HTTP 服务目录（storage = local_http）
+    #[serde(default)]
+    pub local_http: LocalHttpConfig,
     /// 文件识别 DashScope API Key
     #[serde(default = "default_api_key")]
     pub file_asr_api_key: String,
+    /// 文件识别（Fun-ASR）参数
+    #[serde(default)]
+    pub file_asr: FileAsrConfig,
 }
 
 impl Default for CloudAsrConfig {
     fn default() -> Self {
         Self {
             streaming: CloudStreamingConfig::default(),
+            storage: StorageBackend::default(),
             oss: OssConfig::default(),
+            s3: S3Config::default(),
+            local_http: LocalHttpConfig::default(),
             file_asr_api_key: default_api_key(),
+            file_asr: FileAsrConfig::default(),
+        }
+    }
+}
+
+impl CloudAsrConfig {
+    /// 去掉 API Key 和存储密钥后的副本，用于写入本机的任务记录
+    pub fn without_secrets(&self) -> Self {
+        let mut config = self.clone();
+        config.file_asr_api_key.clear();
+        config.oss.access_key_id.clear();
+        config.oss.access_key_secret.clear();
+        config.s3.access_key_id.clear();
+        config.s3.secret_access_key.clear();
+        config.streaming.server_config_mut().api_key.clear();
+        config
+    }
+
+    /// 恢复任务时用当前配置中的密钥补全任务记录里的配置
+    pub fn restore_secrets(&mut self, current: &CloudAsrConfig) {
+        self.file_asr_api_key = current.file_asr_api_key.clone();
+        self.oss.access_key_id = current.oss.access_key_id.clone();
+        self.oss.access_key_secret = current.oss.access_key_secret.clone();
+        self.s3.access_key_id = current.s3.access_key_id.clone();
+        self.s3.secret_access_key = current.s3.secret_access_key.clone();
+        self.streaming.server_config_mut().api_key =
+            current.streaming.server_config().api_key.clone();
+    }
+}
+
+impl AsrProviderConfig {
+    /// 去掉密钥后的副本（本地配置不含密钥）
+    pub fn without_secrets(&self) -> Self {
+        match self {
+            Self::Cloud(config) => Self::Cloud(Box::new(config.without_secrets())),
+            Self::Local(config) => Self::Local(config.clone()),
+        }
+    }
+}
+
+/// Fun-ASR 文件识别参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileAsrConfig {
+    /// 说话人分离：结果中每句带 speaker_id
+    #[serde(default)]
+    pub diarization_enabled: bool,
+    /// 说话人数量（2-100），仅作参考；省略时自动判断
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_count: Option<u32>,
+    /// 语言提示，如 ["zh", "en"]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_hints: Option<Vec<String>>,
+    /// 热词表 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vocabulary_id: Option<String>,
+    /// 识别的音轨（从 0 开始）；省略时只识别第一个音轨
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<Vec<u32>>,
+    /// 过滤语气词（嗯、啊等）
+    #[serde(default)]
+    pub disfluency_removal_enabled: bool,
+}
+
+impl FileAsrConfig {
+    /// 提交任务时的 parameters 字段（只包含已设置的参数）
+    pub fn parameters(&self) -> serde_json::Value {
+        let mut params = serde_json::Map::new();
+        if self.diarization_enabled {
+            params.insert("diarization_enabled".into(), true.into());
+            if let Some(count) = self.speaker_count {
+                params.insert("speaker_count".into(), count.into());
+            }
+        }
+        if let Some(hints) = &self.language_hints {
+            params.insert("language_hints".into(), hints.clone().into());
         }
+        if let Some(id) = &self.vocabulary_id {
+            params.insert("vocabulary_id".into(), id.clone().into());
+        }
+        if let Some(channels) = &self.channel_id {
+            params.insert("channel_id".into(), channels.clone().into());
+        }
+        if self.disfluency_removal_enabled {
+            params.insert("disfluency_removal_enabled".into(), true.into());
+        }
+        params.into()
     }
 }
 
@@ -50,6 +150,22 @@ impl Default for CloudStreamingConfig {
     }
 }
 
+impl CloudStreamingConfig {
+    fn server_config(&self) -> &ServerConfig {
+        match self {
+            Self::Gummy(c) => &c.server_config,
+            Self::Paraformer(c) => &c.server_config,
+        }
+    }
+
+    fn server_config_mut(&mut self) -> &mut ServerConfig {
+        match self {
+            Self::Gummy(c) => &mut c.server_config,
+            Self::Paraformer(c) => &mut c.server_config,
+        }
+    }
+}
+
 /// OSS 上传配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OssConfig {
@@ -62,6 +178,9 @@ pub struct OssConfig {
     pub access_key_id: String,
     #[serde(default)]
     pub access_key_secret: String,
+    /// 上传的对象为私有，Fun-ASR 通过签名 URL 读取；签名 URL 的有效期（秒）
+    #[serde(default = "default_presign_expires_secs")]
+    pub presign_expires_secs: u64,
 }
 
 impl Default for OssConfig {
@@ -71,10 +190,73 @@ impl Default for OssConfig {
             bucket: String::new(),
             access_key_id: String::new(),
             access_key_secret: String::new(),
+            presign_expires_secs: default_presign_expires_secs(),
         }
     }
 }
 
+/// 文件识别使用的对象存储
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// 阿里云 OSS
+    #[default]
+    Oss,
+    /// S3 兼容存储（AWS S3、MinIO 等）
+    S3,
+    /// DashScope 临时文件存储（无需自备存储，文件 48 小时后自动过期）
+    Dashscope,
+    /// 由本地 HTTP 服务对外提供的目录（需保证 Fun-ASR 可以访问 base_url）
+    LocalHttp,
+}
+
+/// S3 兼容存储配置（SigV4 签名）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    /// 服务地址（含协议），如 https://s3.us-east-1.amazonaws.com、http://127.0.0.1:9000
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    #[serde(default)]
+    pub bucket: String,
+    #[serde(default)]
+    pub access_key_id: String,
+    #[serde(default)]
+    pub secret_access_key: String,
+    /// path-style 地址（MinIO 等自建服务通常需要开启）
+    #[serde(default = "default_true")]
+    pub path_style: bool,
+    /// 签名 URL 有效期（秒）
+    #[serde(default = "default_presign_expires_secs")]
+    pub presign_expires_secs: u64,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: default_s3_region(),
+            bucket: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+            presign_expires_secs: default_presign_expires_secs(),
+        }
+    }
+}
+
+/// 本地 HTTP 服务配置：上传即复制到 directory，Fun-ASR 通过 base_url 读取
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalHttpConfig {
+    /// HTTP 服务的根目录
+    #[serde(default)]
+    pub directory: String,
+    /// 根目录对外的访问地址，如 https://files.example.com/asr
+    #[serde(default)]
+    pub base_url: String,
+}
+
 /// 单个本地 Whisper 模型配置（识别或翻译）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalModelConfig {
@@ -108,6 +290,9 @@ pub struct LocalAsrConfig {
     /// 语音翻译模型配置
     #[serde(default)]
     pub translation: LocalModelConfig,
+    /// 本地流式识别（滚动窗口）配置
+    #[serde(default)]
+    pub streaming: LocalStreamingConfig,
 }
 
 impl Default for LocalAsrConfig {
@@ -115,6 +300,31 @@ impl Default for LocalAsrConfig {
         Self {
             recognition: LocalModelConfig::default(),
             translation: LocalModelConfig::default(),
+            streaming: LocalStreamingConfig::default(),
+        }
+    }
+}
+
+/// 本地流式识别配置：音频累积到滚动窗口中，每收到 step_ms 新音频对整个窗口推理一次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalStreamingConfig {
+    /// 窗口最大时长（毫秒），超过后强制提交窗口内全部结果
+    #[serde(default = "default_window_ms")]
+    pub window_ms: u64,
+    /// 推理步长（毫秒）
+    #[serde(default = "default_step_ms")]
+    pub step_ms: u64,
+    /// 窗口末尾的重叠区（毫秒）：落在其中的句子保持临时结果，下次推理时重新识别
+    #[serde(default = "default_overlap_ms")]
+    pub overlap_ms: u64,
+}
+
+impl Default for LocalStreamingConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: default_window_ms(),
+            step_ms: default_step_ms(),
+            overlap_ms: default_overlap_ms(),
         }
     }
 }
@@ -233,6 +443,14 @@ fn default_oss_endpoint() -> String {
     String::new()
 }
 
+fn default_presign_expires_secs() -> u64 {
+    3600
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
 fn default_local_language() -> String {
     "auto".to_string()
 }
@@ -241,27 +459,56 @@ fn default_n_threads() -> u32 {
     4
 }
 
+fn default_window_ms() -> u64 {
+    8000
+}
+
+fn default_step_ms() -> u64 {
+    2000
+}
+
+fn default_overlap_ms() -> u64 {
+    1000
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_provider_config_roundtrip_cloud() {
-        let config = AsrProviderConfig::Cloud(CloudAsrConfig {
+        let config = AsrProviderConfig::Cloud(Box::new(CloudAsrConfig {
             streaming: CloudStreamingConfig::Gummy(GummyConfig::default()),
+            storage: StorageBackend::S3,
             oss: OssConfig {
                 endpoint: "oss-cn-beijing.aliyuncs.com".to_string(),
                 bucket: "my-bucket".to_string(),
                 access_key_id: "key_id".to_string(),
                 access_key_secret: "key_secret".to_string(),
+                presign_expires_secs: 7200,
+            },
+            s3: S3Config {
+                endpoint: "http://127.0.0.1:9000".to_string(),
+                bucket: "asr".to_string(),
+                ..Default::default()
             },
+            local_http: LocalHttpConfig::default(),
             file_asr_api_key: "sk-xxx".to_string(),
-        });
+            file_asr: FileAsrConfig {
+                diarization_enabled: true,
+                speaker_count: Some(2),
+                ..Default::default()
+            },
+        }));
         let json = serde_json::to_string(&config).unwrap();
         let back: AsrProviderConfig = serde_json::from_str(&json).unwrap();
         if let AsrProviderConfig::Cloud(c) = back {
             assert_eq!(c.file_asr_api_key, "sk-xxx");
             assert_eq!(c.oss.bucket, "my-bucket");
+            assert_eq!(c.file_asr.speaker_count, Some(2));
+            assert_eq!(c.storage, StorageBackend::S3);
+            assert_eq!(c.s3.region, "us-east-1");
+            assert!(c.s3.path_style);
         } else {
             panic!("expected Cloud variant");
         }
@@ -276,14 +523,37 @@ mod tests {
                 n_threads: 4,
             },
             translation: LocalModelConfig::default(),
+            streaming: LocalStreamingConfig::default(),
         });
         let json = serde_json::to_string(&config).unwrap();
         let back: AsrProviderConfig = serde_json::from_str(&json).unwrap();
         if let AsrProviderConfig::Local(c) = back {
             assert_eq!(c.recognition.model_path, "/models/ggml-base.bin");
             assert_eq!(c.recognition.n_threads, 4);
+            assert_eq!(c.streaming.window_ms, 8000);
         } else {
             panic!("expected Local variant");
         }
     }
+
+    #[test]
+    fn test_file_asr_parameters() {
+        assert_eq!(FileAsrConfig::default().parameters(), serde_json::json!({}));
+
+        let config: FileAsrConfig = serde_json::from_str(
+            r#"{"diarization_enabled": true, "speaker_count": 3, "language_hints": ["zh", "en"],
+                "channel_id": [0, 1], "disfluency_removal_enabled": true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.parameters(),
+            serde_json::json!({
+                "diarization_enabled": true,
+                "speaker_count": 3,
+                "language_hints": ["zh", "en"],
+                "channel_id": [0, 1],
+                "disfluency_removal_enabled": true
+            })
+        );
+    }
 }
@@ -0,0 +1,252 @@
+use crate::app_state;
+use log::warn;
+use serde::Serialize;
+use std::fmt;
+
+/// 前端监听的错误事件名称
+pub const ASR_ERROR_EVENT: &str = "asr-error";
+
+/// 流式识别错误（结构化，便于前端按类型展示）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AsrError {
+    /// API Key 无效、未授权或无权限
+    AuthFailed { message: String },
+    /// 网络不可达、DNS 失败、连接被重置等
+    NetworkUnreachable { message: String },
+    /// TLS 握手或证书错误
+    Tls { message: String },
+    /// 协议错误：服务端返回无法解析的消息、握手响应异常等
+    Protocol { message: String },
+    /// 服务端返回 task-failed
+    TaskFailed { code: String, message: String },
+    /// 调用频率或额度超限、账户欠费
+    QuotaExceeded { code: String, message: String },
+    /// 本地配置错误（如地址非法、模型未配置）
+    Config { message: String },
+    /// 本地模型推理失败
+    Inference { message: String },
+}
+
+/// 发送给前端的错误事件
+#[derive(Debug, Clone, Serialize)]
+pub struct AsrErrorEvent {
+    #[serde(flatten)]
+    pub error: AsrError,
+    /// 是否会自动重试（前端据此决定提示「重连中」还是「已停止」）
+    pub retryable: bool,
+    /// 可直接展示的中文描述
+    pub display: String,
+}
+
+impl AsrError {
+    /// 根据 task-failed 的错误代码分类
+    pub fn from_task_failed(code: &str, message: &str) -> Self {
+        let code_lower = code.to_lowercase();
+        let message_lower = message.to_lowercase();
+        if code_lower.contains("apikey")
+            || code_lower.contains("unauthorized")
+            || code_lower.contains("accessdenied")
+            || message_lower.contains("api-key")
+            || message_lower.contains("api key")
+        {
+            Self::AuthFailed {
+                message: format!("{} ({})", message, code),
+            }
+        } else if code_lower.contains("throttling")
+            || code_lower.contains("quota")
+            || code_lower.contains("arrearage")
+        {
+            Self::QuotaExceeded {
+                code: code.to_string(),
+                message: message.to_string(),
+            }
+        } else if code_lower.contains("invalidparameter")
+            || code_lower.starts_with("badrequest")
+            || code_lower.contains("unsupported")
+            || code_lower.contains("modelnotfound")
+            || code_lower.contains("notfound.model")
+        {
+            // 参数、格式、模型不被接受：每次重试都会得到同样的结果
+            Self::Config {
+                message: format!("{} ({})", message, code),
+            }
+        } else {
+            Self::TaskFailed {
+                code: code.to_string(),
+                message: message.to_string(),
+            }
+        }
+    }
+
+    /// 是否值得重连重试（认证、额度、配置类错误重试无意义）
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::NetworkUnreachable { .. } | Self::Tls { .. } | Self::Protocol { .. } => true,
+            Self::TaskFailed { .. } => true,
+            Self::AuthFailed { .. }
+            | Self::QuotaExceeded { .. }
+            | Self::Config { .. }
+            | Self::Inference { .. } => false,
+        }
+    }
+
+    /// 以 asr-error 事件推送到前端
+    pub fn emit(&self) {
+        let event = AsrErrorEvent {
+            error: self.clone(),
+            retryable: self.is_retryable(),
+            display: self.to_string(),
+        };
+        if let Err(err) = app_state::emit_event(ASR_ERROR_EVENT, &event) {
+            warn!("发送 ASR 错误事件到前端失败: {}", err);
+        }
+    }
+}
+
+impl fmt::Display for AsrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AuthFailed { message } => {
+                write!(f, "认证失败，请检查 API Key 配置: {}", message)
+            }
+            Self::NetworkUnreachable { message } => write!(f, "网络不可达: {}", message),
+            Self::Tls { message } => write!(f, "TLS 连接错误: {}", message),
+            Self::Protocol { message } => write!(f, "协议错误: {}", message),
+            Self::TaskFailed { code, message } => {
+                write!(f, "识别任务失败: {} (错误代码: {})", message, code)
+            }
+            Self::QuotaExceeded { code, message } => {
+                write!(f, "调用额度或频率超限: {} (错误代码: {})", message, code)
+            }
+            Self::Config { message } => write!(f, "配置错误: {}", message),
+            Self::Inference { message } => write!(f, "本地推理失败: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AsrError {}
+
+impl From<tungstenite::Error> for AsrError {
+    fn from(err: tungstenite::Error) -> Self {
+        use tungstenite::Error;
+        match err {
+            Error::Http(response) => {
+                let status = response.status();
+                let body = response
+                    .body()
+                    .as_ref()
+                    .map(|b| String::from_utf8_lossy(b).to_string())
+                    .unwrap_or_default();
+                let message = format!("HTTP {} {}", status, body).trim().to_string();
+                match status.as_u16() {
+                    401 | 403 => Self::AuthFailed { message },
+                    429 => Self::QuotaExceeded {
+                        code: status.as_u16().to_string(),
+                        message,
+                    },
+                    _ => Self::Protocol { message },
+                }
+            }
+            Error::Io(e) => Self::NetworkUnreachable {
+                message: e.to_string(),
+            },
+            Error::ConnectionClosed | Error::AlreadyClosed => Self::NetworkUnreachable {
+                message: err.to_string(),
+            },
+            Error::Tls(e) => Self::Tls {
+                message: e.to_string(),
+            },
+            Error::Url(e) => Self::Config {
+                message: format!("无效的 WebSocket 地址: {}", e),
+            },
+            other => Self::Protocol {
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+impl From<serde_json::Error> for AsrError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Protocol {
+            message: format!("JSON 解析/序列化失败: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_failed_classification() {
+        assert!(matches!(
+            AsrError::from_task_failed("InvalidApiKey", "Invalid API-key provided."),
+            AsrError::AuthFailed { .. }
+        ));
+        assert!(matches!(
+            AsrError::from_task_failed("Throttling.RateQuota", "Requests rate limit exceeded"),
+            AsrError::QuotaExceeded { .. }
+        ));
+        assert!(matches!(
+            AsrError::from_task_failed("Arrearage", "Access denied, account is in arrears"),
+            AsrError::QuotaExceeded { .. }
+        ));
+        assert!(matches!(
+            AsrError::from_task_failed("InvalidParameter", "sample_rate is not supported"),
+            AsrError::Config { .. }
+        ));
+        assert!(matches!(
+            AsrError::from_task_failed("BadRequest.UnsupportedFormat", "format is invalid"),
+            AsrError::Config { .. }
+        ));
+        assert_eq!(
+            AsrError::from_task_failed("InternalError", "server busy"),
+            AsrError::TaskFailed {
+                code: "InternalError".to_string(),
+                message: "server busy".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_retryable() {
+        assert!(AsrError::NetworkUnreachable {
+            message: String::new()
+        }
+        .is_retryable());
+        assert!(!AsrError::AuthFailed {
+            message: String::new()
+        }
+        .is_retryable());
+    }
+
+    #[test]
+    fn test_http_status_mapping() {
+        let response = tungstenite::http::Response::builder()
+            .status(401)
+            .body(None)
+            .unwrap();
+        assert!(matches!(
+            AsrError::from(tungstenite::Error::Http(response)),
+            AsrError::AuthFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_error_event_serialization() {
+        let event = AsrErrorEvent {
+            error: AsrError::TaskFailed {
+                code: "InternalError".to_string(),
+                message: "server busy".to_string(),
+            },
+            retryable: true,
+            display: "识别任务失败".to_string(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "task_failed");
+        assert_eq!(json["code"], "InternalError");
+        assert_eq!(json["retryable"], true);
+    }
+}
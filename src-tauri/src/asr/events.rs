@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// 前端监听的事件名称
 pub const ASR_RESULT_EVENT: &str = "asr-result";
@@ -11,6 +11,16 @@ pub enum AsrResultKind {
     Translation,
 }
 
+/// 音频来源：并发捕获时用于区分说话方（麦克风为「我」，系统音频为「对方」）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioSource {
+    Microphone,
+    System,
+    /// 本地音视频文件（离线回放）
+    File,
+}
+
 /// 发送给前端的识别/翻译结果
 #[derive(Debug, Clone, Serialize)]
 pub struct AsrResultEvent {
@@ -23,4 +33,112 @@ pub struct AsrResultEvent {
     pub kind: AsrResultKind,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lang: Option<String>,
+    /// 所属捕获会话（实时识别时填充）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<AudioSource>,
+    /// 文件识别的扩展信息（说话人、字级时间戳），平铺到事件中
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<SentenceDetail>,
+}
+
+/// 句子的扩展信息（Fun-ASR 文件识别）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SentenceDetail {
+    /// 说话人编号（从 0 开始，开启说话人分离时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<u32>,
+    /// 所属音轨
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<WordTiming>,
+}
+
+/// 字/词级时间戳（毫秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub begin_time: u64,
+    pub end_time: u64,
+    pub text: String,
+    /// 紧随其后的标点
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub punctuation: String,
+}
+
+/// 结果来源标识：由识别会话统一写入每条结果
+#[derive(Debug, Clone, Default)]
+pub struct ResultOrigin {
+    pub session_id: Option<String>,
+    pub source: Option<AudioSource>,
+}
+
+impl ResultOrigin {
+    pub fn new(session_id: impl Into<String>, source: AudioSource) -> Self {
+        Self {
+            session_id: Some(session_id.into()),
+            source: Some(source),
+        }
+    }
+
+    /// 为结果写入来源标识
+    pub fn tag(&self, event: &mut AsrResultEvent) {
+        event.session_id.clone_from(&self.session_id);
+        event.source = self.source;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result() -> AsrResultEvent {
+        AsrResultEvent {
+            sentence_id: 1,
+            begin_time: 0,
+            end_time: None,
+            text: "你好".to_string(),
+            is_final: true,
+            kind: AsrResultKind::Transcription,
+            lang: None,
+            session_id: None,
+            source: None,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn test_origin_tag_serialization() {
+        let untagged = serde_json::to_value(result()).unwrap();
+        assert!(untagged.get("session_id").is_none());
+        assert!(untagged.get("source").is_none());
+
+        let mut event = result();
+        ResultOrigin::new("mic", AudioSource::Microphone).tag(&mut event);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["session_id"], "mic");
+        assert_eq!(json["source"], "microphone");
+        assert!(json.get("speaker_id").is_none());
+    }
+
+    #[test]
+    fn test_detail_is_flattened() {
+        let mut event = result();
+        event.detail = Some(SentenceDetail {
+            speaker_id: Some(1),
+            channel_id: None,
+            words: vec![WordTiming {
+                begin_time: 0,
+                end_time: 300,
+                text: "你好".to_string(),
+                punctuation: "。".to_string(),
+            }],
+        });
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["speaker_id"], 1);
+        assert!(json.get("channel_id").is_none());
+        assert_eq!(json["words"][0]["end_time"], 300);
+        assert_eq!(json["words"][0]["punctuation"], "。");
+    }
 }
@@ -0,0 +1,370 @@
+// 云端文件识别任务：按阶段持久化到应用数据目录，推送 file-asr-progress 事件，支持取消；
+// 应用重启后未完成的任务从已保存的阶段继续（已提交的任务只恢复轮询，不会重新上传）
+use crate::app_state;
+use crate::asr::config::CloudAsrConfig;
+use anyhow::Context;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+/// 前端监听的进度事件名称
+pub const FILE_ASR_PROGRESS_EVENT: &str = "file-asr-progress";
+
+/// 已结束的任务记录保留时长
+const FINISHED_RETENTION_MS: i64 = 7 * 24 * 3600 * 1000;
+
+/// 任务阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStage {
+    /// 上传到对象存储（尚未提交识别）
+    Uploading,
+    /// 已提交 Fun-ASR，等待首次查询
+    Submitted,
+    /// 轮询任务状态
+    Polling,
+    /// 下载识别结果并写出文件
+    Downloading,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStage {
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// 持久化的任务记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileJob {
+    pub id: String,
+    pub input_path: String,
+    pub output_path: String,
+    /// 恢复任务所需的配置快照；API Key 和存储密钥不写入记录，恢复时取自当前配置
+    #[serde(serialize_with = "serialize_without_secrets")]
+    pub config: CloudAsrConfig,
+    pub stage: JobStage,
+    /// 对象存储中的临时对象，任务结束时删除
+    pub object_key: String,
+    /// 进行中的分片上传，取消或重启时据此中止
+    #[serde(default)]
+    pub upload_id: Option<String>,
+    #[serde(default)]
+    pub uploaded_bytes: u64,
+    #[serde(default)]
+    pub total_bytes: u64,
+    #[serde(default)]
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub transcription_url: Option<String>,
+    /// Fun-ASR 返回的任务状态（PENDING / RUNNING 等）
+    #[serde(default)]
+    pub task_status: Option<String>,
+    /// 已轮询次数（跨重启累计）
+    #[serde(default)]
+    pub polls: u32,
+    #[serde(default)]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl FileJob {
+    pub fn new(input_path: &Path, output_path: &Path, config: CloudAsrConfig) -> Self {
+        let id = Uuid::new_v4().to_string();
+        let ext = input_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("bin");
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            object_key: format!("vocosphere/tmp/{}.{}", id, ext),
+            id,
+            input_path: input_path.to_string_lossy().to_string(),
+            output_path: output_path.to_string_lossy().to_string(),
+            config,
+            stage: JobStage::Uploading,
+            upload_id: None,
+            uploaded_bytes: 0,
+            total_bytes: 0,
+            task_id: None,
+            transcription_url: None,
+            task_status: None,
+            polls: 0,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// 进入新阶段：保存记录并推送进度事件
+    pub fn advance(&mut self, stage: JobStage) {
+        self.stage = stage;
+        self.touch();
+    }
+
+    /// 阶段不变时的状态更新（如轮询次数），同样保存并推送
+    pub fn touch(&mut self) {
+        self.updated_at = chrono::Utc::now().timestamp_millis();
+        if let Some(store) = STORE.get() {
+            if let Err(err) = store.save(self) {
+                warn!("⚠️ 保存文件识别任务 {} 失败: {}", self.id, err);
+            }
+        }
+        let payload = FileJobProgress::from(&*self);
+        if let Err(err) = app_state::emit_event(FILE_ASR_PROGRESS_EVENT, &payload) {
+            warn!("⚠️ 推送文件识别进度失败: {}", err);
+        }
+    }
+}
+
+fn serialize_without_secrets<S: serde::Serializer>(
+    config: &CloudAsrConfig,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    config.without_secrets().serialize(serializer)
+}
+
+/// file-asr-progress 事件负载（不含配置）
+#[derive(Debug, Clone, Serialize)]
+pub struct FileJobProgress {
+    pub job_id: String,
+    pub stage: JobStage,
+    pub input_path: String,
+    pub output_path: String,
+    /// 上传进度（字节）
+    pub uploaded_bytes: u64,
+    pub total_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_status: Option<String>,
+    pub polls: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&FileJob> for FileJobProgress {
+    fn from(job: &FileJob) -> Self {
+        Self {
+            job_id: job.id.clone(),
+            stage: job.stage,
+            input_path: job.input_path.clone(),
+            output_path: job.output_path.clone(),
+            uploaded_bytes: job.uploaded_bytes,
+            total_bytes: job.total_bytes,
+            task_status: job.task_status.clone(),
+            polls: job.polls,
+            error: job.error.clone(),
+        }
+    }
+}
+
+/// 任务记录目录：每个任务一个 {id}.json
+pub struct FileJobStore {
+    dir: PathBuf,
+}
+
+impl FileJobStore {
+    pub fn new(dir: PathBuf) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("无法创建任务目录 {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// 先写临时文件再重命名，避免中途退出留下半个 JSON
+    pub fn save(&self, job: &FileJob) -> anyhow::Result<()> {
+        let path = self.dir.join(format!("{}.json", job.id));
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(job)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// 读取全部未完成的任务，顺带清理过期的已结束记录
+    pub fn load_pending(&self) -> anyhow::Result<Vec<FileJob>> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut pending = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let job: FileJob = match std::fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| Ok(serde_json::from_slice(&data)?))
+            {
+                Ok(job) => job,
+                Err(err) => {
+                    warn!("⚠️ 跳过无法解析的任务记录 {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+            if !job.stage.is_finished() {
+                pending.push(job);
+            } else if now - job.updated_at > FINISHED_RETENTION_MS {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+        pending.sort_by_key(|j| j.created_at);
+        Ok(pending)
+    }
+}
+
+static STORE: OnceLock<FileJobStore> = OnceLock::new();
+
+/// 运行中任务的取消信号
+static RUNNING: Mutex<BTreeMap<String, watch::Sender<bool>>> = Mutex::new(BTreeMap::new());
+
+/// 前端当前的云端配置，恢复的任务据此补全密钥
+static CREDENTIALS: Mutex<Option<CloudAsrConfig>> = Mutex::new(None);
+
+/// 启用持久化（应用启动时调用），返回需要恢复的任务
+pub fn init(dir: PathBuf) -> anyhow::Result<Vec<FileJob>> {
+    let store = FileJobStore::new(dir)?;
+    let pending = store.load_pending()?;
+    let _ = STORE.set(store);
+    Ok(pending)
+}
+
+/// 记下当前配置中的密钥（前端启动后及加入云端任务时调用）
+pub fn set_credentials(config: &CloudAsrConfig) {
+    *lock_credentials() = Some(config.clone());
+}
+
+pub fn has_credentials() -> bool {
+    lock_credentials().is_some()
+}
+
+/// 用当前配置中的密钥补全配置；尚未收到前端配置时返回 false
+pub fn restore_credentials(config: &mut CloudAsrConfig) -> bool {
+    match lock_credentials().as_ref() {
+        Some(current) => {
+            config.restore_secrets(current);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 登记运行中的任务，返回取消信号
+pub fn register(job_id: &str) -> watch::Receiver<bool> {
+    let (tx, rx) = watch::channel(false);
+    lock_running().insert(job_id.to_string(), tx);
+    rx
+}
+
+pub fn unregister(job_id: &str) {
+    lock_running().remove(job_id);
+}
+
+/// 请求取消运行中的任务；任务不存在时返回 false
+pub fn cancel(job_id: &str) -> bool {
+    match lock_running().get(job_id) {
+        Some(tx) => {
+            info!("🛑 取消文件识别任务: {}", job_id);
+            tx.send_replace(true);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 等待取消信号（发送端被丢弃时永不返回）
+pub async fn cancelled(rx: &mut watch::Receiver<bool>) {
+    if rx.wait_for(|&c| c).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+fn lock_running() -> std::sync::MutexGuard<'static, BTreeMap<String, watch::Sender<bool>>> {
+    RUNNING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn lock_credentials() -> std::sync::MutexGuard<'static, Option<CloudAsrConfig>> {
+    CREDENTIALS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_loads_only_pending_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileJobStore::new(dir.path().join("jobs")).unwrap();
+
+        let mut polling = FileJob::new(
+            Path::new("/tmp/a.mp4"),
+            Path::new("/tmp/a.srt"),
+            CloudAsrConfig::default(),
+        );
+        polling.stage = JobStage::Polling;
+        polling.task_id = Some("task-1".to_string());
+        polling.polls = 12;
+        store.save(&polling).unwrap();
+
+        let mut done = FileJob::new(
+            Path::new("/tmp/b.wav"),
+            Path::new("/tmp/b.srt"),
+            CloudAsrConfig::default(),
+        );
+        done.stage = JobStage::Done;
+        store.save(&done).unwrap();
+
+        let pending = store.load_pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, polling.id);
+        assert_eq!(pending[0].task_id.as_deref(), Some("task-1"));
+        assert_eq!(pending[0].polls, 12);
+        assert!(pending[0].object_key.ends_with(".mp4"));
+    }
+
+    #[test]
+    fn test_saved_job_has_no_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileJobStore::new(dir.path().join("jobs")).unwrap();
+
+        let mut config = CloudAsrConfig {
+            file_asr_api_key: "sk-secret".to_string(),
+            ..Default::default()
+        };
+        config.oss.bucket = "my-bucket".to_string();
+        config.oss.access_key_id = "oss-id".to_string();
+        config.oss.access_key_secret = "oss-secret".to_string();
+        config.s3.secret_access_key = "s3-secret".to_string();
+        let job = FileJob::new(
+            Path::new("/tmp/a.mp4"),
+            Path::new("/tmp/a.srt"),
+            config.clone(),
+        );
+        store.save(&job).unwrap();
+
+        let raw = std::fs::read_to_string(dir.path().join("jobs").join(format!("{}.json", job.id)))
+            .unwrap();
+        for secret in ["sk-secret", "oss-id", "oss-secret", "s3-secret"] {
+            assert!(!raw.contains(secret), "{} 被写入了任务记录", secret);
+        }
+
+        let mut loaded = store.load_pending().unwrap().remove(0);
+        assert_eq!(loaded.config.oss.bucket, "my-bucket");
+        assert!(loaded.config.file_asr_api_key.is_empty());
+        loaded.config.restore_secrets(&config);
+        assert_eq!(loaded.config.file_asr_api_key, "sk-secret");
+        assert_eq!(loaded.config.oss.access_key_secret, "oss-secret");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_signals_registered_job() {
+        let mut rx = register("job-cancel");
+        assert!(!cancel("job-missing"));
+        assert!(cancel("job-cancel"));
+        cancelled(&mut rx).await;
+        unregister("job-cancel");
+        assert!(!cancel("job-cancel"));
+    }
+}
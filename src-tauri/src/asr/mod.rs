@@ -1,19 +1,33 @@
+pub mod batch;
 pub mod config;
+pub mod error;
 pub mod events;
+pub mod file_job;
 pub mod provider;
+pub mod session;
+pub mod storage;
 pub mod subtitle;
 pub mod websocket;
 
 use async_trait::async_trait;
+use error::AsrError;
+use session::AsrSession;
 use std::path::Path;
 use tokio::sync::mpsc;
 
 #[async_trait]
 pub trait AsrProvider: Send + Sync {
     /// 流式识别：消费音频 channel，实时推送 asr-result 事件到前端
-    async fn recognize_stream(&self, rx: mpsc::Receiver<Vec<f32>>) -> anyhow::Result<()>;
+    /// sample_rate 为 rx 中单声道音频的采样率；origin 会写入每条结果，用于区分并发的多个捕获会话
+    /// 返回可停止的会话句柄；启动失败时返回结构化错误（同时已通过 asr-error 事件推送到前端）
+    async fn recognize_stream(
+        &self,
+        rx: mpsc::Receiver<Vec<f32>>,
+        sample_rate: u32,
+        origin: events::ResultOrigin,
+    ) -> Result<AsrSession, AsrError>;
 
-    /// 文件识别：上传 OSS → Fun-ASR → 写 SRT → 返回完整结果
+    /// 文件识别：上传对象存储 → Fun-ASR → 写 SRT/JSON → 返回完整结果（云端以可恢复任务执行）
     async fn recognize_file(
         &self,
         input_path: &Path,
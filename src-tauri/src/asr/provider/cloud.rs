@@ -1,21 +1,20 @@
-use crate::asr::config::{CloudAsrConfig, OssConfig};
-use crate::asr::events::{AsrResultEvent, AsrResultKind};
-use crate::asr::subtitle::srt;
+use crate::asr::config::{CloudAsrConfig, FileAsrConfig};
+use crate::asr::error::AsrError;
+use crate::asr::events::{AsrResultEvent, AsrResultKind, ResultOrigin, SentenceDetail, WordTiming};
+use crate::asr::file_job::{self, FileJob, JobStage};
+use crate::asr::session::AsrSession;
+use crate::asr::storage::{self, ObjectStore, UploadEvent};
+use crate::asr::subtitle;
 use crate::asr::websocket;
 use crate::asr::AsrProvider;
 use anyhow::Context;
 use async_trait::async_trait;
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use hmac::{Hmac, Mac};
 use log::{info, warn};
 use reqwest::Client;
 use serde::Deserialize;
-use sha1::Sha1;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::sync::mpsc;
-use uuid::Uuid;
-
-type HmacSha1 = Hmac<Sha1>;
 
 pub struct CloudAsrProvider {
     config: CloudAsrConfig,
@@ -29,9 +28,19 @@ impl CloudAsrProvider {
 
 #[async_trait]
 impl AsrProvider for CloudAsrProvider {
-    async fn recognize_stream(&self, rx: mpsc::Receiver<Vec<f32>>) -> anyhow::Result<()> {
-        websocket::start_asr_with_config(Some(rx), self.config.streaming.clone()).await;
-        Ok(())
+    async fn recognize_stream(
+        &self,
+        rx: mpsc::Receiver<Vec<f32>>,
+        sample_rate: u32,
+        origin: ResultOrigin,
+    ) -> Result<AsrSession, AsrError> {
+        websocket::start_asr_with_config(
+            Some(rx),
+            sample_rate,
+            self.config.streaming.clone(),
+            origin,
+        )
+        .await
     }
 
     async fn recognize_file(
@@ -39,166 +48,166 @@ impl AsrProvider for CloudAsrProvider {
         input_path: &Path,
         output_path: &Path,
     ) -> anyhow::Result<Vec<AsrResultEvent>> {
-        let ext = input_path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("bin");
-        let object_key = format!("vocosphere/tmp/{}.{}", Uuid::new_v4(), ext);
-
-        let data = std::fs::read(input_path)
-            .with_context(|| format!("无法读取文件: {}", input_path.display()))?;
-
-        info!("上传文件到 OSS: {}", object_key);
-        let oss = OssClient::new(&self.config.oss);
-        let public_url = oss
-            .upload(data, &object_key, "application/octet-stream")
-            .await?;
-        info!("文件上传完成: {}", public_url);
-
-        let fun_asr = FunAsrClient::new(self.config.file_asr_api_key.clone());
-        let task_id = fun_asr.submit_task(&public_url).await?;
-        info!("Fun-ASR 任务已提交: {}", task_id);
-
-        let transcription_url = fun_asr.wait_for_result(&task_id).await?;
-        info!("Fun-ASR 任务完成，结果 URL: {}", transcription_url);
-
-        let sentences = fun_asr.download_transcription(&transcription_url).await?;
-        info!("识别完成，共 {} 条句子", sentences.len());
-
-        let events: Vec<AsrResultEvent> = sentences
-            .iter()
-            .enumerate()
-            .map(|(idx, s)| AsrResultEvent {
-                sentence_id: idx as u32,
-                begin_time: s.begin_time,
-                end_time: Some(s.end_time),
-                text: s.text.clone(),
-                is_final: true,
-                kind: AsrResultKind::Transcription,
-                lang: None,
-            })
-            .collect();
-
-        srt::write_srt(&events, output_path)?;
-        info!("SRT 文件已写出: {}", output_path.display());
-
-        if let Err(e) = oss.delete(&object_key).await {
-            warn!("删除 OSS 临时文件失败（不影响结果）: {}", e);
-        }
-
-        Ok(events)
+        let job = FileJob::new(input_path, output_path, self.config.clone());
+        info!("创建文件识别任务: {}", job.id);
+        run_job(job).await
     }
 }
 
-// ── OSS 客户端 ──────────────────────────────────────────────────────────────
+// ── 文件识别任务 ────────────────────────────────────────────────────────────
 
-struct OssClient<'a> {
-    config: &'a OssConfig,
-    client: Client,
+/// 执行或恢复文件识别任务：从记录的阶段继续，结束时删除存储中的临时文件
+pub async fn run_job(mut job: FileJob) -> anyhow::Result<Vec<AsrResultEvent>> {
+    let store = match storage::build(&job.config) {
+        Ok(store) => store,
+        Err(e) => {
+            job.error = Some(e.to_string());
+            job.advance(JobStage::Failed);
+            return Err(e);
+        }
+    };
+    let fun_asr = FunAsrClient::new(job.config.file_asr_api_key.clone());
+    execute_job(job, store.as_ref(), &fun_asr).await
 }
 
-impl<'a> OssClient<'a> {
-    fn new(config: &'a OssConfig) -> Self {
-        Self {
-            config,
-            client: Client::new(),
+async fn execute_job(
+    mut job: FileJob,
+    store: &dyn ObjectStore,
+    fun_asr: &FunAsrClient,
+) -> anyhow::Result<Vec<AsrResultEvent>> {
+    let mut cancel = file_job::register(&job.id);
+    job.touch();
+
+    let result = tokio::select! {
+        result = drive_job(&mut job, store, fun_asr) => result,
+        _ = file_job::cancelled(&mut cancel) => Err(anyhow::anyhow!("文件识别任务已取消")),
+    };
+    file_job::unregister(&job.id);
+
+    let cancelled = *cancel.borrow();
+    if let Some(upload_id) = job.upload_id.take() {
+        if let Err(e) = store.abort_upload(&job.object_key, &upload_id).await {
+            warn!("中止分片上传失败: {}", e);
         }
     }
+    if cancelled {
+        if let Some(task_id) = &job.task_id {
+            if let Err(e) = fun_asr.cancel_task(task_id).await {
+                warn!("取消 Fun-ASR 任务失败（任务可能已在运行）: {}", e);
+            }
+        }
+    }
+    if let Err(e) = store.delete(&job.object_key).await {
+        warn!("删除存储中的临时文件失败（不影响结果）: {}", e);
+    }
 
-    async fn upload(
-        &self,
-        data: Vec<u8>,
-        object_key: &str,
-        content_type: &str,
-    ) -> anyhow::Result<String> {
-        let date = chrono::Utc::now()
-            .format("%a, %d %b %Y %H:%M:%S GMT")
-            .to_string();
-        let signature = sign_oss_put(
-            &self.config.access_key_secret,
-            content_type,
-            &date,
-            &self.config.bucket,
-            object_key,
-        );
-        let auth = format!("OSS {}:{}", self.config.access_key_id, signature);
-        let url = format!(
-            "https://{}.{}/{}",
-            self.config.bucket, self.config.endpoint, object_key
-        );
-
-        let resp = self
-            .client
-            .put(&url)
-            .header("Date", &date)
-            .header("Content-Type", content_type)
-            .header("x-oss-object-acl", "public-read")
-            .header("Authorization", auth)
-            .body(data)
-            .send()
-            .await
-            .context("OSS PUT 请求失败")?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("OSS 上传失败: HTTP {} — {}", status, body);
+    match &result {
+        Ok(_) => job.advance(JobStage::Done),
+        Err(_) if cancelled => job.advance(JobStage::Cancelled),
+        Err(e) => {
+            warn!("文件识别任务 {} 失败: {:#}", job.id, e);
+            job.error = Some(e.to_string());
+            job.advance(JobStage::Failed);
         }
-        Ok(url)
     }
+    result
+}
 
-    async fn delete(&self, object_key: &str) -> anyhow::Result<()> {
-        let date = chrono::Utc::now()
-            .format("%a, %d %b %Y %H:%M:%S GMT")
-            .to_string();
-        let resource = format!("/{}/{}", self.config.bucket, object_key);
-        let string_to_sign = format!("DELETE\n\n\n{}\n{}", date, resource);
-        let mut mac = HmacSha1::new_from_slice(self.config.access_key_secret.as_bytes())
-            .expect("valid key");
-        mac.update(string_to_sign.as_bytes());
-        let sig = BASE64.encode(mac.finalize().into_bytes());
-        let auth = format!("OSS {}:{}", self.config.access_key_id, sig);
-        let url = format!(
-            "https://{}.{}/{}",
-            self.config.bucket, self.config.endpoint, object_key
-        );
+async fn drive_job(
+    job: &mut FileJob,
+    store: &dyn ObjectStore,
+    fun_asr: &FunAsrClient,
+) -> anyhow::Result<Vec<AsrResultEvent>> {
+    if job.stage == JobStage::Uploading {
+        // 上次退出时未完成的分片上传无法续传，中止后重新上传
+        if let Some(upload_id) = job.upload_id.take() {
+            if let Err(e) = store.abort_upload(&job.object_key, &upload_id).await {
+                warn!("中止上次的分片上传失败: {}", e);
+            }
+        }
 
-        let resp = self
-            .client
-            .delete(&url)
-            .header("Date", &date)
-            .header("Authorization", auth)
-            .send()
-            .await
-            .context("OSS DELETE 请求失败")?;
+        info!("上传文件到对象存储: {}", job.object_key);
+        let input_path = PathBuf::from(&job.input_path);
+        let object_key = job.object_key.clone();
+        store
+            .upload(&input_path, &object_key, &mut |event| match event {
+                UploadEvent::Initiated(upload_id) => {
+                    job.upload_id = Some(upload_id.to_string());
+                    job.touch();
+                }
+                UploadEvent::Progress { uploaded, total } => {
+                    job.uploaded_bytes = uploaded;
+                    job.total_bytes = total;
+                    job.touch();
+                }
+            })
+            .await?;
+        job.upload_id = None;
+        info!("文件上传完成: {}", object_key);
 
-        if !resp.status().is_success() && resp.status().as_u16() != 404 {
-            let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("OSS 删除失败: HTTP {} — {}", status, body);
-        }
-        Ok(())
+        let file_url = store.presign(&object_key).await?;
+        let task_id = fun_asr.submit_task(&file_url, &job.config.file_asr).await?;
+        info!("Fun-ASR 任务已提交: {}", task_id);
+        job.task_id = Some(task_id);
+        job.advance(JobStage::Submitted);
     }
-}
 
-/// OSS v1 签名：PUT 含 x-oss-object-acl:public-read
-fn sign_oss_put(
-    access_key_secret: &str,
-    content_type: &str,
-    date: &str,
-    bucket: &str,
-    object_key: &str,
-) -> String {
-    let canonicalized_headers = "x-oss-object-acl:public-read\n";
-    let canonicalized_resource = format!("/{}/{}", bucket, object_key);
-    let string_to_sign = format!(
-        "PUT\n\n{}\n{}\n{}{}",
-        content_type, date, canonicalized_headers, canonicalized_resource
-    );
-    let mut mac =
-        HmacSha1::new_from_slice(access_key_secret.as_bytes()).expect("HMAC key valid");
-    mac.update(string_to_sign.as_bytes());
-    BASE64.encode(mac.finalize().into_bytes())
+    if matches!(job.stage, JobStage::Submitted | JobStage::Polling) {
+        let task_id = job.task_id.clone().context("任务记录缺少 task_id")?;
+        // 查询请求失败（网络抖动、服务端 5xx）不影响已提交的任务，按指数退避重试，同样计入轮询次数
+        let mut failures = 0u32;
+        let mut last_error: Option<anyhow::Error> = None;
+        let transcription_url = loop {
+            if job.polls >= POLL_MAX_RETRIES {
+                let message = format!("Fun-ASR 任务超时（超过 {} 次轮询）", POLL_MAX_RETRIES);
+                return Err(match last_error {
+                    Some(e) => e.context(message),
+                    None => anyhow::anyhow!(message),
+                });
+            }
+            let backoff = 2u32.pow(failures.min(POLL_BACKOFF_MAX_EXP));
+            tokio::time::sleep(fun_asr.poll_interval * backoff).await;
+            job.polls += 1;
+
+            match fun_asr.query_task(&task_id).await {
+                Ok(TaskState::Succeeded(url)) => break url,
+                Ok(TaskState::Failed { code, message }) => {
+                    anyhow::bail!("Fun-ASR 任务失败: {} ({})", message, code)
+                }
+                Ok(TaskState::Running(status)) => {
+                    info!("Fun-ASR 任务状态: {}（第 {} 次轮询）", status, job.polls);
+                    failures = 0;
+                    last_error = None;
+                    job.task_status = Some(status);
+                    job.advance(JobStage::Polling);
+                }
+                Err(e) => {
+                    failures += 1;
+                    warn!(
+                        "⚠️ Fun-ASR 查询任务失败（第 {} 次轮询），稍后重试: {:#}",
+                        job.polls, e
+                    );
+                    last_error = Some(e);
+                    job.touch();
+                }
+            }
+        };
+        info!("Fun-ASR 任务完成，结果 URL: {}", transcription_url);
+        job.transcription_url = Some(transcription_url);
+        job.advance(JobStage::Downloading);
+    }
+
+    let transcription_url = job
+        .transcription_url
+        .as_deref()
+        .context("任务记录缺少 transcription_url")?;
+    let transcription = fun_asr.download_transcription(transcription_url).await?;
+    let events = transcription_to_events(transcription);
+    info!("识别完成，共 {} 条句子", events.len());
+
+    subtitle::write_transcript(&events, Path::new(&job.output_path))?;
+    info!("识别结果已写出: {}", job.output_path);
+    Ok(events)
 }
 
 // ── Fun-ASR 客户端 ──────────────────────────────────────────────────────────
@@ -206,9 +215,13 @@ fn sign_oss_put(
 const FUNASR_API_BASE: &str = "https://dashscope.aliyuncs.com/api/v1";
 const POLL_INTERVAL_SECS: u64 = 3;
 const POLL_MAX_RETRIES: u32 = 200;
+/// 查询连续失败时轮询间隔最多放大到 2^5 倍
+const POLL_BACKOFF_MAX_EXP: u32 = 5;
 
 struct FunAsrClient {
     api_key: String,
+    base: String,
+    poll_interval: Duration,
     client: Client,
 }
 
@@ -216,22 +229,29 @@ impl FunAsrClient {
     fn new(api_key: String) -> Self {
         Self {
             api_key,
+            base: FUNASR_API_BASE.to_string(),
+            poll_interval: Duration::from_secs(POLL_INTERVAL_SECS),
             client: Client::new(),
         }
     }
 
-    async fn submit_task(&self, file_url: &str) -> anyhow::Result<String> {
+    async fn submit_task(&self, file_url: &str, config: &FileAsrConfig) -> anyhow::Result<String> {
         let body = serde_json::json!({
             "model": "fun-asr",
             "input": { "file_urls": [file_url] },
-            "parameters": {}
+            "parameters": config.parameters()
         });
 
-        let resp = self
+        let mut request = self
             .client
-            .post(format!("{}/services/audio/asr/transcription", FUNASR_API_BASE))
+            .post(format!("{}/services/audio/asr/transcription", self.base))
             .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("X-DashScope-Async", "enable")
+            .header("X-DashScope-Async", "enable");
+        // DashScope 临时存储的 oss:// 地址需要服务端解析
+        if file_url.starts_with("oss://") {
+            request = request.header("X-DashScope-OssResourceResolve", "enable");
+        }
+        let resp = request
             .json(&body)
             .send()
             .await
@@ -247,46 +267,44 @@ impl FunAsrClient {
         Ok(result.output.task_id)
     }
 
-    async fn wait_for_result(&self, task_id: &str) -> anyhow::Result<String> {
-        for attempt in 0..POLL_MAX_RETRIES {
-            tokio::time::sleep(tokio::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
-
-            let resp = self
-                .client
-                .get(format!("{}/tasks/{}", FUNASR_API_BASE, task_id))
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .send()
-                .await
-                .context("Fun-ASR 查询任务请求失败")?;
-
-            if !resp.status().is_success() {
-                let status = resp.status();
-                let text = resp.text().await.unwrap_or_default();
-                anyhow::bail!("Fun-ASR 查询失败: HTTP {} — {}", status, text);
-            }
+    async fn query_task(&self, task_id: &str) -> anyhow::Result<TaskState> {
+        let resp = self
+            .client
+            .get(format!("{}/tasks/{}", self.base, task_id))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .context("Fun-ASR 查询任务请求失败")?;
 
-            let result: TaskQueryResponse = resp.json().await.context("解析查询响应失败")?;
-
-            match result.output.task_status.as_str() {
-                "SUCCEEDED" => {
-                    let url = result
-                        .output
-                        .results
-                        .and_then(|r| r.into_iter().find(|r| r.subtask_status == "SUCCEEDED"))
-                        .map(|r| r.transcription_url)
-                        .context("SUCCEEDED 但未找到 transcription_url")?;
-                    return Ok(url);
-                }
-                "FAILED" => anyhow::bail!("Fun-ASR 任务失败"),
-                status => {
-                    info!("Fun-ASR 任务状态: {}（第 {} 次轮询）", status, attempt + 1);
-                }
-            }
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Fun-ASR 查询失败: HTTP {} — {}", status, text);
         }
-        anyhow::bail!("Fun-ASR 任务超时（超过 {} 次轮询）", POLL_MAX_RETRIES)
+
+        let result: TaskQueryResponse = resp.json().await.context("解析查询响应失败")?;
+        task_state(result.output)
     }
 
-    async fn download_transcription(&self, url: &str) -> anyhow::Result<Vec<FunAsrSentence>> {
+    /// 取消排队中的任务（已开始运行的任务无法取消）
+    async fn cancel_task(&self, task_id: &str) -> anyhow::Result<()> {
+        let resp = self
+            .client
+            .post(format!("{}/tasks/{}/cancel", self.base, task_id))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .context("Fun-ASR 取消任务请求失败")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Fun-ASR 取消失败: HTTP {} — {}", status, text);
+        }
+        Ok(())
+    }
+
+    async fn download_transcription(&self, url: &str) -> anyhow::Result<TranscriptionResponse> {
         let resp = self
             .client
             .get(url)
@@ -298,15 +316,82 @@ impl FunAsrClient {
             anyhow::bail!("下载识别结果失败: HTTP {}", resp.status());
         }
 
-        let result: TranscriptionResponse =
-            resp.json().await.context("解析识别结果 JSON 失败")?;
+        resp.json().await.context("解析识别结果 JSON 失败")
+    }
+}
 
-        Ok(result
-            .transcripts
-            .into_iter()
-            .next()
-            .map(|t| t.sentences)
-            .unwrap_or_default())
+/// 识别结果 → 事件：合并所有音轨的句子并按开始时间排序，保留说话人和字级时间戳
+fn transcription_to_events(transcription: TranscriptionResponse) -> Vec<AsrResultEvent> {
+    let mut sentences: Vec<(Option<u32>, FunAsrSentence)> = transcription
+        .transcripts
+        .into_iter()
+        .flat_map(|t| {
+            let channel_id = t.channel_id;
+            t.sentences.into_iter().map(move |s| (channel_id, s))
+        })
+        .collect();
+    sentences.sort_by_key(|(_, s)| s.begin_time);
+
+    sentences
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (channel_id, s))| AsrResultEvent {
+            sentence_id: idx as u32,
+            begin_time: s.begin_time,
+            end_time: Some(s.end_time),
+            text: s.text,
+            is_final: true,
+            kind: AsrResultKind::Transcription,
+            lang: None,
+            session_id: None,
+            source: None,
+            detail: Some(SentenceDetail {
+                speaker_id: s.speaker_id,
+                channel_id,
+                words: s.words,
+            }),
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum TaskState {
+    Running(String),
+    Succeeded(String),
+    Failed { code: String, message: String },
+}
+
+/// 解析查询结果；失败原因取自 output.code / output.message，缺省时取第一个失败的子任务。
+/// 子任务全部失败时任务状态仍是 SUCCEEDED，同样按失败处理
+fn task_state(output: TaskQueryOutput) -> anyhow::Result<TaskState> {
+    let results = output.results.unwrap_or_default();
+    let failed = |code: Option<String>, message: Option<String>| {
+        let subtask = results.iter().find(|r| r.subtask_status == "FAILED");
+        TaskState::Failed {
+            code: code
+                .or_else(|| subtask.and_then(|r| r.code.clone()))
+                .unwrap_or_else(|| "Unknown".to_string()),
+            message: message
+                .or_else(|| subtask.and_then(|r| r.message.clone()))
+                .unwrap_or_default(),
+        }
+    };
+    match output.task_status.as_str() {
+        "SUCCEEDED" => {
+            let url = results
+                .iter()
+                .find(|r| r.subtask_status == "SUCCEEDED")
+                .and_then(|r| r.transcription_url.clone());
+            match url {
+                Some(url) => Ok(TaskState::Succeeded(url)),
+                None if results.iter().any(|r| r.subtask_status == "FAILED") => {
+                    Ok(failed(None, None))
+                }
+                None => anyhow::bail!("SUCCEEDED 但未找到 transcription_url"),
+            }
+        }
+        "FAILED" => Ok(failed(output.code, output.message)),
+        status => Ok(TaskState::Running(status.to_string())),
     }
 }
 
@@ -328,11 +413,21 @@ struct TaskQueryResponse {
 struct TaskQueryOutput {
     task_status: String,
     results: Option<Vec<TaskResult>>,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
 }
 #[derive(Deserialize)]
 struct TaskResult {
-    transcription_url: String,
+    /// 失败的子任务没有结果地址
+    #[serde(default)]
+    transcription_url: Option<String>,
     subtask_status: String,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
 }
 #[derive(Deserialize)]
 struct TranscriptionResponse {
@@ -340,6 +435,8 @@ struct TranscriptionResponse {
 }
 #[derive(Deserialize)]
 struct Transcript {
+    #[serde(default)]
+    channel_id: Option<u32>,
     sentences: Vec<FunAsrSentence>,
 }
 #[derive(Deserialize)]
@@ -347,43 +444,148 @@ struct FunAsrSentence {
     text: String,
     begin_time: u64,
     end_time: u64,
+    #[serde(default)]
+    speaker_id: Option<u32>,
+    #[serde(default)]
+    words: Vec<WordTiming>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::asr::config::LocalHttpConfig;
+    use crate::asr::storage::local::LocalHttpStore;
+    use crate::asr::storage::mock_http::{self, Reply};
+    use std::sync::{Arc, Mutex};
 
     #[test]
-    fn test_sign_oss_put_is_base64() {
-        let sig = sign_oss_put(
-            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
-            "application/octet-stream",
-            "Thu, 01 Jan 2026 00:00:00 GMT",
-            "my-bucket",
-            "vocosphere/tmp/test.wav",
-        );
-        assert!(!sig.is_empty());
-        assert!(sig
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '+' || c == '/' || c == '='));
+    fn test_transcription_to_events_keeps_speakers_and_words() {
+        let transcription: TranscriptionResponse = serde_json::from_str(
+            r#"{
+                "transcripts": [
+                    {"channel_id": 0, "sentences": [
+                        {"begin_time": 3000, "end_time": 4200, "text": "第二句。", "speaker_id": 1,
+                         "words": [{"begin_time": 3000, "end_time": 4200, "text": "第二句", "punctuation": "。"}]},
+                        {"begin_time": 100, "end_time": 2500, "text": "你好，世界。", "speaker_id": 0,
+                         "words": [
+                            {"begin_time": 100, "end_time": 900, "text": "你好", "punctuation": "，"},
+                            {"begin_time": 900, "end_time": 2500, "text": "世界", "punctuation": "。"}
+                         ]}
+                    ]},
+                    {"channel_id": 1, "sentences": [
+                        {"begin_time": 2600, "end_time": 2900, "text": "嗯"}
+                    ]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let events = transcription_to_events(transcription);
+        let texts: Vec<&str> = events.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, ["你好，世界。", "嗯", "第二句。"]);
+        assert_eq!(events[2].sentence_id, 2);
+
+        let first = events[0].detail.as_ref().unwrap();
+        assert_eq!(first.speaker_id, Some(0));
+        assert_eq!(first.channel_id, Some(0));
+        assert_eq!(first.words.len(), 2);
+        assert_eq!(first.words[1].begin_time, 900);
+
+        let second = events[1].detail.as_ref().unwrap();
+        assert_eq!(second.speaker_id, None);
+        assert_eq!(second.channel_id, Some(1));
+        assert!(second.words.is_empty());
     }
 
     #[test]
-    fn test_sign_oss_put_deterministic() {
-        let sig1 = sign_oss_put(
-            "secret",
-            "audio/wav",
-            "Mon, 01 Jan 2024 12:00:00 GMT",
-            "bucket",
-            "key.wav",
+    fn test_task_state_reports_failure_details() {
+        let parse = |json: &str| task_state(serde_json::from_str(json).unwrap()).unwrap();
+
+        assert_eq!(
+            parse(
+                r#"{"task_status":"FAILED","code":"InvalidFile.DecodeFailed","message":"audio decode failed"}"#
+            ),
+            TaskState::Failed {
+                code: "InvalidFile.DecodeFailed".to_string(),
+                message: "audio decode failed".to_string(),
+            }
+        );
+
+        // 子任务全部失败时报告子任务的错误，而不是找不到 transcription_url
+        assert_eq!(
+            parse(
+                r#"{"task_status":"SUCCEEDED","results":[{"file_url":"http://a/b.wav","subtask_status":"FAILED","code":"InvalidFile.DownloadFailed","message":"download failed"}]}"#
+            ),
+            TaskState::Failed {
+                code: "InvalidFile.DownloadFailed".to_string(),
+                message: "download failed".to_string(),
+            }
         );
-        let sig2 = sign_oss_put(
-            "secret",
-            "audio/wav",
-            "Mon, 01 Jan 2024 12:00:00 GMT",
-            "bucket",
-            "key.wav",
+    }
+
+    #[tokio::test]
+    async fn test_file_job_runs_offline_against_mocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("talk.wav");
+        std::fs::write(&input, b"RIFF").unwrap();
+        let output = dir.path().join("talk.srt");
+
+        let submitted = Arc::new(Mutex::new(None::<String>));
+        let polls = Arc::new(Mutex::new(0));
+        let (recorded, counter) = (submitted.clone(), polls.clone());
+        let base = mock_http::serve(move |req| match (req.method.as_str(), req.path.as_str()) {
+            ("POST", "/services/audio/asr/transcription") => {
+                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                *recorded.lock().unwrap() = body["input"]["file_urls"][0].as_str().map(str::to_string);
+                Reply::ok(r#"{"output":{"task_id":"task-1","task_status":"PENDING"}}"#)
+            }
+            ("GET", "/tasks/task-1") => {
+                let mut polls = counter.lock().unwrap();
+                *polls += 1;
+                if *polls == 1 {
+                    // 偶发的查询失败只重试，不影响任务
+                    Reply::status(503)
+                } else if *polls == 2 {
+                    Reply::ok(r#"{"output":{"task_status":"RUNNING"}}"#)
+                } else {
+                    let host = req.header("Host").unwrap();
+                    Reply::ok(format!(
+                        r#"{{"output":{{"task_status":"SUCCEEDED","results":[{{"subtask_status":"SUCCEEDED","transcription_url":"http://{}/result.json"}}]}}}}"#,
+                        host
+                    ))
+                }
+            }
+            ("GET", "/result.json") => Reply::ok(
+                r#"{"transcripts":[{"channel_id":0,"sentences":[{"begin_time":0,"end_time":1500,"text":"离线测试。","speaker_id":0}]}]}"#,
+            ),
+            _ => Reply::status(404),
+        })
+        .await;
+
+        let store = LocalHttpStore::new(LocalHttpConfig {
+            directory: dir.path().join("www").to_string_lossy().to_string(),
+            base_url: "http://files.local/asr".to_string(),
+        });
+        let fun_asr = FunAsrClient {
+            base,
+            poll_interval: Duration::from_millis(10),
+            ..FunAsrClient::new("sk-test".to_string())
+        };
+        let job = FileJob::new(&input, &output, CloudAsrConfig::default());
+        let object_key = job.object_key.clone();
+
+        let events = execute_job(job, &store, &fun_asr).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].text, "离线测试。");
+        assert_eq!(
+            submitted.lock().unwrap().as_deref(),
+            Some(format!("http://files.local/asr/{}", object_key).as_str())
         );
-        assert_eq!(sig1, sig2);
+        assert_eq!(*polls.lock().unwrap(), 3);
+        assert!(std::fs::read_to_string(&output)
+            .unwrap()
+            .contains("离线测试。"));
+        // 结束后临时文件已删除
+        assert!(!dir.path().join("www").join(&object_key).exists());
     }
 }
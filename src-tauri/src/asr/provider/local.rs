@@ -1,5 +1,9 @@
-use crate::asr::config::LocalAsrConfig;
-use crate::asr::events::{AsrResultEvent, AsrResultKind};
+use crate::asr::config::{LocalAsrConfig, LocalModelConfig};
+use crate::asr::error::AsrError;
+use crate::asr::events::{AsrResultEvent, AsrResultKind, ResultOrigin};
+use crate::asr::session::AsrSession;
+use crate::asr::subtitle;
+use crate::asr::provider::local_stream::{self, Segment, WhisperCli};
 use crate::asr::AsrProvider;
 use crate::app_state;
 use async_trait::async_trait;
@@ -37,18 +41,91 @@ struct WhisperOffsets {
     to: u64,
 }
 
+/// whisper-cli 命令行参数（输入 16kHz 单声道 WAV，输出 JSON 到 <out_prefix>.json）
+pub(super) fn whisper_cli_args(
+    rec: &LocalModelConfig,
+    wav_path: &str,
+    out_prefix: &str,
+) -> Vec<String> {
+    let lang_arg = if rec.language == "auto" { "auto".to_string() } else { rec.language.clone() };
+    [
+        "-m", &rec.model_path,
+        "-f", wav_path,
+        "-l", &lang_arg,
+        "-p", &rec.n_threads.to_string(),
+        "-oj",
+        "-of", out_prefix,
+        "--no-timestamps", "false",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// 读取并解析 whisper-cli 生成的 <out_prefix>.json
+pub(super) async fn read_whisper_json(out_prefix: &str) -> anyhow::Result<Vec<Segment>> {
+    let json_path = format!("{out_prefix}.json");
+    let json_bytes = tokio::fs::read(&json_path)
+        .await
+        .map_err(|e| anyhow::anyhow!("读取 whisper-cli 输出失败: {json_path}: {e}"))?;
+
+    let parsed: WhisperOutput = serde_json::from_slice(&json_bytes)
+        .map_err(|e| anyhow::anyhow!("解析 whisper-cli JSON 失败: {e}"))?;
+
+    Ok(parsed
+        .transcription
+        .into_iter()
+        .map(|seg| Segment {
+            from_ms: seg.offsets.from,
+            to_ms: seg.offsets.to,
+            text: seg.text,
+        })
+        .collect())
+}
+
 // ── Provider 实现 ─────────────────────────────────────────────────────────────
 
 #[async_trait]
 impl AsrProvider for LocalAsrProvider {
-    async fn recognize_stream(&self, _rx: mpsc::Receiver<Vec<f32>>) -> anyhow::Result<()> {
-        anyhow::bail!("本地 ASR 暂不支持流式识别，请切换至云端模式")
+    async fn recognize_stream(
+        &self,
+        rx: mpsc::Receiver<Vec<f32>>,
+        sample_rate: u32,
+        origin: ResultOrigin,
+    ) -> Result<AsrSession, AsrError> {
+        let rec = &self.config.recognition;
+        if rec.model_path.is_empty() {
+            let err = AsrError::Config {
+                message: "请先在「模型」页配置本地语音识别模型路径".to_string(),
+            };
+            err.emit();
+            return Err(err);
+        }
+        if sample_rate as u64 != local_stream::SAMPLE_RATE {
+            let err = AsrError::Config {
+                message: format!(
+                    "本地识别要求 {}Hz 音频，当前采集采样率为 {}Hz",
+                    local_stream::SAMPLE_RATE,
+                    sample_rate
+                ),
+            };
+            err.emit();
+            return Err(err);
+        }
+
+        Ok(local_stream::start(
+            WhisperCli::sidecar(rec.clone()),
+            &self.config.streaming,
+            Some(rec.language.clone()),
+            rx,
+            origin,
+        ))
     }
 
     async fn recognize_file(
         &self,
         input_path: &Path,
-        _output_path: &Path,
+        output_path: &Path,
     ) -> anyhow::Result<Vec<AsrResultEvent>> {
         let app = app_state::get_app_handle()
             .ok_or_else(|| anyhow::anyhow!("AppHandle 未初始化"))?;
@@ -59,8 +136,11 @@ impl AsrProvider for LocalAsrProvider {
             anyhow::bail!("请先在「模型」页配置本地语音识别模型路径");
         }
 
+        // 临时文件名带随机后缀，批量识别时多个文件可以同时处理
+        let tmp_id = uuid::Uuid::new_v4().simple().to_string();
+
         // 将输入文件转为 16kHz 单声道 WAV（whisper-cli 要求）
-        let wav_path = std::env::temp_dir().join("vocosphere_whisper_input.wav");
+        let wav_path = std::env::temp_dir().join(format!("vocosphere_whisper_input_{tmp_id}.wav"));
         let wav_str = wav_path.to_string_lossy().to_string();
         let input_str = input_path.to_string_lossy().to_string();
 
@@ -79,25 +159,14 @@ impl AsrProvider for LocalAsrProvider {
         }
 
         // 运行 whisper-cli，输出 JSON 到临时目录
-        let out_prefix = std::env::temp_dir().join("vocosphere_whisper_out");
+        let out_prefix = std::env::temp_dir().join(format!("vocosphere_whisper_out_{tmp_id}"));
         let out_prefix_str = out_prefix.to_string_lossy().to_string();
 
-        let lang_arg = if rec.language == "auto" { "auto".to_string() } else { rec.language.clone() };
-        let threads_arg = rec.n_threads.to_string();
-
         let whisper_out = app
             .shell()
             .sidecar("whisper-cli")
             .map_err(|e| anyhow::anyhow!("whisper-cli sidecar 错误: {e}"))?
-            .args([
-                "-m", &rec.model_path,
-                "-f", &wav_str,
-                "-l", &lang_arg,
-                "-p", &threads_arg,
-                "-oj",
-                "-of", &out_prefix_str,
-                "--no-timestamps", "false",
-            ])
+            .args(whisper_cli_args(rec, &wav_str, &out_prefix_str))
             .output()
             .await
             .map_err(|e| anyhow::anyhow!("whisper-cli 执行失败: {e}"))?;
@@ -108,30 +177,30 @@ impl AsrProvider for LocalAsrProvider {
         }
 
         // 读取并解析 JSON 输出（whisper-cli 生成 <prefix>.json）
-        let json_path = format!("{out_prefix_str}.json");
-        let json_bytes = tokio::fs::read(&json_path)
-            .await
-            .map_err(|e| anyhow::anyhow!("读取 whisper-cli 输出失败: {json_path}: {e}"))?;
-
-        let parsed: WhisperOutput = serde_json::from_slice(&json_bytes)
-            .map_err(|e| anyhow::anyhow!("解析 whisper-cli JSON 失败: {e}"))?;
+        let segments = read_whisper_json(&out_prefix_str).await;
+        let _ = tokio::fs::remove_file(&wav_path).await;
+        let _ = tokio::fs::remove_file(format!("{out_prefix_str}.json")).await;
+        let segments = segments?;
 
-        let events: Vec<AsrResultEvent> = parsed
-            .transcription
+        let events: Vec<AsrResultEvent> = segments
             .into_iter()
             .enumerate()
             .filter(|(_, seg)| !seg.text.trim().is_empty())
             .map(|(idx, seg)| AsrResultEvent {
                 sentence_id: idx as u32,
-                begin_time: seg.offsets.from,
-                end_time: Some(seg.offsets.to),
+                begin_time: seg.from_ms,
+                end_time: Some(seg.to_ms),
                 text: seg.text.trim().to_string(),
                 is_final: true,
                 kind: AsrResultKind::Transcription,
                 lang: Some(rec.language.clone()),
+                session_id: None,
+                source: None,
+                detail: None,
             })
             .collect();
 
+        subtitle::write_transcript(&events, output_path)?;
         Ok(events)
     }
 }
@@ -0,0 +1,841 @@
+// 本地流式识别：滚动窗口 + whisper-cli 分块推理
+// 音频持续写入窗口，每累积 step_ms 新音频对整个窗口推理一次：
+// 窗口前部已稳定的句子作为最终结果提交并从窗口中裁掉，末尾（重叠区内）的句子作为临时结果推送，
+// 下次推理时随新音频一起重新识别，因此临时结果与最终结果共用同一个句子 ID
+use crate::app_state;
+use crate::asr::config::{LocalModelConfig, LocalStreamingConfig};
+use crate::asr::error::AsrError;
+use crate::asr::events::{AsrResultEvent, AsrResultKind, ResultOrigin};
+use crate::asr::provider::local::{read_whisper_json, whisper_cli_args};
+use crate::asr::session::{
+    publish_results, AsrSession, SessionCommand, SessionControl, SessionStatus,
+};
+use async_trait::async_trait;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use log::{debug, info, warn};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// 捕获管线输出的采样率（16kHz 单声道，与 whisper 要求一致）
+pub(super) const SAMPLE_RATE: u64 = 16000;
+/// 步长下限，避免过于频繁地启动 whisper-cli
+const MIN_STEP_MS: u64 = 500;
+/// 连续推理失败达到该次数后结束会话
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// whisper 识别出的片段（时间相对于送入推理的音频起点，毫秒）
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Segment {
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub text: String,
+}
+
+/// 推理引擎抽象（便于测试时替换）
+#[async_trait]
+pub(crate) trait WhisperEngine: Send + Sync + 'static {
+    async fn transcribe(&self, samples: &[f32]) -> anyhow::Result<Vec<Segment>>;
+}
+
+/// whisper-cli 推理引擎：默认调用打包的 sidecar，测试时可指定可执行文件路径
+pub(crate) struct WhisperCli {
+    program: Option<PathBuf>,
+    config: LocalModelConfig,
+    /// 本会话的临时文件前缀（每次推理复用）
+    file_prefix: PathBuf,
+}
+
+impl WhisperCli {
+    pub fn sidecar(config: LocalModelConfig) -> Self {
+        Self::new(None, config)
+    }
+
+    #[cfg(test)]
+    pub fn with_program(program: impl Into<PathBuf>, config: LocalModelConfig) -> Self {
+        Self::new(Some(program.into()), config)
+    }
+
+    fn new(program: Option<PathBuf>, config: LocalModelConfig) -> Self {
+        let file_prefix =
+            std::env::temp_dir().join(format!("vocosphere_stream_{}", Uuid::new_v4().simple()));
+        Self {
+            program,
+            config,
+            file_prefix,
+        }
+    }
+
+    fn wav_path(&self) -> PathBuf {
+        self.file_prefix.with_extension("wav")
+    }
+
+    /// 运行 whisper-cli，返回 (是否成功, stderr)；future 被丢弃（推理取消）时结束进程
+    async fn run(&self, args: Vec<String>) -> anyhow::Result<(bool, Vec<u8>)> {
+        match &self.program {
+            Some(program) => {
+                let output = tokio::process::Command::new(program)
+                    .args(&args)
+                    .kill_on_drop(true)
+                    .output()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("whisper-cli 执行失败: {e}"))?;
+                Ok((output.status.success(), output.stderr))
+            }
+            None => {
+                let app = app_state::get_app_handle()
+                    .ok_or_else(|| anyhow::anyhow!("AppHandle 未初始化"))?;
+                let (mut rx, child) = app
+                    .shell()
+                    .sidecar("whisper-cli")
+                    .map_err(|e| anyhow::anyhow!("whisper-cli sidecar 错误: {e}"))?
+                    .args(args)
+                    .spawn()
+                    .map_err(|e| anyhow::anyhow!("whisper-cli 执行失败: {e}"))?;
+                let mut child = KillOnDrop(Some(child));
+                let mut stderr = Vec::new();
+                let mut success = false;
+                while let Some(event) = rx.recv().await {
+                    match event {
+                        CommandEvent::Stderr(line) => {
+                            stderr.extend_from_slice(&line);
+                            stderr.push(b'\n');
+                        }
+                        CommandEvent::Error(e) => anyhow::bail!("whisper-cli 执行失败: {e}"),
+                        CommandEvent::Terminated(status) => {
+                            success = status.code == Some(0);
+                            child.0 = None;
+                        }
+                        _ => {}
+                    }
+                }
+                Ok((success, stderr))
+            }
+        }
+    }
+}
+
+/// 丢弃时结束尚未退出的 sidecar 进程
+struct KillOnDrop(Option<CommandChild>);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        if let Some(child) = self.0.take() {
+            let _ = child.kill();
+        }
+    }
+}
+
+#[async_trait]
+impl WhisperEngine for WhisperCli {
+    async fn transcribe(&self, samples: &[f32]) -> anyhow::Result<Vec<Segment>> {
+        let wav_path = self.wav_path();
+        write_wav(&wav_path, samples)?;
+
+        let wav_str = wav_path.to_string_lossy().to_string();
+        let out_prefix = self.file_prefix.to_string_lossy().to_string();
+        let (success, stderr) = self
+            .run(whisper_cli_args(&self.config, &wav_str, &out_prefix))
+            .await?;
+        if !success {
+            anyhow::bail!("whisper-cli 识别失败: {}", String::from_utf8_lossy(&stderr));
+        }
+        read_whisper_json(&out_prefix).await
+    }
+}
+
+impl Drop for WhisperCli {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(self.wav_path());
+        let _ = std::fs::remove_file(self.file_prefix.with_extension("json"));
+    }
+}
+
+/// 写入 16kHz 单声道 16bit WAV
+fn write_wav(path: &std::path::Path, samples: &[f32]) -> anyhow::Result<()> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE as u32,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::create(path, spec)?;
+    for &sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+fn samples_to_ms(samples: u64) -> u64 {
+    samples * 1000 / SAMPLE_RATE
+}
+
+fn ms_to_samples(ms: u64) -> usize {
+    (ms * SAMPLE_RATE / 1000) as usize
+}
+
+/// whisper 对静音输出的占位文本（如 [BLANK_AUDIO]）不作为识别结果
+fn is_speech(text: &str) -> bool {
+    let text = text.trim();
+    !(text.is_empty() || text.starts_with('[') && text.ends_with(']'))
+}
+
+/// 滚动窗口：保存尚未提交的音频，并把推理结果转换为临时/最终结果
+pub(crate) struct RollingWindow {
+    samples: Vec<f32>,
+    /// 窗口起点在会话时间轴上的位置（样本数）
+    start_sample: u64,
+    /// 上次推理后新增的样本数
+    new_samples: usize,
+    /// 上次送入推理的样本数（推理期间新到的音频排在其后）
+    inferred: usize,
+    /// 窗口内被 VAD 丢弃的静音：(窗口内位置, 样本数)
+    gaps: Vec<(usize, u64)>,
+    window_samples: usize,
+    step_samples: usize,
+    overlap_ms: u64,
+    next_sentence_id: u32,
+    language: Option<String>,
+}
+
+impl RollingWindow {
+    pub fn new(config: &LocalStreamingConfig, language: Option<String>) -> Self {
+        let step_ms = config.step_ms.max(MIN_STEP_MS);
+        let window_ms = config.window_ms.max(step_ms + config.overlap_ms);
+        Self {
+            samples: Vec::with_capacity(ms_to_samples(window_ms)),
+            start_sample: 0,
+            new_samples: 0,
+            inferred: 0,
+            gaps: Vec::new(),
+            window_samples: ms_to_samples(window_ms),
+            step_samples: ms_to_samples(step_ms),
+            overlap_ms: config.overlap_ms,
+            next_sentence_id: 0,
+            language,
+        }
+    }
+
+    pub fn push(&mut self, frame: &[f32]) {
+        self.samples.extend_from_slice(frame);
+        self.new_samples += frame.len();
+    }
+
+    /// 新音频是否已达到一个步长
+    pub fn should_infer(&self) -> bool {
+        self.new_samples >= self.step_samples
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// 取出送入推理的音频
+    pub fn snapshot(&mut self) -> Vec<f32> {
+        self.new_samples = 0;
+        self.inferred = self.samples.len();
+        self.samples.clone()
+    }
+
+    /// 根据推理结果生成临时/最终结果，并裁掉已提交的音频
+    /// segments 的时间相对于 snapshot 的起点；flush 为 true 时提交 snapshot 内的全部结果
+    pub fn apply(&mut self, segments: Vec<Segment>, flush: bool) -> Vec<AsrResultEvent> {
+        let segments: Vec<Segment> = segments
+            .into_iter()
+            .filter(|s| is_speech(&s.text))
+            .collect();
+        let window_ms = samples_to_ms(self.inferred as u64);
+        let force = flush || self.inferred >= self.window_samples;
+
+        let commit_count = if force {
+            segments.len()
+        } else {
+            // 最后一个片段可能被窗口截断，始终保留为临时结果
+            let stable_end = window_ms.saturating_sub(self.overlap_ms);
+            segments[..segments.len().saturating_sub(1)]
+                .iter()
+                .take_while(|s| s.to_ms <= stable_end)
+                .count()
+        };
+
+        let mut results = Vec::new();
+        for segment in &segments[..commit_count] {
+            results.push(self.event(
+                self.timeline_ms(segment.from_ms),
+                self.timeline_ms(segment.to_ms),
+                segment.text.trim().to_string(),
+                true,
+            ));
+            self.next_sentence_id += 1;
+        }
+
+        let rest = &segments[commit_count..];
+        if let (Some(first), Some(last)) = (rest.first(), rest.last()) {
+            let text: String = rest.iter().map(|s| s.text.as_str()).collect();
+            results.push(self.event(
+                self.timeline_ms(first.from_ms),
+                self.timeline_ms(last.to_ms),
+                text.trim().to_string(),
+                false,
+            ));
+        }
+
+        let drain = if force {
+            self.inferred
+        } else if commit_count > 0 {
+            ms_to_samples(segments[commit_count - 1].to_ms)
+        } else if segments.is_empty() {
+            // 没有语音：只保留重叠区，避免静音反复参与推理
+            self.inferred.saturating_sub(ms_to_samples(self.overlap_ms))
+        } else {
+            0
+        };
+        self.drain(drain);
+        results
+    }
+
+    /// 推理失败时丢弃超出窗口上限的最旧音频，防止窗口无限增长
+    pub fn drop_overflow(&mut self) {
+        let overflow = self.samples.len().saturating_sub(self.window_samples);
+        if overflow > 0 {
+            warn!(
+                "⚠️ 本地推理失败，丢弃 {}ms 音频",
+                samples_to_ms(overflow as u64)
+            );
+            self.drain(overflow);
+        }
+    }
+
+    /// 跳过未送入窗口的音频（被 VAD 丢弃的静音），保持时间轴连续
+    pub fn skip(&mut self, samples: u64) {
+        if samples == 0 {
+            return;
+        }
+        if self.samples.is_empty() {
+            self.start_sample += samples;
+        } else {
+            self.gaps.push((self.samples.len(), samples));
+        }
+    }
+
+    /// 窗口内时间（毫秒）转换为会话时间轴，计入其之前丢弃的静音
+    fn timeline_ms(&self, ms: u64) -> u64 {
+        let position = ms_to_samples(ms);
+        let skipped: u64 = self
+            .gaps
+            .iter()
+            .filter(|(at, _)| *at <= position)
+            .map(|(_, len)| len)
+            .sum();
+        samples_to_ms(self.start_sample + skipped) + ms
+    }
+
+    fn drain(&mut self, count: usize) {
+        let count = count.min(self.samples.len());
+        self.samples.drain(..count);
+        self.start_sample += count as u64;
+        self.gaps.retain_mut(|(at, len)| {
+            if *at <= count {
+                self.start_sample += *len;
+                false
+            } else {
+                *at -= count;
+                true
+            }
+        });
+    }
+
+    fn event(
+        &self,
+        begin_time: u64,
+        end_time: u64,
+        text: String,
+        is_final: bool,
+    ) -> AsrResultEvent {
+        AsrResultEvent {
+            sentence_id: self.next_sentence_id,
+            begin_time,
+            end_time: Some(end_time),
+            text,
+            is_final,
+            kind: AsrResultKind::Transcription,
+            lang: self.language.clone(),
+            session_id: None,
+            source: None,
+            detail: None,
+        }
+    }
+}
+
+/// 启动本地流式识别会话
+pub(crate) fn start<E: WhisperEngine>(
+    engine: E,
+    config: &LocalStreamingConfig,
+    language: Option<String>,
+    receiver: mpsc::Receiver<Vec<f32>>,
+    origin: ResultOrigin,
+) -> AsrSession {
+    info!(
+        "🖥️ 本地流式识别：窗口 {}ms，步长 {}ms，重叠 {}ms",
+        config.window_ms, config.step_ms, config.overlap_ms
+    );
+    let window = RollingWindow::new(config, language);
+    AsrSession::spawn(SessionStatus::Running, move |control| {
+        run(engine, window, receiver, origin, control)
+    })
+}
+
+async fn run<E: WhisperEngine>(
+    engine: E,
+    mut window: RollingWindow,
+    mut receiver: mpsc::Receiver<Vec<f32>>,
+    origin: ResultOrigin,
+    mut control: SessionControl,
+) -> Result<Vec<AsrResultEvent>, AsrError> {
+    let engine = Arc::new(engine);
+    let mut finals = Vec::new();
+    let mut failures = 0;
+    let mut paused = false;
+    // 推理在独立任务中进行，期间继续接收音频和控制指令
+    let mut inference: Option<Inference> = None;
+    let mut flush_pending = false;
+    let mut input_closed = false;
+
+    loop {
+        if inference.is_none() {
+            if flush_pending {
+                flush_pending = false;
+                if !window.is_empty() {
+                    inference = Some(Inference::start(&engine, &mut window, true));
+                }
+            } else if window.should_infer() {
+                inference = Some(Inference::start(&engine, &mut window, false));
+            }
+        }
+        if input_closed && inference.is_none() {
+            break;
+        }
+
+        tokio::select! {
+            result = Inference::wait(&mut inference) => {
+                if let Some(done) = inference.take() {
+                    done.apply(result, &mut window, &origin, &mut finals, &mut failures)?;
+                }
+            }
+            samples = receiver.recv(), if !input_closed => match samples {
+                Some(frame) => {
+                    if paused {
+                        paused = false;
+                        control.set_status(SessionStatus::Running);
+                    }
+                    window.skip(control.take_skipped_samples());
+                    window.push(&frame);
+                }
+                None => {
+                    info!("音频输入结束，识别剩余音频");
+                    input_closed = true;
+                    flush_pending = true;
+                }
+            },
+            command = control.changed() => match command {
+                SessionCommand::Run => {
+                    if paused {
+                        paused = false;
+                        control.set_status(SessionStatus::Running);
+                    }
+                }
+                SessionCommand::Pause => {
+                    info!("⏸️ 持续静音，提交窗口内的识别结果");
+                    flush_pending = true;
+                    paused = true;
+                    control.set_status(SessionStatus::Paused);
+                }
+                SessionCommand::Stop => {
+                    info!("收到停止指令，识别剩余音频后结束");
+                    receiver.close();
+                    control.set_status(SessionStatus::Stopping);
+                }
+                SessionCommand::Cancel => {
+                    // 丢弃进行中的推理会结束 whisper-cli
+                    info!("收到取消指令，立即结束本地识别");
+                    control.set_status(SessionStatus::Cancelled);
+                    return Ok(finals);
+                }
+            },
+        }
+    }
+
+    info!("本地流式识别结束，共 {} 条最终结果", finals.len());
+    Ok(finals)
+}
+
+/// 对窗口的一次推理，在独立任务中运行；丢弃时中止任务（连同 whisper-cli 进程）
+struct Inference {
+    task: JoinHandle<anyhow::Result<Vec<Segment>>>,
+    flush: bool,
+    audio_ms: u64,
+    started: Instant,
+}
+
+impl Inference {
+    fn start<E: WhisperEngine>(engine: &Arc<E>, window: &mut RollingWindow, flush: bool) -> Self {
+        let samples = window.snapshot();
+        let audio_ms = samples_to_ms(samples.len() as u64);
+        let engine = engine.clone();
+        Self {
+            task: tokio::spawn(async move { engine.transcribe(&samples).await }),
+            flush,
+            audio_ms,
+            started: Instant::now(),
+        }
+    }
+
+    /// 等待推理结束；没有进行中的推理时永不返回
+    async fn wait(inference: &mut Option<Self>) -> anyhow::Result<Vec<Segment>> {
+        match inference {
+            Some(inference) => match (&mut inference.task).await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow::anyhow!("推理任务异常退出: {e}")),
+            },
+            None => std::future::pending().await,
+        }
+    }
+
+    /// 推送推理结果；连续失败达到上限时结束会话
+    fn apply(
+        self,
+        result: anyhow::Result<Vec<Segment>>,
+        window: &mut RollingWindow,
+        origin: &ResultOrigin,
+        finals: &mut Vec<AsrResultEvent>,
+        failures: &mut u32,
+    ) -> Result<(), AsrError> {
+        match result {
+            Ok(segments) => {
+                debug!(
+                    "本地推理 {}ms 音频耗时 {}ms，{} 个片段",
+                    self.audio_ms,
+                    self.started.elapsed().as_millis(),
+                    segments.len()
+                );
+                *failures = 0;
+                publish_results(window.apply(segments, self.flush), origin, finals);
+                Ok(())
+            }
+            Err(e) => {
+                *failures += 1;
+                let err = AsrError::Inference {
+                    message: e.to_string(),
+                };
+                warn!("⚠️ {}（连续失败 {} 次）", err, failures);
+                window.drop_overflow();
+                if *failures >= MAX_CONSECUTIVE_FAILURES {
+                    err.emit();
+                    return Err(err);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for Inference {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+    use tokio::sync::Notify;
+
+    fn config() -> LocalStreamingConfig {
+        LocalStreamingConfig {
+            window_ms: 8000,
+            step_ms: 1000,
+            overlap_ms: 1000,
+        }
+    }
+
+    fn segment(from_ms: u64, to_ms: u64, text: &str) -> Segment {
+        Segment {
+            from_ms,
+            to_ms,
+            text: text.to_string(),
+        }
+    }
+
+    fn push_ms(window: &mut RollingWindow, ms: u64) {
+        window.push(&vec![0.0; ms_to_samples(ms)]);
+    }
+
+    #[test]
+    fn test_commit_stable_segments_and_keep_tail_partial() {
+        let mut window = RollingWindow::new(&config(), Some("zh".to_string()));
+        push_ms(&mut window, 4000);
+        window.snapshot();
+        let results = window.apply(
+            vec![
+                segment(0, 1500, "第一句"),
+                segment(1500, 3000, "第二句"),
+                segment(3000, 4000, "第三"),
+            ],
+            false,
+        );
+        let summary: Vec<_> = results
+            .iter()
+            .map(|r| (r.sentence_id, r.is_final, r.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(0, true, "第一句"), (1, true, "第二句"), (2, false, "第三")]
+        );
+
+        // 已提交的 3000ms 被裁掉，新结果平移到会话时间轴，且沿用临时结果的句子 ID
+        push_ms(&mut window, 2000);
+        window.snapshot();
+        let results = window.apply(vec![segment(0, 1800, "第三句")], true);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].sentence_id, 2);
+        assert!(results[0].is_final);
+        assert_eq!(results[0].begin_time, 3000);
+        assert_eq!(results[0].end_time, Some(4800));
+        assert!(window.is_empty());
+    }
+
+    #[test]
+    fn test_window_limit_forces_commit_and_silence_is_trimmed() {
+        let mut window = RollingWindow::new(&config(), None);
+        push_ms(&mut window, 3000);
+        window.snapshot();
+        assert!(window
+            .apply(vec![segment(0, 1000, "[BLANK_AUDIO]")], false)
+            .is_empty());
+        // 静音只保留重叠区
+        assert_eq!(window.samples.len(), ms_to_samples(1000));
+
+        push_ms(&mut window, 7000);
+        window.snapshot();
+        let results = window.apply(vec![segment(0, 8000, "很长的一句话")], false);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_final);
+        assert_eq!(results[0].begin_time, 2000);
+        assert!(window.is_empty());
+    }
+
+    /// 每秒音频识别为一个片段的假引擎
+    struct FakeEngine;
+
+    #[async_trait]
+    impl WhisperEngine for FakeEngine {
+        async fn transcribe(&self, samples: &[f32]) -> anyhow::Result<Vec<Segment>> {
+            let secs = samples_to_ms(samples.len() as u64) / 1000;
+            Ok((0..secs)
+                .map(|i| segment(i * 1000, (i + 1) * 1000, &format!("片段{}", i)))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_session_stop_returns_finals() {
+        let (tx, rx) = mpsc::channel(100);
+        let session = start(FakeEngine, &config(), None, rx, ResultOrigin::default());
+        for _ in 0..50 {
+            tx.send(vec![0.0; 1600]).await.unwrap();
+        }
+        session.stop();
+        drop(tx);
+        let finals = session.join().await.unwrap();
+        assert_eq!(finals.len(), 5);
+        let ids: Vec<u32> = finals.iter().map(|r| r.sentence_id).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+        assert_eq!(finals[4].end_time, Some(5000));
+    }
+
+    /// 推理耗时很长的假引擎：记录推理开始，以及推理被中止（future 被丢弃）
+    struct SlowEngine {
+        started: Arc<Notify>,
+        aborted: Arc<AtomicBool>,
+    }
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl WhisperEngine for SlowEngine {
+        async fn transcribe(&self, _samples: &[f32]) -> anyhow::Result<Vec<Segment>> {
+            let guard = SetOnDrop(self.aborted.clone());
+            self.started.notify_one();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            std::mem::forget(guard);
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_during_slow_inference_returns_promptly() {
+        let started = Arc::new(Notify::new());
+        let aborted = Arc::new(AtomicBool::new(false));
+        let engine = SlowEngine {
+            started: started.clone(),
+            aborted: aborted.clone(),
+        };
+        let (tx, rx) = mpsc::channel(100);
+        let session = start(engine, &config(), None, rx, ResultOrigin::default());
+        for _ in 0..10 {
+            tx.send(vec![0.0; 1600]).await.unwrap();
+        }
+        started.notified().await;
+
+        // 推理进行中仍能接收音频和指令
+        tx.send(vec![0.0; 1600]).await.unwrap();
+        session.cancel();
+        let finals = tokio::time::timeout(Duration::from_secs(1), session.join())
+            .await
+            .expect("取消应立即返回")
+            .unwrap();
+        assert!(finals.is_empty());
+
+        // 进行中的推理被中止
+        for _ in 0..100 {
+            if aborted.load(Ordering::SeqCst) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(aborted.load(Ordering::SeqCst));
+    }
+
+    /// 把每段连续的非静音（50ms 粒度）识别为一个片段的假引擎
+    struct ToneEngine;
+
+    #[async_trait]
+    impl WhisperEngine for ToneEngine {
+        async fn transcribe(&self, samples: &[f32]) -> anyhow::Result<Vec<Segment>> {
+            let mut segments: Vec<Segment> = Vec::new();
+            let mut run_start = None;
+            let chunks: Vec<bool> = samples
+                .chunks(800)
+                .map(|c| c.iter().any(|s| s.abs() > 0.01))
+                .collect();
+            for (i, &voiced) in chunks.iter().chain([&false]).enumerate() {
+                match (voiced, run_start) {
+                    (true, None) => run_start = Some(i as u64 * 50),
+                    (false, Some(from)) => {
+                        segments.push(segment(from, i as u64 * 50, "语音"));
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(segments)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gate_silence_keeps_wall_clock_timeline() {
+        use crate::audio::vad::{Vad, VadConfig, VadEvent, VadMode};
+
+        let (tx, rx) = mpsc::channel(100);
+        let session = start(ToneEngine, &config(), None, rx, ResultOrigin::default());
+        let pauser = session.pauser();
+        let vad_config = VadConfig {
+            mode: VadMode::Gate,
+            attack_ms: 100,
+            hangover_ms: 300,
+            pre_roll_ms: 200,
+            ..VadConfig::default()
+        };
+        let mut vad =
+            Vad::new(vad_config, SAMPLE_RATE as u32).with_listener(Box::new(move |event| {
+                if let VadEvent::SpeechStart {
+                    skipped_samples, ..
+                } = event
+                {
+                    pauser.skip(skipped_samples);
+                }
+            }));
+
+        // 静音 2s、语音 1s、静音 3s、语音 1s、静音 0.5s（50ms 一帧）
+        let tone = |amplitude: f32| -> Vec<f32> {
+            (0..800)
+                .map(|i| amplitude * (i as f32 * 0.1).sin())
+                .collect()
+        };
+        let plan = [(0.0, 40), (0.3, 20), (0.0, 60), (0.3, 20), (0.0, 10)];
+        for (amplitude, count) in plan {
+            for _ in 0..count {
+                for frame in vad.process(tone(amplitude)) {
+                    tx.send(frame).await.unwrap();
+                    // 与实时采集一致：识别端在下一次静音丢弃前已读走之前的音频
+                    while tx.capacity() < tx.max_capacity() {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            }
+        }
+        session.stop();
+        drop(tx);
+
+        let finals = session.join().await.unwrap();
+        let times: Vec<_> = finals
+            .iter()
+            .map(|r| (r.begin_time, r.end_time.unwrap()))
+            .collect();
+        assert_eq!(times, vec![(2000, 3000), (6000, 7000)]);
+    }
+
+    /// 真实 whisper-cli 测试：需设置 WHISPER_CLI、WHISPER_MODEL（如 ggml-tiny.bin）、
+    /// WHISPER_WAV（16kHz 单声道 WAV），运行 cargo test -- --ignored
+    #[tokio::test]
+    #[ignore]
+    async fn test_whisper_cli_streaming_with_fixture() {
+        let (Ok(cli), Ok(model), Ok(wav)) = (
+            std::env::var("WHISPER_CLI"),
+            std::env::var("WHISPER_MODEL"),
+            std::env::var("WHISPER_WAV"),
+        ) else {
+            panic!("需要设置 WHISPER_CLI / WHISPER_MODEL / WHISPER_WAV");
+        };
+        let mut reader = hound::WavReader::open(&wav).unwrap();
+        assert_eq!(reader.spec().sample_rate, SAMPLE_RATE as u32);
+        assert_eq!(reader.spec().channels, 1);
+        let samples: Vec<f32> = reader
+            .samples::<i16>()
+            .map(|s| s.unwrap() as f32 / i16::MAX as f32)
+            .collect();
+
+        let engine = WhisperCli::with_program(
+            cli,
+            LocalModelConfig {
+                model_path: model,
+                ..LocalModelConfig::default()
+            },
+        );
+        let (tx, rx) = mpsc::channel(1000);
+        let session = start(engine, &config(), None, rx, ResultOrigin::default());
+        for frame in samples.chunks(800) {
+            tx.send(frame.to_vec()).await.unwrap();
+        }
+        drop(tx);
+        let finals = session.join().await.unwrap();
+        assert!(finals.iter().any(|r| !r.text.is_empty()));
+        assert!(finals
+            .windows(2)
+            .all(|w| w[0].sentence_id < w[1].sentence_id));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod cloud;
 pub mod local;
+mod local_stream;
 
 pub use cloud::CloudAsrProvider;
 pub use local::LocalAsrProvider;
@@ -0,0 +1,306 @@
+use crate::app_state;
+use crate::asr::error::AsrError;
+use crate::asr::events::{AsrResultEvent, ResultOrigin, ASR_RESULT_EVENT};
+use log::warn;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// 识别会话状态（通过 watch channel 对外广播）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// 任务已启动，正在识别
+    Running,
+    /// 连接中断，正在进行第 attempt 次重连
+    Reconnecting { attempt: u32 },
+    /// 因静音暂停：当前任务已结束，检测到语音后开启新任务
+    Paused,
+    /// 已请求停止，等待服务端返回剩余结果
+    Stopping,
+    /// 正常结束
+    Finished,
+    /// 因不可恢复的错误结束
+    Failed { error: AsrError },
+    /// 被取消（未等待剩余结果）
+    Cancelled,
+}
+
+impl SessionStatus {
+    /// 是否为终止状态
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Finished | Self::Failed { .. } | Self::Cancelled)
+    }
+}
+
+/// 控制指令：由 AsrSession 发出，会话驱动方监听
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionCommand {
+    Run,
+    /// 静音暂停：结束当前任务（保留会话），有新音频时再开启新任务
+    Pause,
+    /// 优雅停止：发送剩余音频和 finish-task，等待 task-finished
+    Stop,
+    /// 立即取消：直接断开连接
+    Cancel,
+}
+
+/// 会话驱动方持有的控制端（接收指令、上报状态）
+pub struct SessionControl {
+    command: watch::Receiver<SessionCommand>,
+    status: watch::Sender<SessionStatus>,
+    skipped_samples: Arc<AtomicU64>,
+}
+
+impl SessionControl {
+    /// 等待指令变化；AsrSession 被丢弃时视为取消
+    pub async fn changed(&mut self) -> SessionCommand {
+        match self.command.changed().await {
+            Ok(()) => *self.command.borrow_and_update(),
+            Err(_) => SessionCommand::Cancel,
+        }
+    }
+
+    /// 上报状态
+    pub fn set_status(&self, status: SessionStatus) {
+        self.status.send_replace(status);
+    }
+
+    /// 取出被 VAD 丢弃、尚未计入时间轴的样本数
+    pub fn take_skipped_samples(&self) -> u64 {
+        self.skipped_samples.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// 流式识别会话句柄：可停止、取消、观察状态，并在结束后取回全部最终结果
+pub struct AsrSession {
+    command: Arc<watch::Sender<SessionCommand>>,
+    skipped_samples: Arc<AtomicU64>,
+    status: watch::Receiver<SessionStatus>,
+    task: JoinHandle<Result<Vec<AsrResultEvent>, AsrError>>,
+}
+
+impl AsrSession {
+    /// 以给定的驱动函数启动会话；驱动函数返回收集到的最终结果
+    pub fn spawn<F, Fut>(initial: SessionStatus, driver: F) -> Self
+    where
+        F: FnOnce(SessionControl) -> Fut,
+        Fut: Future<Output = Result<Vec<AsrResultEvent>, AsrError>> + Send + 'static,
+    {
+        let (command_tx, command_rx) = watch::channel(SessionCommand::Run);
+        let (status_tx, status_rx) = watch::channel(initial);
+        let skipped_samples = Arc::new(AtomicU64::new(0));
+        let control = SessionControl {
+            command: command_rx,
+            status: status_tx.clone(),
+            skipped_samples: skipped_samples.clone(),
+        };
+        let fut = driver(control);
+        let task = tokio::spawn(async move {
+            let result = fut.await;
+            // 驱动函数未上报终止状态时，按返回值补齐
+            if !status_tx.borrow().is_terminal() {
+                status_tx.send_replace(match &result {
+                    Ok(_) => SessionStatus::Finished,
+                    Err(e) => SessionStatus::Failed { error: e.clone() },
+                });
+            }
+            result
+        });
+        Self {
+            command: Arc::new(command_tx),
+            skipped_samples,
+            status: status_rx,
+            task,
+        }
+    }
+
+    /// 请求优雅停止（发送 finish-task 并等待剩余结果），配合 join 获取最终结果
+    pub fn stop(&self) {
+        self.command.send_if_modified(|cmd| {
+            if matches!(*cmd, SessionCommand::Run | SessionCommand::Pause) {
+                *cmd = SessionCommand::Stop;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// 立即取消，不等待服务端剩余结果
+    pub fn cancel(&self) {
+        self.command.send_replace(SessionCommand::Cancel);
+    }
+
+    /// 获取暂停/恢复句柄（供 VAD 在音频线程中使用）
+    pub fn pauser(&self) -> SessionPauser {
+        SessionPauser {
+            command: self.command.clone(),
+            skipped_samples: self.skipped_samples.clone(),
+        }
+    }
+
+    /// 订阅状态变化
+    pub fn status(&self) -> watch::Receiver<SessionStatus> {
+        self.status.clone()
+    }
+
+    /// 会话是否已结束
+    pub fn is_finished(&self) -> bool {
+        self.status.borrow().is_terminal() || self.task.is_finished()
+    }
+
+    /// 等待会话结束，返回全部最终（is_final）识别结果
+    pub async fn join(self) -> Result<Vec<AsrResultEvent>, AsrError> {
+        match self.task.await {
+            Ok(result) => result,
+            Err(e) => Err(AsrError::Protocol {
+                message: format!("识别会话异常退出: {}", e),
+            }),
+        }
+    }
+}
+
+/// 静音暂停/恢复句柄：只能在运行与暂停之间切换，不影响停止和取消
+#[derive(Clone)]
+pub struct SessionPauser {
+    command: Arc<watch::Sender<SessionCommand>>,
+    skipped_samples: Arc<AtomicU64>,
+}
+
+impl SessionPauser {
+    /// 暂停识别（结束当前任务）
+    pub fn pause(&self) {
+        self.switch(SessionCommand::Run, SessionCommand::Pause);
+    }
+
+    /// 恢复识别
+    pub fn resume(&self) {
+        self.switch(SessionCommand::Pause, SessionCommand::Run);
+    }
+
+    /// 记录被 VAD 丢弃的样本数（无论是否暂停），由驱动方计入时间轴
+    pub fn skip(&self, samples: u64) {
+        self.skipped_samples.fetch_add(samples, Ordering::Relaxed);
+    }
+
+    fn switch(&self, from: SessionCommand, to: SessionCommand) {
+        self.command.send_if_modified(|cmd| {
+            if *cmd == from {
+                *cmd = to;
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+/// 写入来源标识后推送识别结果到前端，并收集最终结果
+pub(crate) fn publish_results(
+    results: Vec<AsrResultEvent>,
+    origin: &ResultOrigin,
+    finals: &mut Vec<AsrResultEvent>,
+) {
+    for mut result in results {
+        origin.tag(&mut result);
+        if let Err(err) = app_state::emit_event(ASR_RESULT_EVENT, &result) {
+            warn!("发送识别结果到前端失败: {}", err);
+        }
+        if result.is_final {
+            finals.push(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::events::AsrResultKind;
+
+    fn final_result(id: u32) -> AsrResultEvent {
+        AsrResultEvent {
+            sentence_id: id,
+            begin_time: 0,
+            end_time: Some(1000),
+            text: format!("第{}句", id),
+            is_final: true,
+            kind: AsrResultKind::Transcription,
+            lang: None,
+            session_id: None,
+            source: None,
+            detail: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_then_join_returns_results() {
+        let session = AsrSession::spawn(SessionStatus::Running, |mut control| async move {
+            let mut results = vec![final_result(0)];
+            assert_eq!(control.changed().await, SessionCommand::Stop);
+            control.set_status(SessionStatus::Stopping);
+            results.push(final_result(1));
+            Ok(results)
+        });
+        let mut status = session.status();
+        session.stop();
+        let results = session.join().await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(*status.borrow_and_update(), SessionStatus::Finished);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_failed_status() {
+        let session = AsrSession::spawn(SessionStatus::Running, |mut control| async move {
+            assert_eq!(control.changed().await, SessionCommand::Cancel);
+            Err(AsrError::NetworkUnreachable {
+                message: "offline".to_string(),
+            })
+        });
+        let status = session.status();
+        session.cancel();
+        assert!(session.join().await.is_err());
+        assert!(matches!(*status.borrow(), SessionStatus::Failed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_stop_after_cancel_is_ignored() {
+        let session = AsrSession::spawn(SessionStatus::Running, |mut control| async move {
+            assert_eq!(control.changed().await, SessionCommand::Cancel);
+            control.set_status(SessionStatus::Cancelled);
+            Ok(vec![])
+        });
+        session.cancel();
+        session.stop();
+        let status = session.status();
+        session.join().await.unwrap();
+        assert_eq!(*status.borrow(), SessionStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_pause_resume_carries_skipped_samples() {
+        let session = AsrSession::spawn(SessionStatus::Running, |mut control| async move {
+            assert_eq!(control.changed().await, SessionCommand::Pause);
+            assert_eq!(control.changed().await, SessionCommand::Run);
+            assert_eq!(control.take_skipped_samples(), 16000);
+            assert_eq!(control.changed().await, SessionCommand::Stop);
+            // 未暂停时丢弃的静音同样累计
+            assert_eq!(control.take_skipped_samples(), 800);
+            Ok(vec![])
+        });
+        let pauser = session.pauser();
+        pauser.pause();
+        tokio::task::yield_now().await;
+        pauser.skip(16000);
+        pauser.resume();
+        tokio::task::yield_now().await;
+        pauser.resume();
+        pauser.skip(800);
+        session.stop();
+        pauser.pause();
+        session.join().await.unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+// DashScope 临时存储：先获取上传凭证，再以表单 POST 上传到百炼提供的 OSS，
+// 得到 oss:// 地址直接交给 Fun-ASR；文件 48 小时后自动过期，无需删除
+use super::multipart::check_status;
+use super::{ObjectStore, UploadEvent};
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use log::info;
+use reqwest::{Body, Client};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+const API_BASE: &str = "https://dashscope.aliyuncs.com/api/v1";
+/// 上传凭证与模型绑定，只能用于该模型的识别任务
+const MODEL: &str = "fun-asr";
+
+pub struct DashScopeStore {
+    api_key: String,
+    base: String,
+    client: Client,
+    /// 对象 key → 上传后的 oss:// 地址
+    uploaded: Mutex<BTreeMap<String, String>>,
+}
+
+impl DashScopeStore {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            base: API_BASE.to_string(),
+            client: Client::new(),
+            uploaded: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    async fn get_policy(&self) -> anyhow::Result<UploadPolicy> {
+        let resp = self
+            .client
+            .get(format!("{}/uploads", self.base))
+            .query(&[("action", "getPolicy"), ("model", MODEL)])
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .context("获取 DashScope 上传凭证请求失败")?;
+        let resp = check_status(resp, "获取 DashScope 上传凭证失败").await?;
+        let policy: PolicyResponse = resp.json().await.context("解析上传凭证失败")?;
+        Ok(policy.data)
+    }
+
+    fn lock_uploaded(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.uploaded.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl ObjectStore for DashScopeStore {
+    async fn upload(
+        &self,
+        path: &Path,
+        key: &str,
+        on_event: &mut (dyn for<'e> FnMut(UploadEvent<'e>) + Send),
+    ) -> anyhow::Result<()> {
+        let total = tokio::fs::metadata(path)
+            .await
+            .with_context(|| format!("无法读取文件: {}", path.display()))?
+            .len();
+        let policy = self.get_policy().await?;
+        if total > policy.max_file_size_mb * 1024 * 1024 {
+            anyhow::bail!(
+                "文件超过 DashScope 临时存储的大小上限（{} MB），请改用 OSS 或 S3",
+                policy.max_file_size_mb
+            );
+        }
+
+        let file_name = key.rsplit('/').next().unwrap_or(key);
+        let object_key = format!("{}/{}", policy.upload_dir.trim_end_matches('/'), file_name);
+        let fields = [
+            ("OSSAccessKeyId", policy.oss_access_key_id.as_str()),
+            ("Signature", policy.signature.as_str()),
+            ("policy", policy.policy.as_str()),
+            ("x-oss-object-acl", policy.x_oss_object_acl.as_str()),
+            (
+                "x-oss-forbid-overwrite",
+                policy.x_oss_forbid_overwrite.as_str(),
+            ),
+            ("key", object_key.as_str()),
+            ("success_action_status", "200"),
+        ];
+        let boundary = format!("----vocosphere{}", uuid::Uuid::new_v4().simple());
+        let (head, tail) = form_parts(&boundary, &fields, file_name);
+        let content_length = head.len() as u64 + total + tail.len() as u64;
+        let file = tokio::fs::File::open(path)
+            .await
+            .with_context(|| format!("无法读取文件: {}", path.display()))?;
+        // 文件内容按块读取，不整体载入内存
+        let body = stream::once(async move { Ok(head.into()) })
+            .chain(tokio_util::io::ReaderStream::new(file))
+            .chain(stream::once(async move { Ok(tail.into()) }));
+
+        let resp = self
+            .client
+            .post(&policy.upload_host)
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .header("Content-Length", content_length)
+            .body(Body::wrap_stream(body))
+            .send()
+            .await
+            .context("DashScope 临时存储上传请求失败")?;
+        check_status(resp, "DashScope 临时存储上传失败").await?;
+        on_event(UploadEvent::Progress {
+            uploaded: total,
+            total,
+        });
+
+        let url = format!("oss://{}", object_key);
+        info!("文件已上传到 DashScope 临时存储: {}", url);
+        self.lock_uploaded().insert(key.to_string(), url);
+        Ok(())
+    }
+
+    async fn presign(&self, key: &str) -> anyhow::Result<String> {
+        self.lock_uploaded()
+            .get(key)
+            .cloned()
+            .context("文件尚未上传到 DashScope 临时存储")
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.lock_uploaded().remove(key);
+        Ok(())
+    }
+}
+
+/// multipart/form-data 请求体中文件内容之前和之后的部分，文件字段必须放在最后
+fn form_parts(boundary: &str, fields: &[(&str, &str)], file_name: &str) -> (Vec<u8>, Vec<u8>) {
+    let mut body = Vec::with_capacity(1024);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+            boundary, file_name
+        )
+        .as_bytes(),
+    );
+    let tail = format!("\r\n--{}--\r\n", boundary).into_bytes();
+    (body, tail)
+}
+
+#[derive(Deserialize)]
+struct PolicyResponse {
+    data: UploadPolicy,
+}
+#[derive(Deserialize)]
+struct UploadPolicy {
+    policy: String,
+    signature: String,
+    upload_dir: String,
+    upload_host: String,
+    max_file_size_mb: u64,
+    oss_access_key_id: String,
+    x_oss_object_acl: String,
+    x_oss_forbid_overwrite: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::storage::mock_http::{self, Reply};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_upload_with_policy_returns_oss_url() {
+        let form = Arc::new(Mutex::new(String::new()));
+        let received = form.clone();
+        let host = Arc::new(Mutex::new(String::new()));
+        let upload_host = host.clone();
+        let base = mock_http::serve(move |req| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/uploads") => {
+                assert_eq!(req.header("Authorization"), Some("Bearer sk-test"));
+                assert_eq!(req.param("action"), Some("getPolicy"));
+                Reply::ok(format!(
+                    r#"{{"request_id":"r1","data":{{"policy":"cG9saWN5","signature":"c2ln",
+                    "upload_dir":"dashscope-instant/abc/2026-10-17","upload_host":"{}/oss",
+                    "expire_in_seconds":300,"max_file_size_mb":100,"capacity_limit_mb":999999,
+                    "oss_access_key_id":"LTAI","x_oss_object_acl":"private","x_oss_forbid_overwrite":"true"}}}}"#,
+                    upload_host.lock().unwrap()
+                ))
+            }
+            ("POST", "/oss") => {
+                *received.lock().unwrap() = String::from_utf8_lossy(&req.body).to_string();
+                Reply::ok("")
+            }
+            _ => Reply::status(404),
+        })
+        .await;
+        *host.lock().unwrap() = base.clone();
+
+        let store = DashScopeStore {
+            base,
+            ..DashScopeStore::new("sk-test".to_string())
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.wav");
+        std::fs::write(&path, b"RIFF").unwrap();
+        store
+            .upload(&path, "vocosphere/tmp/job.wav", &mut |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.presign("vocosphere/tmp/job.wav").await.unwrap(),
+            "oss://dashscope-instant/abc/2026-10-17/job.wav"
+        );
+        let form = form.lock().unwrap().clone();
+        assert!(form.contains("name=\"key\"\r\n\r\ndashscope-instant/abc/2026-10-17/job.wav\r\n"));
+        assert!(form.contains("name=\"OSSAccessKeyId\"\r\n\r\nLTAI\r\n"));
+        assert!(form.contains("filename=\"job.wav\""));
+        assert!(form.contains("\r\n\r\nRIFF\r\n"));
+
+        store.delete("vocosphere/tmp/job.wav").await.unwrap();
+        assert!(store.presign("vocosphere/tmp/job.wav").await.is_err());
+    }
+}
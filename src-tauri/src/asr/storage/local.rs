@@ -0,0 +1,95 @@
+// 本地 HTTP 目录：上传即复制到服务目录，Fun-ASR 通过 base_url 访问（也用于离线测试整条识别流程）
+use super::{ObjectStore, UploadEvent};
+use crate::asr::config::LocalHttpConfig;
+use anyhow::Context;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+pub struct LocalHttpStore {
+    config: LocalHttpConfig,
+}
+
+impl LocalHttpStore {
+    pub fn new(config: LocalHttpConfig) -> Self {
+        Self { config }
+    }
+
+    fn object_path(&self, key: &str) -> PathBuf {
+        Path::new(&self.config.directory).join(key)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalHttpStore {
+    async fn upload(
+        &self,
+        path: &Path,
+        key: &str,
+        on_event: &mut (dyn for<'e> FnMut(UploadEvent<'e>) + Send),
+    ) -> anyhow::Result<()> {
+        let target = self.object_path(key);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("无法创建目录 {}", parent.display()))?;
+        }
+        let total = tokio::fs::copy(path, &target)
+            .await
+            .with_context(|| format!("复制文件到 {} 失败", target.display()))?;
+        on_event(UploadEvent::Progress {
+            uploaded: total,
+            total,
+        });
+        Ok(())
+    }
+
+    async fn presign(&self, key: &str) -> anyhow::Result<String> {
+        Ok(format!(
+            "{}/{}",
+            self.config.base_url.trim_end_matches('/'),
+            key
+        ))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_file(self.object_path(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("a.wav");
+        std::fs::write(&input, b"RIFF").unwrap();
+        let store = LocalHttpStore::new(LocalHttpConfig {
+            directory: dir.path().join("www").to_string_lossy().to_string(),
+            base_url: "http://127.0.0.1:8000/asr/".to_string(),
+        });
+
+        let mut uploaded = 0;
+        store
+            .upload(&input, "tmp/a.wav", &mut |event| {
+                if let UploadEvent::Progress { uploaded: n, .. } = event {
+                    uploaded = n;
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(uploaded, 4);
+        assert_eq!(
+            store.presign("tmp/a.wav").await.unwrap(),
+            "http://127.0.0.1:8000/asr/tmp/a.wav"
+        );
+
+        store.delete("tmp/a.wav").await.unwrap();
+        assert!(!dir.path().join("www/tmp/a.wav").exists());
+        store.delete("tmp/a.wav").await.unwrap();
+    }
+}
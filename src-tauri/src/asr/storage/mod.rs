@@ -0,0 +1,106 @@
+// 文件识别的对象存储：上传本地音视频，生成 Fun-ASR 可读取的 URL，识别结束后删除
+pub mod dashscope;
+pub mod local;
+#[cfg(test)]
+pub mod mock_http;
+mod multipart;
+pub mod oss;
+pub mod s3;
+
+use crate::asr::config::{CloudAsrConfig, StorageBackend};
+use async_trait::async_trait;
+use std::path::Path;
+
+/// 上传过程中的回调事件
+pub enum UploadEvent<'a> {
+    /// 已初始化分片上传（调用方应记录 upload_id，以便取消或重启后中止）
+    Initiated(&'a str),
+    /// 已上传字节数
+    Progress { uploaded: u64, total: u64 },
+}
+
+/// 对象存储后端
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// 上传本地文件到 key
+    async fn upload(
+        &self,
+        path: &Path,
+        key: &str,
+        on_event: &mut (dyn for<'e> FnMut(UploadEvent<'e>) + Send),
+    ) -> anyhow::Result<()>;
+
+    /// 返回 Fun-ASR 可读取的 URL（通常是限时签名 URL）
+    async fn presign(&self, key: &str) -> anyhow::Result<String>;
+
+    /// 删除对象（对象不存在时也返回 Ok）
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// 中止未完成的分片上传；不支持分片上传的后端无需实现
+    async fn abort_upload(&self, _key: &str, _upload_id: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// 按配置创建存储后端，缺少必填项时报错
+pub fn build(config: &CloudAsrConfig) -> anyhow::Result<Box<dyn ObjectStore>> {
+    match config.storage {
+        StorageBackend::Oss => {
+            let oss = &config.oss;
+            if oss.bucket.is_empty()
+                || oss.access_key_id.is_empty()
+                || oss.access_key_secret.is_empty()
+            {
+                anyhow::bail!("请先配置 OSS（Bucket、AccessKey ID 与 AccessKey Secret）");
+            }
+            Ok(Box::new(oss::OssStore::new(oss.clone())))
+        }
+        StorageBackend::S3 => {
+            let s3 = &config.s3;
+            if s3.endpoint.is_empty()
+                || s3.bucket.is_empty()
+                || s3.access_key_id.is_empty()
+                || s3.secret_access_key.is_empty()
+            {
+                anyhow::bail!("请先配置 S3 存储（Endpoint、Bucket 与访问密钥）");
+            }
+            Ok(Box::new(s3::S3Store::new(s3.clone())?))
+        }
+        StorageBackend::Dashscope => {
+            if config.file_asr_api_key.is_empty() {
+                anyhow::bail!("请先配置文件识别 API Key");
+            }
+            Ok(Box::new(dashscope::DashScopeStore::new(
+                config.file_asr_api_key.clone(),
+            )))
+        }
+        StorageBackend::LocalHttp => {
+            let local = &config.local_http;
+            if local.directory.is_empty() || local.base_url.is_empty() {
+                anyhow::bail!("请先配置本地 HTTP 目录和访问地址");
+            }
+            Ok(Box::new(local::LocalHttpStore::new(local.clone())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_requires_backend_config() {
+        let config = CloudAsrConfig::default();
+        let err = build(&config).err().unwrap();
+        assert!(err.to_string().contains("OSS"));
+
+        let mut config = CloudAsrConfig {
+            storage: StorageBackend::LocalHttp,
+            ..CloudAsrConfig::default()
+        };
+        assert!(build(&config).is_err());
+        config.local_http.directory = "/srv/asr".to_string();
+        config.local_http.base_url = "http://127.0.0.1:8000".to_string();
+        assert!(build(&config).is_ok());
+    }
+}
@@ -0,0 +1,286 @@
+// S3 兼容的上传协议（OSS 与 S3 相同）：小文件单次 PUT，大文件分片上传
+// 分片从磁盘按片读取、有限并发、逐片重试，失败时中止（Abort）已初始化的分片上传
+use super::UploadEvent;
+use anyhow::Context;
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use reqwest::{Method, RequestBuilder, Response};
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// 默认分片大小；不超过一个分片的文件直接单次 PUT
+pub const PART_SIZE: u64 = 8 * 1024 * 1024;
+/// 同时上传的分片数（内存占用约为 分片大小 × 并发数）
+const PART_CONCURRENCY: usize = 4;
+/// 单个分片的最大尝试次数
+pub const PART_ATTEMPTS: u32 = 3;
+
+/// 提供带签名请求的存储服务
+pub trait SignedStore: Sync {
+    /// 日志和错误信息中的服务名
+    fn service(&self) -> &'static str;
+
+    fn part_size(&self) -> u64;
+
+    /// sub_resource 形如 "partNumber=1&uploadId=xxx"（已按参数名排序），同时用于 URL 和签名
+    fn request(
+        &self,
+        method: Method,
+        key: &str,
+        sub_resource: Option<&str>,
+        content_type: &str,
+    ) -> RequestBuilder;
+}
+
+/// 上传本地文件
+pub async fn upload_file(
+    store: &impl SignedStore,
+    path: &Path,
+    key: &str,
+    content_type: &str,
+    on_event: &mut (dyn FnMut(UploadEvent<'_>) + Send),
+) -> anyhow::Result<()> {
+    let service = store.service();
+    let total = tokio::fs::metadata(path)
+        .await
+        .with_context(|| format!("无法读取文件: {}", path.display()))?
+        .len();
+
+    if total <= store.part_size() {
+        let data = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("无法读取文件: {}", path.display()))?;
+        let resp = store
+            .request(Method::PUT, key, None, content_type)
+            .body(data)
+            .send()
+            .await
+            .with_context(|| format!("{} PUT 请求失败", service))?;
+        check_status(resp, &format!("{} 上传失败", service)).await?;
+        on_event(UploadEvent::Progress {
+            uploaded: total,
+            total,
+        });
+        return Ok(());
+    }
+
+    let upload_id = initiate(store, key, content_type).await?;
+    on_event(UploadEvent::Initiated(&upload_id));
+    info!(
+        "{} 分片上传已初始化: {}（{} 字节，{} 片）",
+        service,
+        key,
+        total,
+        total.div_ceil(store.part_size())
+    );
+
+    let result = async {
+        let etags = upload_parts(store, path, key, &upload_id, total, on_event).await?;
+        complete(store, key, &upload_id, &etags).await
+    }
+    .await;
+    if result.is_err() {
+        if let Err(e) = abort(store, key, &upload_id).await {
+            warn!("中止 {} 分片上传失败: {}", service, e);
+        }
+    }
+    result
+}
+
+/// 中止分片上传，释放已上传的分片
+pub async fn abort(store: &impl SignedStore, key: &str, upload_id: &str) -> anyhow::Result<()> {
+    let sub = format!("uploadId={}", upload_id);
+    let resp = store
+        .request(Method::DELETE, key, Some(&sub), "")
+        .send()
+        .await
+        .with_context(|| format!("{} 中止分片上传请求失败", store.service()))?;
+    if resp.status().as_u16() != 404 {
+        check_status(resp, &format!("{} 中止分片上传失败", store.service())).await?;
+    }
+    Ok(())
+}
+
+pub async fn delete(store: &impl SignedStore, key: &str) -> anyhow::Result<()> {
+    let resp = store
+        .request(Method::DELETE, key, None, "")
+        .send()
+        .await
+        .with_context(|| format!("{} DELETE 请求失败", store.service()))?;
+    if resp.status().as_u16() != 404 {
+        check_status(resp, &format!("{} 删除失败", store.service())).await?;
+    }
+    Ok(())
+}
+
+/// 并发上传全部分片，返回按分片序号排列的 ETag
+async fn upload_parts(
+    store: &impl SignedStore,
+    path: &Path,
+    key: &str,
+    upload_id: &str,
+    total: u64,
+    on_event: &mut (dyn FnMut(UploadEvent<'_>) + Send),
+) -> anyhow::Result<Vec<String>> {
+    let part_size = store.part_size();
+    let part_count = total.div_ceil(part_size) as usize;
+    let mut parts = stream::iter(0..part_count)
+        .map(|index| async move {
+            let offset = index as u64 * part_size;
+            let len = part_size.min(total - offset);
+            let data = read_part(path, offset, len).await?;
+            let etag = upload_part_with_retry(store, key, upload_id, index + 1, data).await?;
+            anyhow::Ok((index, etag, len))
+        })
+        .buffer_unordered(PART_CONCURRENCY);
+
+    let mut etags = vec![String::new(); part_count];
+    let mut uploaded = 0;
+    while let Some(part) = parts.next().await {
+        let (index, etag, len) = part?;
+        etags[index] = etag;
+        uploaded += len;
+        on_event(UploadEvent::Progress { uploaded, total });
+    }
+    Ok(etags)
+}
+
+async fn upload_part_with_retry(
+    store: &impl SignedStore,
+    key: &str,
+    upload_id: &str,
+    part_number: usize,
+    data: Vec<u8>,
+) -> anyhow::Result<String> {
+    let mut attempt = 1;
+    loop {
+        match upload_part(store, key, upload_id, part_number, data.clone()).await {
+            Ok(etag) => return Ok(etag),
+            Err(err) if attempt < PART_ATTEMPTS => {
+                warn!(
+                    "分片 {} 上传失败（第 {} 次），稍后重试: {:#}",
+                    part_number, attempt, err
+                );
+                tokio::time::sleep(tokio::time::Duration::from_millis(500 * attempt as u64)).await;
+                attempt += 1;
+            }
+            Err(err) => {
+                return Err(err.context(format!("分片 {} 上传失败", part_number)));
+            }
+        }
+    }
+}
+
+async fn initiate(
+    store: &impl SignedStore,
+    key: &str,
+    content_type: &str,
+) -> anyhow::Result<String> {
+    let service = store.service();
+    let resp = store
+        .request(Method::POST, key, Some("uploads"), content_type)
+        .send()
+        .await
+        .with_context(|| format!("{} 初始化分片上传请求失败", service))?;
+    let body = check_status(resp, &format!("{} 初始化分片上传失败", service))
+        .await?
+        .text()
+        .await?;
+    xml_value(&body, "UploadId").context("初始化分片上传响应中没有 UploadId")
+}
+
+async fn upload_part(
+    store: &impl SignedStore,
+    key: &str,
+    upload_id: &str,
+    part_number: usize,
+    data: Vec<u8>,
+) -> anyhow::Result<String> {
+    let service = store.service();
+    let sub = format!("partNumber={}&uploadId={}", part_number, upload_id);
+    let resp = store
+        .request(Method::PUT, key, Some(&sub), "")
+        .body(data)
+        .send()
+        .await
+        .with_context(|| format!("{} 分片上传请求失败", service))?;
+    let resp = check_status(resp, &format!("{} 分片上传失败", service)).await?;
+    resp.headers()
+        .get("ETag")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .context("分片上传响应中没有 ETag")
+}
+
+async fn complete(
+    store: &impl SignedStore,
+    key: &str,
+    upload_id: &str,
+    etags: &[String],
+) -> anyhow::Result<()> {
+    let mut body = String::from("<CompleteMultipartUpload>");
+    for (index, etag) in etags.iter().enumerate() {
+        body.push_str(&format!(
+            "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+            index + 1,
+            etag
+        ));
+    }
+    body.push_str("</CompleteMultipartUpload>");
+
+    let service = store.service();
+    let sub = format!("uploadId={}", upload_id);
+    let resp = store
+        .request(Method::POST, key, Some(&sub), "application/xml")
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("{} 完成分片上传请求失败", service))?;
+    check_status(resp, &format!("{} 完成分片上传失败", service)).await?;
+    Ok(())
+}
+
+async fn read_part(path: &Path, offset: u64, len: u64) -> anyhow::Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("无法读取文件: {}", path.display()))?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    let mut data = vec![0; len as usize];
+    file.read_exact(&mut data).await?;
+    Ok(data)
+}
+
+pub async fn check_status(resp: Response, what: &str) -> anyhow::Result<Response> {
+    if resp.status().is_success() {
+        return Ok(resp);
+    }
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    anyhow::bail!("{}: HTTP {} — {}", what, status, body)
+}
+
+/// 取 XML 中第一个 <tag> 的文本（响应结构简单，无需完整解析）
+fn xml_value(body: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = body.find(&open)? + open.len();
+    let end = start + body[start..].find(&close)?;
+    Some(body[start..end].to_string())
+}
+
+/// 查询参数 / 路径编码（RFC 3986 非保留字符之外全部转义；keep_slash 用于对象路径）
+pub fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric()
+            || matches!(b, b'-' | b'_' | b'.' | b'~')
+            || (keep_slash && b == b'/')
+        {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    encoded
+}
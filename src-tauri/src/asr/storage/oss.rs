@@ -0,0 +1,415 @@
+// 阿里云 OSS（V1 签名）：对象以私有 ACL 上传，Fun-ASR 通过限时的签名 URL 读取
+use super::multipart::{self, uri_encode, SignedStore, PART_SIZE};
+use super::{ObjectStore, UploadEvent};
+use crate::asr::config::OssConfig;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, RequestBuilder};
+use sha1::Sha1;
+use std::path::Path;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// 上传对象的 ACL：始终私有，不受 Bucket 默认 ACL 影响
+const OBJECT_ACL_HEADER: &str = "x-oss-object-acl:private\n";
+
+pub struct OssStore {
+    config: OssConfig,
+    client: Client,
+    part_size: u64,
+}
+
+impl OssStore {
+    pub fn new(config: OssConfig) -> Self {
+        Self {
+            config,
+            client: Client::new(),
+            part_size: PART_SIZE,
+        }
+    }
+
+    /// 对象 URL：endpoint 带协议时（兼容服务、本地测试服务）使用 path-style，否则为 OSS 的虚拟主机风格
+    fn object_url(&self, object_key: &str) -> String {
+        let endpoint = self.config.endpoint.trim_end_matches('/');
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            format!("{}/{}/{}", endpoint, self.config.bucket, object_key)
+        } else {
+            format!("https://{}.{}/{}", self.config.bucket, endpoint, object_key)
+        }
+    }
+
+    /// 生成限时的 GET 签名 URL（V1 URL 签名），有效期见 OssConfig::presign_expires_secs
+    fn presign_get(&self, object_key: &str) -> String {
+        let expires = chrono::Utc::now().timestamp() + self.config.presign_expires_secs as i64;
+        let signature = sign_oss_url(
+            &self.config.access_key_secret,
+            expires,
+            &format!("/{}/{}", self.config.bucket, object_key),
+        );
+        format!(
+            "{}?OSSAccessKeyId={}&Expires={}&Signature={}",
+            self.object_url(object_key),
+            uri_encode(&self.config.access_key_id, false),
+            expires,
+            uri_encode(&signature, false)
+        )
+    }
+}
+
+impl SignedStore for OssStore {
+    fn service(&self) -> &'static str {
+        "OSS"
+    }
+
+    fn part_size(&self) -> u64 {
+        self.part_size
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        key: &str,
+        sub_resource: Option<&str>,
+        content_type: &str,
+    ) -> RequestBuilder {
+        let date = chrono::Utc::now()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        let mut resource = format!("/{}/{}", self.config.bucket, key);
+        let mut url = self.object_url(key);
+        if let Some(sub) = sub_resource {
+            resource = format!("{}?{}", resource, sub);
+            url = format!("{}?{}", url, sub);
+        }
+        // 创建对象的请求（单次 PUT、初始化分片上传）带上私有 ACL
+        let creates_object = matches!(
+            (&method, sub_resource),
+            (&Method::PUT, None) | (&Method::POST, Some("uploads"))
+        );
+        let oss_headers = if creates_object {
+            OBJECT_ACL_HEADER
+        } else {
+            ""
+        };
+        let signature = sign_oss(
+            &self.config.access_key_secret,
+            method.as_str(),
+            content_type,
+            &date,
+            oss_headers,
+            &resource,
+        );
+
+        let mut builder = self
+            .client
+            .request(method, url)
+            .header("Date", date)
+            .header(
+                "Authorization",
+                format!("OSS {}:{}", self.config.access_key_id, signature),
+            );
+        if !content_type.is_empty() {
+            builder = builder.header("Content-Type", content_type);
+        }
+        for line in oss_headers.lines() {
+            if let Some((name, value)) = line.split_once(':') {
+                builder = builder.header(name, value);
+            }
+        }
+        builder
+    }
+}
+
+#[async_trait]
+impl ObjectStore for OssStore {
+    async fn upload(
+        &self,
+        path: &Path,
+        key: &str,
+        on_event: &mut (dyn for<'e> FnMut(UploadEvent<'e>) + Send),
+    ) -> anyhow::Result<()> {
+        multipart::upload_file(self, path, key, "application/octet-stream", on_event).await
+    }
+
+    async fn presign(&self, key: &str) -> anyhow::Result<String> {
+        Ok(self.presign_get(key))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        multipart::delete(self, key).await
+    }
+
+    async fn abort_upload(&self, key: &str, upload_id: &str) -> anyhow::Result<()> {
+        multipart::abort(self, key, upload_id).await
+    }
+}
+
+/// OSS V1 签名
+fn sign_oss(
+    access_key_secret: &str,
+    verb: &str,
+    content_type: &str,
+    date: &str,
+    canonicalized_headers: &str,
+    canonicalized_resource: &str,
+) -> String {
+    let string_to_sign = format!(
+        "{}\n\n{}\n{}\n{}{}",
+        verb, content_type, date, canonicalized_headers, canonicalized_resource
+    );
+    let mut mac = HmacSha1::new_from_slice(access_key_secret.as_bytes()).expect("HMAC key valid");
+    mac.update(string_to_sign.as_bytes());
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+/// OSS V1 URL 签名：GET 请求，Date 位置为过期时间戳
+fn sign_oss_url(access_key_secret: &str, expires: i64, canonicalized_resource: &str) -> String {
+    sign_oss(
+        access_key_secret,
+        "GET",
+        "",
+        &expires.to_string(),
+        "",
+        canonicalized_resource,
+    )
+}
+
+/// OSS v1 签名：PUT 含 x-oss-object-acl:private
+#[cfg(test)]
+fn sign_oss_put(
+    access_key_secret: &str,
+    content_type: &str,
+    date: &str,
+    bucket: &str,
+    object_key: &str,
+) -> String {
+    sign_oss(
+        access_key_secret,
+        "PUT",
+        content_type,
+        date,
+        OBJECT_ACL_HEADER,
+        &format!("/{}/{}", bucket, object_key),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::storage::mock_http::{self, Reply};
+    use crate::asr::storage::multipart::PART_ATTEMPTS;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_sign_oss_put_is_base64() {
+        let sig = sign_oss_put(
+            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+            "application/octet-stream",
+            "Thu, 01 Jan 2026 00:00:00 GMT",
+            "my-bucket",
+            "vocosphere/tmp/test.wav",
+        );
+        assert!(!sig.is_empty());
+        assert!(sig
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '+' || c == '/' || c == '='));
+    }
+
+    #[test]
+    fn test_sign_oss_put_deterministic() {
+        let sig1 = sign_oss_put(
+            "secret",
+            "audio/wav",
+            "Mon, 01 Jan 2024 12:00:00 GMT",
+            "bucket",
+            "key.wav",
+        );
+        let sig2 = sign_oss_put(
+            "secret",
+            "audio/wav",
+            "Mon, 01 Jan 2024 12:00:00 GMT",
+            "bucket",
+            "key.wav",
+        );
+        assert_eq!(sig1, sig2);
+    }
+
+    #[test]
+    fn test_sign_oss_put_private_acl_vector() {
+        // 期望值用 openssl dgst -sha1 -hmac 独立计算
+        let sig = sign_oss_put(
+            "secret",
+            "audio/wav",
+            "Mon, 01 Jan 2024 12:00:00 GMT",
+            "bucket",
+            "key.wav",
+        );
+        assert_eq!(sig, "NY95FY6YnjMjsqmGH3WEfm6SEL4=");
+    }
+
+    #[test]
+    fn test_sign_oss_url_known_vector() {
+        // 阿里云 OSS 文档中 URL 签名的示例
+        let sig = sign_oss_url(
+            "OtxrzxIsfpFjA7SwPzILwy8Bw21TLhquhboDYROV",
+            1141889120,
+            "/oss-example/oss-api.pdf",
+        );
+        assert_eq!(sig, "EwaNTn1erJGkimiJ9WmXgwnANLc=");
+    }
+
+    #[test]
+    fn test_presign_get_url() {
+        let config = OssConfig {
+            presign_expires_secs: 600,
+            ..mock_config("oss-cn-beijing.aliyuncs.com".to_string())
+        };
+        let url = OssStore::new(config).presign_get("vocosphere/tmp/a.wav");
+        let (base, query) = url.split_once('?').unwrap();
+        assert_eq!(
+            base,
+            "https://bucket.oss-cn-beijing.aliyuncs.com/vocosphere/tmp/a.wav"
+        );
+
+        let params: BTreeMap<&str, &str> = query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .collect();
+        assert_eq!(params["OSSAccessKeyId"], "id");
+        let expires: i64 = params["Expires"].parse().unwrap();
+        let remaining = expires - chrono::Utc::now().timestamp();
+        assert!((590..=600).contains(&remaining), "{}", remaining);
+        let signature = sign_oss_url("secret", expires, "/bucket/vocosphere/tmp/a.wav");
+        assert_eq!(params["Signature"], uri_encode(&signature, false));
+        assert!(!params["Signature"].contains(['+', '/', '=']));
+    }
+
+    /// 本地 OSS 模拟服务：记录收到的请求，分片按序号保存，可让指定分片失败若干次
+    #[derive(Default)]
+    struct MockOss {
+        requests: Vec<String>,
+        parts: BTreeMap<usize, Vec<u8>>,
+        objects: BTreeMap<String, Vec<u8>>,
+        /// 分片序号 → 剩余失败次数
+        failures: BTreeMap<usize, u32>,
+    }
+
+    async fn serve(mock: Arc<Mutex<MockOss>>) -> String {
+        mock_http::serve(move |req| {
+            let mut mock = mock.lock().unwrap();
+            mock.requests
+                .push(format!("{} {}?{}", req.method, req.path, req.query));
+            match (req.method.as_str(), req.query.as_str()) {
+                ("POST", "uploads") => {
+                    assert_eq!(req.header("x-oss-object-acl"), Some("private"));
+                    Reply::ok("<InitiateMultipartUploadResult><UploadId>upload-1</UploadId></InitiateMultipartUploadResult>")
+                }
+                ("PUT", q) if q.starts_with("partNumber=") => {
+                    let part: usize = req.param("partNumber").unwrap().parse().unwrap();
+                    if let Some(left) = mock.failures.get_mut(&part).filter(|n| **n > 0) {
+                        *left -= 1;
+                        return Reply::status(500);
+                    }
+                    mock.parts.insert(part, req.body);
+                    Reply::ok("").header("ETag", &format!("\"etag-{}\"", part))
+                }
+                ("POST", q) if q.starts_with("uploadId=") => {
+                    let text = String::from_utf8_lossy(&req.body).to_string();
+                    assert_eq!(text.matches("<Part>").count(), mock.parts.len());
+                    let data: Vec<u8> = mock.parts.values().flatten().copied().collect();
+                    mock.objects.insert(req.path, data);
+                    Reply::ok("")
+                }
+                ("PUT", "") => {
+                    mock.objects.insert(req.path, req.body);
+                    Reply::ok("")
+                }
+                ("DELETE", _) => Reply::status(204),
+                _ => Reply::status(400),
+            }
+        })
+        .await
+    }
+
+    fn mock_config(endpoint: String) -> OssConfig {
+        OssConfig {
+            endpoint,
+            bucket: "bucket".to_string(),
+            access_key_id: "id".to_string(),
+            access_key_secret: "secret".to_string(),
+            ..OssConfig::default()
+        }
+    }
+
+    fn write_fixture(dir: &Path, len: usize) -> std::path::PathBuf {
+        let path = dir.join("input.bin");
+        let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_retries_and_reports_progress() {
+        let mock = Arc::new(Mutex::new(MockOss::default()));
+        mock.lock().unwrap().failures.insert(2, 1);
+        let store = OssStore {
+            part_size: 1000,
+            ..OssStore::new(mock_config(serve(mock.clone()).await))
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_fixture(dir.path(), 4500);
+
+        let mut upload_id = None;
+        let mut progress = Vec::new();
+        store
+            .upload(&path, "tmp/a.bin", &mut |event| match event {
+                UploadEvent::Initiated(id) => upload_id = Some(id.to_string()),
+                UploadEvent::Progress { uploaded, total } => progress.push((uploaded, total)),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(upload_id.as_deref(), Some("upload-1"));
+        assert_eq!(progress.len(), 5);
+        assert_eq!(progress.last(), Some(&(4500, 4500)));
+
+        let mock = mock.lock().unwrap();
+        assert_eq!(
+            mock.objects["/bucket/tmp/a.bin"],
+            std::fs::read(&path).unwrap()
+        );
+        // 分片 2 失败一次后重试成功
+        let part2 = mock
+            .requests
+            .iter()
+            .filter(|r| r.contains("partNumber=2&"))
+            .count();
+        assert_eq!(part2, 2);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_aborts_on_failure() {
+        let mock = Arc::new(Mutex::new(MockOss::default()));
+        mock.lock().unwrap().failures.insert(3, PART_ATTEMPTS);
+        let store = OssStore {
+            part_size: 1000,
+            ..OssStore::new(mock_config(serve(mock.clone()).await))
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_fixture(dir.path(), 3500);
+
+        let err = store
+            .upload(&path, "tmp/b.bin", &mut |_| {})
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("分片 3"));
+
+        let mock = mock.lock().unwrap();
+        assert!(mock
+            .requests
+            .contains(&"DELETE /bucket/tmp/b.bin?uploadId=upload-1".to_string()));
+        assert!(mock.objects.is_empty());
+    }
+}
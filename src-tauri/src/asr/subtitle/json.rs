@@ -0,0 +1,49 @@
+use crate::asr::events::AsrResultEvent;
+use std::path::Path;
+
+/// 将识别结果写出为 JSON 文件（保留说话人、字级时间戳等完整信息）
+/// 只写 is_final=true 的结果
+pub fn write_json(events: &[AsrResultEvent], path: &Path) -> anyhow::Result<()> {
+    let finals: Vec<&AsrResultEvent> = events.iter().filter(|e| e.is_final).collect();
+    std::fs::write(path, serde_json::to_string_pretty(&finals)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asr::events::{AsrResultKind, SentenceDetail, WordTiming};
+
+    #[test]
+    fn test_write_json_keeps_detail() {
+        let events = vec![AsrResultEvent {
+            sentence_id: 0,
+            begin_time: 100,
+            end_time: Some(900),
+            text: "你好。".to_string(),
+            is_final: true,
+            kind: AsrResultKind::Transcription,
+            lang: None,
+            session_id: None,
+            source: None,
+            detail: Some(SentenceDetail {
+                speaker_id: Some(0),
+                channel_id: Some(0),
+                words: vec![WordTiming {
+                    begin_time: 100,
+                    end_time: 900,
+                    text: "你好".to_string(),
+                    punctuation: "。".to_string(),
+                }],
+            }),
+        }];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        write_json(&events, &path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[0]["text"], "你好。");
+        assert_eq!(json[0]["speaker_id"], 0);
+        assert_eq!(json[0]["words"][0]["begin_time"], 100);
+    }
+}
@@ -1 +1,18 @@
+pub mod json;
 pub mod srt;
+
+use crate::asr::events::AsrResultEvent;
+use std::path::Path;
+
+/// 按输出文件扩展名写出识别结果：.json 为 JSON，其余为 SRT 字幕
+pub fn write_transcript(events: &[AsrResultEvent], path: &Path) -> anyhow::Result<()> {
+    let is_json = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+    if is_json {
+        json::write_json(events, path)
+    } else {
+        srt::write_srt(events, path)
+    }
+}
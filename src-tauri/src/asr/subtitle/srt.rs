@@ -12,7 +12,8 @@ fn format_srt_time(ms: u64) -> String {
 }
 
 /// 将识别结果写出为 SRT 字幕文件
-/// 只写 is_final=true 的结果；end_time 缺失时用 begin_time + 2000ms 补全
+/// 只写 is_final=true 的结果；end_time 缺失时用 begin_time + 2000ms 补全；
+/// 带说话人信息时在文本前加「[说话人N]」
 pub fn write_srt(events: &[AsrResultEvent], path: &Path) -> anyhow::Result<()> {
     let mut content = String::new();
     let finals: Vec<&AsrResultEvent> = events.iter().filter(|e| e.is_final).collect();
@@ -21,7 +22,10 @@ pub fn write_srt(events: &[AsrResultEvent], path: &Path) -> anyhow::Result<()> {
         let end = format_srt_time(event.end_time.unwrap_or(event.begin_time + 2000));
         writeln!(content, "{}", idx + 1).unwrap();
         writeln!(content, "{} --> {}", begin, end).unwrap();
-        writeln!(content, "{}", event.text).unwrap();
+        match event.detail.as_ref().and_then(|d| d.speaker_id) {
+            Some(speaker) => writeln!(content, "[说话人{}] {}", speaker + 1, event.text).unwrap(),
+            None => writeln!(content, "{}", event.text).unwrap(),
+        }
         writeln!(content).unwrap();
     }
     std::fs::write(path, content)?;
@@ -31,7 +35,7 @@ pub fn write_srt(events: &[AsrResultEvent], path: &Path) -> anyhow::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::asr::events::AsrResultKind;
+    use crate::asr::events::{AsrResultKind, SentenceDetail};
 
     #[test]
     fn test_format_zero() {
@@ -59,6 +63,9 @@ mod tests {
                 is_final: true,
                 kind: AsrResultKind::Transcription,
                 lang: None,
+                session_id: None,
+                source: None,
+                detail: None,
             },
             AsrResultEvent {
                 sentence_id: 1,
@@ -68,6 +75,9 @@ mod tests {
                 is_final: true,
                 kind: AsrResultKind::Transcription,
                 lang: None,
+                session_id: None,
+                source: None,
+                detail: None,
             },
         ];
         let dir = tempfile::tempdir().unwrap();
@@ -91,6 +101,9 @@ mod tests {
                 is_final: false,
                 kind: AsrResultKind::Transcription,
                 lang: None,
+                session_id: None,
+                source: None,
+                detail: None,
             },
             AsrResultEvent {
                 sentence_id: 0,
@@ -100,6 +113,9 @@ mod tests {
                 is_final: true,
                 kind: AsrResultKind::Transcription,
                 lang: None,
+                session_id: None,
+                source: None,
+                detail: None,
             },
         ];
         let dir = tempfile::tempdir().unwrap();
@@ -109,4 +125,28 @@ mod tests {
         assert!(!content.contains("临时结果"));
         assert!(content.contains("最终结果"));
     }
+
+    #[test]
+    fn test_write_srt_speaker_label() {
+        let events = vec![AsrResultEvent {
+            sentence_id: 0,
+            begin_time: 0,
+            end_time: Some(1000),
+            text: "大家好".to_string(),
+            is_final: true,
+            kind: AsrResultKind::Transcription,
+            lang: None,
+            session_id: None,
+            source: None,
+            detail: Some(SentenceDetail {
+                speaker_id: Some(1),
+                ..Default::default()
+            }),
+        }];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.srt");
+        write_srt(&events, &path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("[说话人2] 大家好"));
+    }
 }
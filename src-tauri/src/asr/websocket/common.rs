@@ -1,14 +1,15 @@
 // 共用模块：包含所有模型共用的 WebSocket 连接和音频处理逻辑
 // 注意：识别结果处理逻辑和协议相关逻辑已移至各模型目录下
 // 本模块不依赖任何模型的协议定义，保持完全独立
+use crate::asr::error::AsrError;
 use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
 use log::{info, warn};
 use std::time::Instant;
 use tokio::net::TcpStream;
-use tokio::sync::mpsc;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tungstenite::client::IntoClientRequest;
+use tungstenite::http::HeaderValue;
 use tungstenite::{Bytes, Message};
 
 // 音频处理工具：将f32采样转换为16位PCM字节流
@@ -30,20 +31,33 @@ pub(crate) mod audio_processor {
     }
 }
 
+pub(crate) type WsWrite = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+pub(crate) type WsRead = SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+
 pub(crate) struct WsStream {
-    pub ws_write: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-    pub ws_read: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    pub ws_write: WsWrite,
+    pub ws_read: WsRead,
 }
 
 /// 建立 WebSocket 连接
-pub(crate) async fn connect(ws_url: &str, api_key: &str) -> WsStream {
+pub(crate) async fn connect(ws_url: &str, api_key: &str) -> Result<WsStream, AsrError> {
     info!("websocket connecting to: {}", ws_url);
 
-    let mut request = ws_url.into_client_request().unwrap();
+    if api_key.trim().is_empty() {
+        return Err(AsrError::AuthFailed {
+            message: "未配置 API Key".to_string(),
+        });
+    }
+
+    let mut request = ws_url.into_client_request()?;
     let headers = request.headers_mut();
     headers.insert(
         "Authorization",
-        format!("Bearer {}", api_key).parse().unwrap(),
+        format!("Bearer {}", api_key)
+            .parse()
+            .map_err(|_| AsrError::AuthFailed {
+                message: "API Key 包含非法字符".to_string(),
+            })?,
     );
 
     // 内容检查：可通过环境变量 DISABLE_DATA_INSPECTION=true 禁用
@@ -54,16 +68,17 @@ pub(crate) async fn connect(ws_url: &str, api_key: &str) -> WsStream {
         .unwrap_or(false);
 
     if !disable_inspection {
-        headers.insert("X-DashScope-DataInspection", "enable".parse().unwrap());
+        headers.insert(
+            "X-DashScope-DataInspection",
+            HeaderValue::from_static("enable"),
+        );
         info!("📋 内容检查已启用（可通过 DISABLE_DATA_INSPECTION=true 禁用）");
     } else {
         warn!("⚠️ 内容检查已禁用（DISABLE_DATA_INSPECTION=true）");
     }
 
     let (ws_stream, response) =
-        tokio_tungstenite::connect_async_tls_with_config(request, None, false, None)
-            .await
-            .unwrap();
+        tokio_tungstenite::connect_async_tls_with_config(request, None, false, None).await?;
 
     // 打印连接响应信息
     info!("WebSocket 连接响应状态码: {}", response.status());
@@ -81,59 +96,53 @@ pub(crate) async fn connect(ws_url: &str, api_key: &str) -> WsStream {
     }
 
     let (ws_write, ws_read) = ws_stream.split();
-    WsStream { ws_write, ws_read }
+    Ok(WsStream { ws_write, ws_read })
+}
+
+/// 将一帧音频以 PCM16 二进制消息发送到 WebSocket
+/// 注意：不包含任何协议相关的逻辑，发送失败由调用者决定是否重连
+pub(crate) async fn send_audio_frame(
+    ws_write: &mut WsWrite,
+    samples: &[f32],
+) -> Result<(), tungstenite::Error> {
+    let pcm_bytes = audio_processor::f32_vec_to_pcm_bytes(samples);
+    ws_write.send(Message::Binary(Bytes::from(pcm_bytes))).await
 }
 
-/// 发送音频流到 WebSocket
-/// 返回 ws_write 供调用者发送结束指令
-/// 注意：不包含任何协议相关的逻辑，只负责音频流发送
-pub(crate) async fn send_audio_stream(
-    receiver: &mut mpsc::Receiver<Vec<f32>>,
-    mut ws_write: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
-) -> SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message> {
-    let start_time = Instant::now();
-    let mut total_bytes = 0u64;
-    let mut frame_count = 0u64;
-    let mut last_stats_time = Instant::now();
-
-    while let Some(samples) = receiver.recv().await {
-        // 转换f32音频数据为PCM字节流
-        let pcm_bytes = audio_processor::f32_vec_to_pcm_bytes(&samples);
-        let pcm_bytes_len = pcm_bytes.len();
-
-        // 发送音频数据到WebSocket（实时流，不延迟以保持低延迟）
-        if let Err(e) = ws_write.send(Message::Binary(Bytes::from(pcm_bytes))).await {
-            // 发送失败可能是连接断开，记录警告但不立即停止
-            // 让上层逻辑决定是否重连
-            warn!("⚠️ 发送音频失败: {}（连接可能已断开，继续尝试）", e);
-            // 短暂延迟后继续，避免快速重试导致资源浪费
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-            // 继续尝试发送（而不是立即返回），让调用者决定何时停止
+/// 发送统计（每 5 秒打印一次）
+pub(crate) struct SendStats {
+    start_time: Instant,
+    last_stats_time: Instant,
+    total_bytes: u64,
+    frame_count: u64,
+}
+
+impl SendStats {
+    pub fn new() -> Self {
+        Self {
+            start_time: Instant::now(),
+            last_stats_time: Instant::now(),
+            total_bytes: 0,
+            frame_count: 0,
         }
+    }
 
-        // 统计发送情况
-        total_bytes += pcm_bytes_len as u64;
-        frame_count += 1;
+    /// 记录一帧已发送的音频
+    pub fn record(&mut self, sample_count: usize) {
+        self.total_bytes += (sample_count * 2) as u64;
+        self.frame_count += 1;
 
-        // 每5秒打印一次发送统计
-        let elapsed = last_stats_time.elapsed();
-        if elapsed.as_secs() >= 5 {
-            let duration = start_time.elapsed().as_secs_f64();
-            let avg_bitrate = (total_bytes as f64 * 8.0 / duration) / 1000.0; // kbps
+        if self.last_stats_time.elapsed().as_secs() >= 5 {
+            let duration = self.start_time.elapsed().as_secs_f64();
+            let avg_bitrate = (self.total_bytes as f64 * 8.0 / duration) / 1000.0; // kbps
             info!(
                 "📤 发送统计: {} 帧, {:.1} KB, 平均 {:.1} kbps, 速率: {:.1} 帧/秒",
-                frame_count,
-                total_bytes as f64 / 1024.0,
+                self.frame_count,
+                self.total_bytes as f64 / 1024.0,
                 avg_bitrate,
-                frame_count as f64 / duration
+                self.frame_count as f64 / duration
             );
-            last_stats_time = Instant::now();
+            self.last_stats_time = Instant::now();
         }
-
-        // 实时流不需要延迟，让tokio调度器自然处理
-        tokio::task::yield_now().await;
     }
-
-    info!("音频流发送完成");
-    ws_write
 }
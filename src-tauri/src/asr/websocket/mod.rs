@@ -1,32 +1,46 @@
 mod common;
 pub mod gummy;
 pub mod paraformer;
+mod session;
 
 use crate::asr::config::{CloudStreamingConfig, GummyConfig, ParaformerConfig};
+use crate::asr::error::AsrError;
+use crate::asr::events::ResultOrigin;
+use crate::asr::session::AsrSession;
 
 /// 统一的流式 ASR 启动接口，按 CloudStreamingConfig 分发
+/// 首个任务建立成功后返回会话句柄，之后的收发与断线重连在后台进行
+/// sample_rate 为 receiver 中音频的采样率
 pub async fn start_asr_with_config(
     receiver: Option<tokio::sync::mpsc::Receiver<Vec<f32>>>,
+    sample_rate: u32,
     config: CloudStreamingConfig,
-) {
+    origin: ResultOrigin,
+) -> Result<AsrSession, AsrError> {
     match config {
-        CloudStreamingConfig::Gummy(c) => start_gummy_asr(receiver, c).await,
-        CloudStreamingConfig::Paraformer(c) => start_paraformer_asr(receiver, c).await,
+        CloudStreamingConfig::Gummy(c) => start_gummy_asr(receiver, sample_rate, c, origin).await,
+        CloudStreamingConfig::Paraformer(c) => {
+            start_paraformer_asr(receiver, sample_rate, c, origin).await
+        }
     }
 }
 
 /// 启动 Gummy ASR 服务
 pub async fn start_gummy_asr(
     receiver: Option<tokio::sync::mpsc::Receiver<Vec<f32>>>,
+    sample_rate: u32,
     config: GummyConfig,
-) {
-    gummy::start_with_config(receiver, config).await;
+    origin: ResultOrigin,
+) -> Result<AsrSession, AsrError> {
+    gummy::start_with_config(receiver, sample_rate, config, origin).await
 }
 
 /// 启动 Paraformer ASR 服务
 pub async fn start_paraformer_asr(
     receiver: Option<tokio::sync::mpsc::Receiver<Vec<f32>>>,
+    sample_rate: u32,
     config: ParaformerConfig,
-) {
-    paraformer::start_with_config(receiver, config).await;
+    origin: ResultOrigin,
+) -> Result<AsrSession, AsrError> {
+    paraformer::start_with_config(receiver, sample_rate, config, origin).await
 }
@@ -0,0 +1,968 @@
+// 可重连的流式识别会话
+// 负责 WebSocket 生命周期：建连 → run-task → 发送音频/接收结果 → 断线重连 → finish-task
+// 模型相关的协议细节（run-task 参数、事件解析、结果处理）由 StreamingModel 实现提供
+use crate::asr::config::ServerConfig;
+use crate::asr::error::AsrError;
+use crate::asr::events::{AsrResultEvent, ResultOrigin};
+use crate::asr::session::{
+    publish_results, AsrSession, SessionCommand, SessionControl, SessionStatus,
+};
+use crate::asr::websocket::common::{connect, send_audio_frame, SendStats, WsStream, WsWrite};
+use futures::{SinkExt, StreamExt};
+use log::{debug, error, info, warn};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tungstenite::{Bytes, Message, Utf8Bytes};
+use uuid::Uuid;
+
+/// 默认音频采样率（文件测试任务使用；实时会话以 StreamingModel::sample_rate 为准）
+pub(crate) const STREAM_SAMPLE_RATE: u32 = 16000;
+
+/// 心跳间隔：定期发送 Ping，检测半开连接
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// 超过该时长未收到服务端任何消息（含 Pong），判定连接已死
+const DEAD_CONNECTION_TIMEOUT: Duration = Duration::from_secs(20);
+/// 重连期间最多缓存的音频时长（毫秒），超出后丢弃最旧的数据
+const MAX_PENDING_AUDIO_MS: u64 = 60_000;
+/// 发送 finish-task 后等待 task-finished 的最长时间
+const FINISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 服务端事件（与具体模型协议无关的抽象）
+#[derive(Debug)]
+pub(crate) enum ServerEvent {
+    TaskStarted,
+    /// 模型处理后的识别结果（已按时间轴偏移），由会话层推送到前端
+    Results(Vec<AsrResultEvent>),
+    TaskFinished,
+    TaskFailed {
+        code: String,
+        message: String,
+    },
+    /// 无需会话层处理的事件（包括模型判定为非致命的错误）
+    Other,
+}
+
+/// 时间轴偏移：跨任务重连后，将新任务的结果平移到整个会话的时间轴上
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TimelineOffset {
+    /// 之前所有任务已发送音频的总时长（毫秒）
+    pub time_ms: u64,
+    /// 之前所有任务已使用的句子 ID 数量
+    pub sentence_id: u32,
+    /// 当前任务内被 VAD 丢弃的静音：(任务内位置, 时长)，单位毫秒
+    gaps: Vec<(u64, u64)>,
+}
+
+impl TimelineOffset {
+    pub fn apply_time(&self, ms: u64) -> u64 {
+        let skipped: u64 = self
+            .gaps
+            .iter()
+            .filter(|(at, _)| *at <= ms)
+            .map(|(_, len)| len)
+            .sum();
+        ms + self.time_ms + skipped
+    }
+
+    /// 记录当前任务已发送 at_ms 音频后丢弃的静音
+    pub fn skip(&mut self, at_ms: u64, len_ms: u64) {
+        if len_ms > 0 {
+            self.gaps.push((at_ms, len_ms));
+        }
+    }
+
+    /// 任务结束：已发送音频和任务内的静音并入基准偏移
+    pub fn finish_task(&mut self, sent_ms: u64, sentence_id: u32) {
+        self.time_ms += sent_ms + self.gaps.drain(..).map(|(_, len)| len).sum::<u64>();
+        self.sentence_id = sentence_id;
+    }
+
+    pub fn apply_sentence_id(&self, id: u32) -> u32 {
+        id + self.sentence_id
+    }
+}
+
+/// 流式模型协议抽象（Gummy / Paraformer 各自实现）
+pub(crate) trait StreamingModel: Send + Sync + 'static {
+    /// 模型名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// WebSocket 服务配置
+    fn server_config(&self) -> &ServerConfig;
+
+    /// 发送音频的采样率（与 run-task 参数中的 sample_rate 保持一致）
+    fn sample_rate(&self) -> u32;
+
+    /// 构建 run-task 指令 JSON
+    fn run_task_message(&self, task_id: &str) -> Result<String, AsrError>;
+
+    /// 构建 finish-task 指令 JSON
+    fn finish_task_message(&self, task_id: &str) -> Result<String, AsrError>;
+
+    /// 解析并处理一条服务端文本消息；识别结果按 offset 平移后返回
+    fn handle_message(&mut self, text: &str, offset: &TimelineOffset) -> ServerEvent;
+
+    /// 下一个可用的会话级句子 ID（用于计算重连后的句子 ID 偏移）
+    fn next_sentence_id(&self) -> u32;
+}
+
+/// 指数退避重连策略
+#[derive(Debug, Clone)]
+pub(crate) struct Backoff {
+    initial: Duration,
+    max: Duration,
+    max_attempts: u32,
+    attempt: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(30), 10)
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration, max_attempts: u32) -> Self {
+        Self {
+            initial,
+            max,
+            max_attempts,
+            attempt: 0,
+        }
+    }
+
+    /// 下一次重连前的等待时长；超过最大重试次数返回 None
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempt >= self.max_attempts {
+            return None;
+        }
+        let factor = 1u32.checked_shl(self.attempt).unwrap_or(u32::MAX);
+        let delay = self.initial.saturating_mul(factor).min(self.max);
+        self.attempt += 1;
+        Some(delay)
+    }
+
+    /// 新任务确实正常工作（已产生识别结果）后重置计数
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+}
+
+/// 重连期间的音频缓存（按时长上限丢弃最旧数据）
+pub(crate) struct PendingAudio {
+    frames: VecDeque<Vec<f32>>,
+    samples: usize,
+    max_samples: usize,
+    /// 因缓存溢出被丢弃的样本数（计入时间轴，保证后续结果与真实时间对齐）
+    dropped_samples: u64,
+}
+
+impl PendingAudio {
+    pub fn new(max_ms: u64, sample_rate: u32) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            samples: 0,
+            max_samples: (max_ms * sample_rate as u64 / 1000) as usize,
+            dropped_samples: 0,
+        }
+    }
+
+    pub fn push_back(&mut self, frame: Vec<f32>) {
+        self.samples += frame.len();
+        self.frames.push_back(frame);
+        while self.samples > self.max_samples {
+            match self.frames.pop_front() {
+                Some(old) => {
+                    self.samples -= old.len();
+                    self.dropped_samples += old.len() as u64;
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// 发送失败的帧放回队首，保证顺序
+    pub fn push_front(&mut self, frame: Vec<f32>) {
+        self.samples += frame.len();
+        self.frames.push_front(frame);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Vec<f32>> {
+        let frame = self.frames.pop_front()?;
+        self.samples -= frame.len();
+        Some(frame)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 取出并清零已丢弃的样本数
+    pub fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped_samples)
+    }
+}
+
+/// 单个任务的结束方式
+#[derive(Debug)]
+enum TaskEnd {
+    /// 收到 task-finished，会话正常结束
+    Finished,
+    /// 因静音暂停而结束任务，等待新音频
+    Paused,
+    /// 连接断开或任务异常中断，需要重连
+    ConnectionLost,
+    /// 不可恢复的错误（如认证失败），放弃重连
+    Fatal(AsrError),
+    /// 会话被取消，直接断开
+    Cancelled,
+}
+
+/// 生成新的 task_id（32 位十六进制）
+pub(crate) fn new_task_id() -> String {
+    Uuid::new_v4().to_string().replace("-", "")
+}
+
+/// 建立连接并启动任务，等待 task-started
+pub(crate) async fn open_task<M: StreamingModel>(
+    model: &mut M,
+    task_id: &str,
+) -> Result<WsStream, AsrError> {
+    let server = model.server_config();
+    let mut ws = connect(&server.ws_url, &server.api_key).await?;
+
+    let run_task_json = model.run_task_message(task_id)?;
+    info!("run_task_json:{}", run_task_json);
+    ws.ws_write
+        .send(Message::Text(Utf8Bytes::from(run_task_json)))
+        .await?;
+    info!("已发送run-task指令 ({})", model.name());
+
+    // 握手阶段不会产生识别结果，偏移无意义
+    let offset = TimelineOffset::default();
+    while let Some(msg) = ws.ws_read.next().await {
+        match msg? {
+            Message::Text(text) => match model.handle_message(&text, &offset) {
+                ServerEvent::TaskStarted => {
+                    info!("收到task-started事件，开始发送音频流");
+                    return Ok(ws);
+                }
+                ServerEvent::TaskFailed { code, message } => {
+                    error!("❌ 任务启动失败: {} (错误代码: {})", message, code);
+                    return Err(AsrError::from_task_failed(&code, &message));
+                }
+                other => warn!("等待task-started，收到其他事件: {:?}", other),
+            },
+            Message::Close(frame) => {
+                return Err(AsrError::Protocol {
+                    message: format!("等待 task-started 时连接被关闭: {:?}", frame),
+                });
+            }
+            _ => {}
+        }
+    }
+    Err(AsrError::NetworkUnreachable {
+        message: "等待 task-started 时连接已断开".to_string(),
+    })
+}
+
+/// 启动可重连的流式识别会话
+/// 首个任务在当前任务中同步建立（失败直接返回），之后的收发与重连在后台进行，通过 AsrSession 控制
+pub(crate) async fn start<M: StreamingModel>(
+    mut model: M,
+    receiver: mpsc::Receiver<Vec<f32>>,
+    origin: ResultOrigin,
+) -> Result<AsrSession, AsrError> {
+    let task_id = new_task_id();
+    info!("task_id:{} , length:{}", task_id, task_id.len());
+    let ws = open_task(&mut model, &task_id).await.inspect_err(|e| {
+        error!("❌ 启动识别任务失败: {}", e);
+        e.emit();
+    })?;
+
+    info!("开始识别...");
+    Ok(AsrSession::spawn(SessionStatus::Running, move |control| {
+        run(
+            model,
+            receiver,
+            origin,
+            control,
+            ws,
+            task_id,
+            Backoff::default(),
+        )
+    }))
+}
+
+/// 会话运行期状态（跨任务保留）
+struct SessionState {
+    receiver: mpsc::Receiver<Vec<f32>>,
+    control: SessionControl,
+    pending: PendingAudio,
+    offset: TimelineOffset,
+    /// 音频输入已结束（channel 关闭或收到停止指令后读空）
+    input_closed: bool,
+    cancelled: bool,
+    sample_rate: u32,
+    /// 已请求静音暂停，当前任务发送 finish-task 后进入空闲
+    pausing: bool,
+    stats: SendStats,
+    /// 结果来源标识（写入每条推送的结果）
+    origin: ResultOrigin,
+    /// 已推送的最终结果（会话结束时返回）
+    finals: Vec<AsrResultEvent>,
+    /// 当前任务已产生识别结果（用于判断重连是否真正恢复）
+    task_has_results: bool,
+}
+
+impl SessionState {
+    /// 被 VAD 丢弃的静音（含暂停期间）按当前发送位置计入时间轴
+    fn record_skipped(&mut self, sent_samples: u64) {
+        let skipped = self.control.take_skipped_samples();
+        if skipped > 0 {
+            self.offset.skip(
+                samples_to_ms(sent_samples, self.sample_rate),
+                samples_to_ms(skipped, self.sample_rate),
+            );
+        }
+    }
+
+    /// 处理控制指令
+    fn apply_command(&mut self, command: SessionCommand) {
+        match command {
+            SessionCommand::Run => {}
+            SessionCommand::Pause => {
+                if !self.input_closed && !self.receiver.is_closed() {
+                    info!("⏸️ 持续静音，结束当前任务");
+                    self.pausing = true;
+                }
+            }
+            SessionCommand::Stop => {
+                info!("收到停止指令，发送剩余音频后结束任务");
+                // 关闭后 recv 仍会返回已缓冲的音频，读空后返回 None 触发 finish-task
+                self.receiver.close();
+                self.control.set_status(SessionStatus::Stopping);
+            }
+            SessionCommand::Cancel => {
+                info!("收到取消指令，立即结束识别会话");
+                self.cancelled = true;
+            }
+        }
+    }
+}
+
+/// 会话主循环：每轮驱动一个任务，连接断开时按指数退避重连并续传缓存音频
+/// 中途失败时仍返回已收集的最终结果，失败原因通过会话状态和 asr-error 事件上报
+async fn run<M: StreamingModel>(
+    mut model: M,
+    receiver: mpsc::Receiver<Vec<f32>>,
+    origin: ResultOrigin,
+    control: SessionControl,
+    first_ws: WsStream,
+    first_task_id: String,
+    mut backoff: Backoff,
+) -> Result<Vec<AsrResultEvent>, AsrError> {
+    let mut state = SessionState {
+        receiver,
+        control,
+        pending: PendingAudio::new(MAX_PENDING_AUDIO_MS, model.sample_rate()),
+        offset: TimelineOffset::default(),
+        input_closed: false,
+        cancelled: false,
+        sample_rate: model.sample_rate(),
+        pausing: false,
+        stats: SendStats::new(),
+        origin,
+        finals: Vec::new(),
+        task_has_results: false,
+    };
+    let mut current = Some((first_ws, first_task_id));
+    // 暂停后恢复：立即开启新任务，不做退避等待
+    let mut resuming = false;
+
+    let final_status = loop {
+        let (ws, task_id) = match current.take() {
+            Some(opened) => opened,
+            None if resuming => {
+                resuming = false;
+                let task_id = new_task_id();
+                match open_task(&mut model, &task_id).await {
+                    Ok(ws) => {
+                        info!("▶️ 检测到语音，开启新任务: {}", task_id);
+                        state.control.set_status(SessionStatus::Running);
+                        (ws, task_id)
+                    }
+                    Err(e) => {
+                        warn!("⚠️ 恢复识别失败: {}", e);
+                        e.emit();
+                        if !e.is_retryable() {
+                            break SessionStatus::Failed { error: e };
+                        }
+                        continue;
+                    }
+                }
+            }
+            None => {
+                // 重连：先按退避等待（期间持续缓存音频），再重新 run-task
+                let Some(delay) = backoff.next_delay() else {
+                    error!(
+                        "❌ 重连失败次数超过上限（{} 次），放弃识别会话",
+                        backoff.attempt()
+                    );
+                    let err = AsrError::NetworkUnreachable {
+                        message: format!("重连失败 {} 次，识别已停止", backoff.attempt()),
+                    };
+                    err.emit();
+                    break SessionStatus::Failed { error: err };
+                };
+                warn!(
+                    "🔄 {}ms 后进行第 {} 次重连...",
+                    delay.as_millis(),
+                    backoff.attempt()
+                );
+                state.control.set_status(SessionStatus::Reconnecting {
+                    attempt: backoff.attempt(),
+                });
+                buffer_for(delay, &mut state).await;
+                if state.cancelled {
+                    break SessionStatus::Cancelled;
+                }
+
+                let task_id = new_task_id();
+                match open_task(&mut model, &task_id).await {
+                    Ok(ws) => {
+                        // 任务启动后仍可能立即失败，收到识别结果后才重置退避计数
+                        info!("✅ 重连成功，新 task_id: {}", task_id);
+                        state.control.set_status(if state.receiver.is_closed() {
+                            SessionStatus::Stopping
+                        } else {
+                            SessionStatus::Running
+                        });
+                        (ws, task_id)
+                    }
+                    Err(e) => {
+                        warn!("⚠️ 重连失败: {}", e);
+                        e.emit();
+                        if !e.is_retryable() {
+                            error!("❌ 不可恢复的错误，放弃重连: {}", e);
+                            break SessionStatus::Failed { error: e };
+                        }
+                        continue;
+                    }
+                }
+            }
+        };
+
+        // 缓存溢出丢弃的音频也计入时间轴
+        state.offset.time_ms += samples_to_ms(state.pending.take_dropped(), state.sample_rate);
+
+        state.task_has_results = false;
+        let (end, sent_samples) = drive_task(&mut model, ws, &task_id, &mut state).await;
+        if state.task_has_results {
+            backoff.reset();
+        }
+
+        state.offset.finish_task(
+            samples_to_ms(sent_samples, state.sample_rate),
+            model.next_sentence_id(),
+        );
+        debug!(
+            "任务 {} 结束: {:?}，时间轴偏移: {:?}",
+            task_id, end, state.offset
+        );
+
+        // 暂停时任务结束（含 finish 超时、连接断开）都进入空闲等待
+        let end = match end {
+            TaskEnd::Finished | TaskEnd::ConnectionLost if state.pausing => TaskEnd::Paused,
+            end => end,
+        };
+
+        match end {
+            TaskEnd::Paused => {
+                state.pausing = false;
+                state.control.set_status(SessionStatus::Paused);
+                match wait_for_audio(&mut state).await {
+                    Some(frame) => {
+                        state.pending.push_back(frame);
+                        resuming = true;
+                    }
+                    None if state.cancelled => break SessionStatus::Cancelled,
+                    None => break SessionStatus::Finished,
+                }
+            }
+            TaskEnd::Finished => break SessionStatus::Finished,
+            TaskEnd::Cancelled => break SessionStatus::Cancelled,
+            TaskEnd::Fatal(e) => {
+                error!("❌ 不可恢复的错误，结束识别会话: {}", e);
+                break SessionStatus::Failed { error: e };
+            }
+            TaskEnd::ConnectionLost => {
+                if state.input_closed && state.pending.is_empty() {
+                    info!("音频输入已结束且无待发送数据，不再重连");
+                    break SessionStatus::Finished;
+                }
+                warn!("⚠️ WebSocket 连接中断，准备重连（音频将暂存，恢复后续传）");
+            }
+        }
+    };
+
+    info!("结束接收服务端数据，共 {} 条最终结果", state.finals.len());
+    state.control.set_status(final_status);
+    Ok(state.finals)
+}
+
+/// 驱动单个任务：先续传缓存音频，然后同时发送实时音频、接收识别结果和响应控制指令
+/// 返回任务结束方式及本任务实际发送的样本数
+async fn drive_task<M: StreamingModel>(
+    model: &mut M,
+    ws: WsStream,
+    task_id: &str,
+    state: &mut SessionState,
+) -> (TaskEnd, u64) {
+    let WsStream {
+        mut ws_write,
+        mut ws_read,
+    } = ws;
+    let mut sent_samples = 0u64;
+
+    // 续传重连期间缓存的音频
+    while let Some(frame) = state.pending.pop_front() {
+        state.record_skipped(sent_samples);
+        if let Err(e) = send_audio_frame(&mut ws_write, &frame).await {
+            warn!("⚠️ 续传缓存音频失败: {}", e);
+            state.pending.push_front(frame);
+            return (TaskEnd::ConnectionLost, sent_samples);
+        }
+        sent_samples += frame.len() as u64;
+        state.stats.record(frame.len());
+    }
+
+    // 发送 finish-task 的时刻；之后只接收剩余结果
+    let mut finishing_since = None;
+    if state.input_closed || state.pausing {
+        if send_finish_task(model, &mut ws_write, task_id)
+            .await
+            .is_err()
+        {
+            return (TaskEnd::ConnectionLost, sent_samples);
+        }
+        finishing_since = Some(Instant::now());
+    }
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat.tick().await;
+    let mut last_activity = Instant::now();
+
+    loop {
+        tokio::select! {
+            samples = state.receiver.recv(), if finishing_since.is_none() => match samples {
+                Some(frame) => {
+                    state.record_skipped(sent_samples);
+                    if let Err(e) = send_audio_frame(&mut ws_write, &frame).await {
+                        warn!("⚠️ 发送音频失败: {}（连接已断开，音频转入重连缓存）", e);
+                        state.pending.push_back(frame);
+                        return (TaskEnd::ConnectionLost, sent_samples);
+                    }
+                    sent_samples += frame.len() as u64;
+                    state.stats.record(frame.len());
+                }
+                None => {
+                    info!("音频流发送完成");
+                    state.input_closed = true;
+                    if send_finish_task(model, &mut ws_write, task_id).await.is_err() {
+                        return (TaskEnd::ConnectionLost, sent_samples);
+                    }
+                    finishing_since = Some(Instant::now());
+                }
+            },
+            msg = ws_read.next() => {
+                last_activity = Instant::now();
+                if let Some(end) = handle_ws_message(model, msg, state) {
+                    return (end, sent_samples);
+                }
+            },
+            command = state.control.changed(), if !state.cancelled => {
+                state.apply_command(command);
+                if state.cancelled {
+                    return (TaskEnd::Cancelled, sent_samples);
+                }
+                if state.pausing && finishing_since.is_none() {
+                    if send_finish_task(model, &mut ws_write, task_id).await.is_err() {
+                        return (TaskEnd::ConnectionLost, sent_samples);
+                    }
+                    finishing_since = Some(Instant::now());
+                }
+            },
+            _ = heartbeat.tick() => {
+                if finishing_since.is_some_and(|since| since.elapsed() > FINISH_TIMEOUT) {
+                    warn!(
+                        "⚠️ 发送 finish-task 后 {} 秒未收到 task-finished，强制结束",
+                        FINISH_TIMEOUT.as_secs()
+                    );
+                    return (TaskEnd::Finished, sent_samples);
+                }
+                if last_activity.elapsed() > DEAD_CONNECTION_TIMEOUT {
+                    warn!(
+                        "⚠️ {} 秒未收到服务端消息，判定连接已断开",
+                        DEAD_CONNECTION_TIMEOUT.as_secs()
+                    );
+                    return (TaskEnd::ConnectionLost, sent_samples);
+                }
+                if let Err(e) = ws_write.send(Message::Ping(Bytes::new())).await {
+                    warn!("⚠️ 发送心跳失败: {}", e);
+                    return (TaskEnd::ConnectionLost, sent_samples);
+                }
+            }
+        }
+    }
+}
+
+/// 处理一条 WebSocket 消息；返回 Some 表示当前任务结束
+fn handle_ws_message<M: StreamingModel>(
+    model: &mut M,
+    msg: Option<Result<Message, tungstenite::Error>>,
+    state: &mut SessionState,
+) -> Option<TaskEnd> {
+    let msg = match msg {
+        None => {
+            warn!("⚠️ WebSocket 读取结束（连接已断开）");
+            return Some(TaskEnd::ConnectionLost);
+        }
+        Some(Err(e)) => {
+            warn!("⚠️ WebSocket 消息接收错误: {}", e);
+            return Some(TaskEnd::ConnectionLost);
+        }
+        Some(Ok(msg)) => msg,
+    };
+
+    match msg {
+        Message::Text(text) => {
+            debug!("收到文本消息: {}", text);
+            match model.handle_message(&text, &state.offset) {
+                ServerEvent::Results(results) => {
+                    state.task_has_results |= !results.is_empty();
+                    publish_results(results, &state.origin, &mut state.finals);
+                    None
+                }
+                ServerEvent::TaskFinished => {
+                    info!("收到task-finished事件，任务完成");
+                    Some(TaskEnd::Finished)
+                }
+                ServerEvent::TaskFailed { code, message } => {
+                    error!("❌ 任务失败: {} (错误代码: {})", message, code);
+                    let err = AsrError::from_task_failed(&code, &message);
+                    err.emit();
+                    if err.is_retryable() {
+                        // 服务端在 task-failed 后会结束任务，通过重连开启新任务
+                        Some(TaskEnd::ConnectionLost)
+                    } else {
+                        Some(TaskEnd::Fatal(err))
+                    }
+                }
+                _ => None,
+            }
+        }
+        Message::Close(close_frame) => {
+            if let Some(ref frame) = close_frame {
+                warn!(
+                    "⚠️ WebSocket 连接已关闭: 代码={:?}, 原因={:?}",
+                    frame.code, frame.reason
+                );
+            } else {
+                warn!("⚠️ WebSocket 连接已关闭（无详细信息）");
+            }
+            Some(TaskEnd::ConnectionLost)
+        }
+        Message::Binary(_) => {
+            debug!("收到二进制消息（可能是音频响应）");
+            None
+        }
+        _ => None,
+    }
+}
+
+/// 发送 finish-task 指令
+async fn send_finish_task<M: StreamingModel>(
+    model: &M,
+    ws_write: &mut WsWrite,
+    task_id: &str,
+) -> Result<(), ()> {
+    let finish_task_json = match model.finish_task_message(task_id) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("序列化 finish-task 失败: {}", e);
+            return Err(());
+        }
+    };
+    if let Err(e) = ws_write
+        .send(Message::Text(Utf8Bytes::from(finish_task_json)))
+        .await
+    {
+        warn!("发送结束指令失败: {}（可能连接已断开）", e);
+        return Err(());
+    }
+    info!("已发送finish-task指令");
+    Ok(())
+}
+
+/// 在等待期间持续从 channel 读取音频放入缓存，避免采集端因 channel 满而丢帧
+async fn buffer_for(delay: Duration, state: &mut SessionState) {
+    let sleep = tokio::time::sleep(delay);
+    tokio::pin!(sleep);
+    loop {
+        tokio::select! {
+            _ = &mut sleep => return,
+            samples = state.receiver.recv(), if !state.input_closed => match samples {
+                Some(frame) => state.pending.push_back(frame),
+                None => state.input_closed = true,
+            },
+            command = state.control.changed(), if !state.cancelled => {
+                state.apply_command(command);
+                if state.cancelled {
+                    return;
+                }
+            },
+        }
+    }
+}
+
+/// 暂停期间等待新音频（不占用连接）；返回 None 表示输入结束或会话被取消
+async fn wait_for_audio(state: &mut SessionState) -> Option<Vec<f32>> {
+    loop {
+        tokio::select! {
+            samples = state.receiver.recv() => {
+                if samples.is_none() {
+                    state.input_closed = true;
+                }
+                return samples;
+            },
+            command = state.control.changed(), if !state.cancelled => {
+                state.apply_command(command);
+                if state.cancelled {
+                    return None;
+                }
+            },
+        }
+    }
+}
+
+fn samples_to_ms(samples: u64, sample_rate: u32) -> u64 {
+    samples * 1000 / sample_rate as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_backoff_exponential_and_capped() {
+        let mut backoff = Backoff::new(Duration::from_millis(500), Duration::from_secs(4), 5);
+        let delays: Vec<u128> = std::iter::from_fn(|| backoff.next_delay())
+            .map(|d| d.as_millis())
+            .collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 4000]);
+        assert!(backoff.next_delay().is_none());
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_pending_audio_drops_oldest() {
+        // 100ms @16kHz = 1600 样本
+        let mut pending = PendingAudio::new(100, STREAM_SAMPLE_RATE);
+        pending.push_back(vec![0.1; 800]);
+        pending.push_back(vec![0.2; 800]);
+        pending.push_back(vec![0.3; 800]);
+        assert_eq!(pending.take_dropped(), 800);
+        assert_eq!(pending.pop_front().unwrap()[0], 0.2);
+        assert_eq!(pending.pop_front().unwrap()[0], 0.3);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_pending_audio_push_front_keeps_order() {
+        let mut pending = PendingAudio::new(1000, STREAM_SAMPLE_RATE);
+        pending.push_back(vec![0.2; 10]);
+        pending.push_front(vec![0.1; 10]);
+        assert_eq!(pending.pop_front().unwrap()[0], 0.1);
+        assert_eq!(pending.pop_front().unwrap()[0], 0.2);
+    }
+
+    #[test]
+    fn test_timeline_offset() {
+        let mut offset = TimelineOffset {
+            time_ms: 60_000,
+            sentence_id: 12,
+            gaps: Vec::new(),
+        };
+        assert_eq!(offset.apply_time(1_500), 61_500);
+        assert_eq!(offset.apply_sentence_id(0), 12);
+
+        // 任务内丢弃的静音只平移其后的结果
+        offset.skip(2_000, 5_000);
+        assert_eq!(offset.apply_time(1_500), 61_500);
+        assert_eq!(offset.apply_time(2_500), 67_500);
+        offset.finish_task(3_000, 20);
+        assert_eq!(
+            offset,
+            TimelineOffset {
+                time_ms: 68_000,
+                sentence_id: 20,
+                gaps: Vec::new(),
+            }
+        );
+        assert_eq!(samples_to_ms(16_000, 16_000), 1_000);
+        assert_eq!(samples_to_ms(8_000, 8_000), 1_000);
+    }
+
+    /// 按 header.event 解析服务端消息的假模型
+    struct FakeModel {
+        server: ServerConfig,
+    }
+
+    impl StreamingModel for FakeModel {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn server_config(&self) -> &ServerConfig {
+            &self.server
+        }
+
+        fn sample_rate(&self) -> u32 {
+            STREAM_SAMPLE_RATE
+        }
+
+        fn run_task_message(&self, task_id: &str) -> Result<String, AsrError> {
+            Ok(format!(
+                r#"{{"header":{{"action":"run-task","task_id":"{}"}}}}"#,
+                task_id
+            ))
+        }
+
+        fn finish_task_message(&self, task_id: &str) -> Result<String, AsrError> {
+            Ok(format!(
+                r#"{{"header":{{"action":"finish-task","task_id":"{}"}}}}"#,
+                task_id
+            ))
+        }
+
+        fn handle_message(&mut self, text: &str, _offset: &TimelineOffset) -> ServerEvent {
+            let value: serde_json::Value = serde_json::from_str(text).unwrap();
+            let header = &value["header"];
+            match header["event"].as_str() {
+                Some("task-started") => ServerEvent::TaskStarted,
+                Some("task-failed") => ServerEvent::TaskFailed {
+                    code: header["error_code"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    message: header["error_message"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                },
+                _ => ServerEvent::Other,
+            }
+        }
+
+        fn next_sentence_id(&self) -> u32 {
+            0
+        }
+    }
+
+    /// 每个任务 task-started 之后立即 task-failed 的服务端，返回地址和连接计数
+    async fn failing_server(code: &'static str) -> (String, Arc<AtomicU32>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicU32::new(0));
+        let counter = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    // 等待 run-task
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if msg.is_text() {
+                            break;
+                        }
+                    }
+                    for event in [
+                        r#"{"header":{"event":"task-started"}}"#.to_string(),
+                        format!(
+                            r#"{{"header":{{"event":"task-failed","error_code":"{}","error_message":"rejected"}}}}"#,
+                            code
+                        ),
+                    ] {
+                        let _ = ws.send(Message::Text(Utf8Bytes::from(event))).await;
+                    }
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+        (url, connections)
+    }
+
+    async fn run_against(code: &'static str) -> (SessionStatus, u32) {
+        let (ws_url, connections) = failing_server(code).await;
+        let mut model = FakeModel {
+            server: ServerConfig {
+                ws_url,
+                api_key: "sk-test".to_string(),
+            },
+        };
+        let task_id = new_task_id();
+        let ws = open_task(&mut model, &task_id).await.unwrap();
+        let (_tx, rx) = mpsc::channel(10);
+        let backoff = Backoff::new(Duration::from_millis(1), Duration::from_millis(1), 3);
+        let session = AsrSession::spawn(SessionStatus::Running, move |control| {
+            run(
+                model,
+                rx,
+                ResultOrigin::default(),
+                control,
+                ws,
+                task_id,
+                backoff,
+            )
+        });
+        let status = session.status();
+        tokio::time::timeout(Duration::from_secs(10), session.join())
+            .await
+            .expect("会话应在重试耗尽后结束")
+            .unwrap();
+        let status = status.borrow().clone();
+        (status, connections.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_task_failed_after_start_does_not_reconnect_forever() {
+        // 参数错误不重连
+        let (status, connections) = run_against("InvalidParameter").await;
+        assert!(matches!(
+            status,
+            SessionStatus::Failed {
+                error: AsrError::Config { .. }
+            }
+        ));
+        assert_eq!(connections, 1);
+
+        // 可重试的错误：任务启动成功但没有结果，不重置退避计数，重试 3 次后放弃
+        let (status, connections) = run_against("InternalError").await;
+        assert!(matches!(
+            status,
+            SessionStatus::Failed {
+                error: AsrError::NetworkUnreachable { .. }
+            }
+        ));
+        assert_eq!(connections, 4);
+    }
+}
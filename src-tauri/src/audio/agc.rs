@@ -0,0 +1,386 @@
+// 自动增益控制（AGC）+ 软拐点前瞻限幅器，替代固定倍数放大后硬削波
+// AGC：按帧跟踪输入 RMS，增益以 attack（降低）/ release（提升）时间常数向目标响度靠拢，帧内线性过渡
+// 限幅器：延迟 lookahead 个样本，提前在峰值到来前平滑压低增益，避免削波失真
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// AGC 与限幅器配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgcConfig {
+    /// 关闭时使用固定增益（gain_db），仍经过限幅器
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 目标响度（RMS，dBFS）
+    #[serde(default = "default_target_db")]
+    pub target_db: f32,
+    #[serde(default = "default_min_gain_db")]
+    pub min_gain_db: f32,
+    #[serde(default = "default_max_gain_db")]
+    pub max_gain_db: f32,
+    /// 增益降低的时间常数（毫秒）
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: f32,
+    /// 增益提升的时间常数（毫秒）
+    #[serde(default = "default_release_ms")]
+    pub release_ms: f32,
+    /// 输入低于该电平时保持增益不变，避免放大底噪
+    #[serde(default = "default_noise_floor_db")]
+    pub noise_floor_db: f32,
+    /// 限幅阈值（dBFS）
+    #[serde(default = "default_limiter_threshold_db")]
+    pub limiter_threshold_db: f32,
+    /// 软拐点宽度（dB）
+    #[serde(default = "default_limiter_knee_db")]
+    pub limiter_knee_db: f32,
+    /// 前瞻时长（毫秒），同时是限幅器引入的延迟
+    #[serde(default = "default_limiter_lookahead_ms")]
+    pub limiter_lookahead_ms: f32,
+    /// 限幅后增益恢复的时间常数（毫秒）
+    #[serde(default = "default_limiter_release_ms")]
+    pub limiter_release_ms: f32,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_db: default_target_db(),
+            min_gain_db: default_min_gain_db(),
+            max_gain_db: default_max_gain_db(),
+            attack_ms: default_attack_ms(),
+            release_ms: default_release_ms(),
+            noise_floor_db: default_noise_floor_db(),
+            limiter_threshold_db: default_limiter_threshold_db(),
+            limiter_knee_db: default_limiter_knee_db(),
+            limiter_lookahead_ms: default_limiter_lookahead_ms(),
+            limiter_release_ms: default_limiter_release_ms(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_target_db() -> f32 {
+    -20.0
+}
+
+fn default_min_gain_db() -> f32 {
+    -10.0
+}
+
+fn default_max_gain_db() -> f32 {
+    30.0
+}
+
+fn default_attack_ms() -> f32 {
+    50.0
+}
+
+fn default_release_ms() -> f32 {
+    1500.0
+}
+
+fn default_noise_floor_db() -> f32 {
+    -55.0
+}
+
+fn default_limiter_threshold_db() -> f32 {
+    -1.0
+}
+
+fn default_limiter_knee_db() -> f32 {
+    6.0
+}
+
+fn default_limiter_lookahead_ms() -> f32 {
+    5.0
+}
+
+fn default_limiter_release_ms() -> f32 {
+    80.0
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn linear_to_db(value: f32) -> f32 {
+    20.0 * value.max(1e-6).log10()
+}
+
+/// 时间常数对应的单步平滑系数
+fn smoothing_coef(step_ms: f32, tau_ms: f32) -> f32 {
+    if tau_ms <= 0.0 {
+        0.0
+    } else {
+        (-step_ms / tau_ms).exp()
+    }
+}
+
+/// 软拐点前瞻限幅器
+struct Limiter {
+    threshold_db: f32,
+    knee_db: f32,
+    lookahead: usize,
+    /// 延迟线（预填 lookahead 个 0）
+    delay: VecDeque<f32>,
+    /// 前瞻窗口内目标增益的单调队列 (样本序号, 目标增益)，队首为窗口最小值
+    window: VecDeque<(u64, f32)>,
+    index: u64,
+    gain: f32,
+    attack_coef: f32,
+    release_coef: f32,
+}
+
+impl Limiter {
+    fn new(config: &AgcConfig, sample_rate: u32) -> Self {
+        let sample_ms = 1000.0 / sample_rate as f32;
+        let lookahead = (config.limiter_lookahead_ms / sample_ms).round().max(1.0) as usize;
+        Self {
+            threshold_db: config.limiter_threshold_db,
+            knee_db: config.limiter_knee_db.max(0.0),
+            lookahead,
+            delay: VecDeque::from(vec![0.0; lookahead]),
+            window: VecDeque::with_capacity(lookahead + 1),
+            index: 0,
+            gain: 1.0,
+            // 约 lookahead/5 个样本的时间常数，保证峰值到达输出时增益已基本降到位
+            attack_coef: smoothing_coef(5.0, lookahead as f32),
+            release_coef: smoothing_coef(sample_ms, config.limiter_release_ms),
+        }
+    }
+
+    /// 软拐点静态曲线：输入幅度对应的目标增益（线性）
+    fn target_gain(&self, amplitude: f32) -> f32 {
+        let over = linear_to_db(amplitude) - self.threshold_db;
+        let half_knee = self.knee_db / 2.0;
+        let reduction_db = if over <= -half_knee {
+            0.0
+        } else if over < half_knee {
+            -(over + half_knee).powi(2) / (2.0 * self.knee_db)
+        } else {
+            -over
+        };
+        db_to_linear(reduction_db)
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        let target = self.target_gain(sample.abs());
+        while self.window.back().is_some_and(|&(_, g)| g >= target) {
+            self.window.pop_back();
+        }
+        self.window.push_back((self.index, target));
+        while self
+            .window
+            .front()
+            .is_some_and(|&(i, _)| i + (self.lookahead as u64) < self.index)
+        {
+            self.window.pop_front();
+        }
+        self.index += 1;
+
+        let window_min = self.window.front().map_or(1.0, |&(_, g)| g);
+        let coef = if window_min < self.gain {
+            self.attack_coef
+        } else {
+            self.release_coef
+        };
+        self.gain = window_min + (self.gain - window_min) * coef;
+
+        self.delay.push_back(sample);
+        let delayed = self.delay.pop_front().unwrap_or(0.0);
+        // 平滑残差导致的极小超调由安全钳位兜底
+        (delayed * self.gain).clamp(-1.0, 1.0)
+    }
+}
+
+/// AGC + 限幅器处理阶段
+pub struct Agc {
+    enabled: bool,
+    target_db: f32,
+    min_gain_db: f32,
+    max_gain_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    noise_floor_db: f32,
+    sample_rate: u32,
+    /// 当前 AGC 增益（dB）
+    gain_db: f32,
+    limiter: Limiter,
+    /// 最近一帧限幅器的最大增益衰减（dB，正数）
+    gain_reduction_db: f32,
+}
+
+impl Agc {
+    /// initial_gain_db：AGC 的初始增益；关闭 AGC 时即固定增益
+    pub fn new(config: &AgcConfig, initial_gain_db: f32, sample_rate: u32) -> Self {
+        Self {
+            enabled: config.enabled,
+            target_db: config.target_db,
+            min_gain_db: config.min_gain_db,
+            max_gain_db: config.max_gain_db.max(config.min_gain_db),
+            attack_ms: config.attack_ms,
+            release_ms: config.release_ms,
+            noise_floor_db: config.noise_floor_db,
+            sample_rate,
+            gain_db: if config.enabled {
+                initial_gain_db.clamp(config.min_gain_db, config.max_gain_db)
+            } else {
+                initial_gain_db
+            },
+            limiter: Limiter::new(config, sample_rate),
+            gain_reduction_db: 0.0,
+        }
+    }
+
+    /// 当前 AGC 增益（dB）
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// 最近一帧限幅器的最大增益衰减（dB）
+    pub fn gain_reduction_db(&self) -> f32 {
+        self.gain_reduction_db
+    }
+
+    pub fn process(&mut self, frame: &[f32]) -> Vec<f32> {
+        if frame.is_empty() {
+            return Vec::new();
+        }
+
+        let start_db = self.gain_db;
+        if self.enabled {
+            let level_db = crate::audio::vad::rms_db(frame);
+            if level_db > self.noise_floor_db {
+                let desired = (self.target_db - level_db).clamp(self.min_gain_db, self.max_gain_db);
+                let frame_ms = frame.len() as f32 * 1000.0 / self.sample_rate as f32;
+                let tau = if desired < self.gain_db {
+                    self.attack_ms
+                } else {
+                    self.release_ms
+                };
+                let coef = smoothing_coef(frame_ms, tau);
+                self.gain_db = desired + (self.gain_db - desired) * coef;
+            }
+        }
+
+        // 帧内增益线性过渡，避免帧边界处的增益跳变
+        let step = (self.gain_db - start_db) / frame.len() as f32;
+        let mut min_limiter_gain = 1.0f32;
+        let output = frame
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| {
+                let gain = db_to_linear(start_db + step * (i + 1) as f32);
+                let out = self.limiter.process(s * gain);
+                min_limiter_gain = min_limiter_gain.min(self.limiter.gain);
+                out
+            })
+            .collect();
+        self.gain_reduction_db = -linear_to_db(min_limiter_gain);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::vad::rms_db;
+
+    const RATE: u32 = 16000;
+    const FRAME: usize = 800;
+
+    fn sine(amplitude: f32, seconds: f32) -> Vec<f32> {
+        let n = (RATE as f32 * seconds) as usize;
+        (0..n)
+            .map(|i| {
+                amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin()
+            })
+            .collect()
+    }
+
+    /// 确定性的均匀白噪声（线性同余发生器）
+    fn noise(amplitude: f32, seconds: f32) -> Vec<f32> {
+        let mut state = 12345u32;
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                amplitude * ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    fn run(agc: &mut Agc, input: &[f32]) -> Vec<f32> {
+        input.chunks(FRAME).flat_map(|f| agc.process(f)).collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s.abs()).fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_agc_converges_to_target() {
+        let config = AgcConfig::default();
+        let mut agc = Agc::new(&config, 0.0, RATE);
+        // -40dBFS 左右的正弦波
+        let output = run(&mut agc, &sine(0.014, 8.0));
+        let tail = &output[output.len() - RATE as usize..];
+        assert!(
+            (rms_db(tail) - config.target_db).abs() < 1.5,
+            "{}",
+            rms_db(tail)
+        );
+
+        // 响度突增时快速降低增益
+        let output = run(&mut agc, &sine(0.5, 1.0));
+        let tail = &output[output.len() - FRAME * 4..];
+        assert!(
+            (rms_db(tail) - config.target_db).abs() < 2.0,
+            "{}",
+            rms_db(tail)
+        );
+    }
+
+    #[test]
+    fn test_limiter_soft_limits_without_hard_clipping() {
+        let config = AgcConfig {
+            enabled: false,
+            ..AgcConfig::default()
+        };
+        // 固定 +20dB 增益，峰值远超满幅
+        let mut agc = Agc::new(&config, 20.0, RATE);
+        let output = run(&mut agc, &sine(0.5, 1.0));
+        let ceiling = db_to_linear(config.limiter_threshold_db);
+        assert!(peak(&output) <= ceiling * 1.05, "{}", peak(&output));
+        assert!(agc.gain_reduction_db() > 10.0);
+        // 不是硬削波：满幅附近的样本只占少数
+        let clipped = output.iter().filter(|s| s.abs() >= 0.999).count();
+        assert_eq!(clipped, 0);
+    }
+
+    #[test]
+    fn test_noise_floor_holds_gain() {
+        let config = AgcConfig::default();
+        let mut agc = Agc::new(&config, 6.0, RATE);
+        run(&mut agc, &noise(0.0005, 3.0));
+        assert_eq!(agc.gain_db(), 6.0);
+        assert_eq!(agc.gain_reduction_db(), 0.0);
+    }
+
+    #[test]
+    fn test_deterministic_and_delay_preserves_length() {
+        let input: Vec<f32> = sine(0.3, 0.5)
+            .iter()
+            .zip(noise(0.1, 0.5))
+            .map(|(a, b)| a + b)
+            .collect();
+        let mut a = Agc::new(&AgcConfig::default(), 9.5, RATE);
+        let mut b = Agc::new(&AgcConfig::default(), 9.5, RATE);
+        let out_a = run(&mut a, &input);
+        let out_b = run(&mut b, &input);
+        assert_eq!(out_a.len(), input.len());
+        assert_eq!(out_a, out_b);
+    }
+}
@@ -0,0 +1,351 @@
+// 可组合的音频处理链：每个处理步骤实现 AudioStage，按 CaptureConfig.stages 的顺序组成 AudioChain
+// 降噪、滤波、VAD、AGC、录音、电平表等阶段可以增减和调整顺序，采集代码不需要改动
+mod stages;
+
+pub use stages::{DownmixStage, MeterStage, ResampleStage, SendStage, StatsStage};
+
+use crate::audio::agc::Agc;
+use crate::audio::config::AudioConfig;
+use crate::audio::denoise::Denoiser;
+use crate::audio::downmix::Downmixer;
+use crate::audio::highpass::HighPassFilter;
+use crate::audio::level::LevelMeter;
+use crate::audio::recorder::RecorderTap;
+use crate::audio::vad::{Vad, VadState};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// 一帧音频在处理链中流转的数据
+pub struct AudioBlock {
+    /// 解交错后的输入（选中的声道，处理前），电平表用于统计输入电平
+    pub input: Vec<Vec<f32>>,
+    /// 当前各声道样本：初始为 input 的副本，重采样后长度改变，下混后只剩一个声道
+    pub channels: Vec<Vec<f32>>,
+    /// channels 当前的采样率
+    pub sample_rate: u32,
+    /// 当前增益与限幅器衰减（dB，由 AGC 写入）
+    pub gain_db: f32,
+    pub gain_reduction_db: f32,
+    /// 待发送的帧：None 时发送 channels[0]；VAD 门限可能改为空（静音）或多帧（补发预录音频）
+    pub frames: Option<Vec<Vec<f32>>>,
+    /// 累计丢帧数（发送阶段写入）
+    pub dropped_frames: u64,
+    /// VAD 状态（VAD 阶段写入）
+    pub vad: Option<VadState>,
+}
+
+impl AudioBlock {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            input: Vec::new(),
+            channels: Vec::new(),
+            sample_rate,
+            gain_db: 0.0,
+            gain_reduction_db: 0.0,
+            frames: None,
+            dropped_frames: 0,
+            vad: None,
+        }
+    }
+
+    /// 从交错样本中取出选中的声道，开始处理新的一帧（复用已分配的缓冲区）
+    pub fn load(
+        &mut self,
+        interleaved: &[f32],
+        channel_count: usize,
+        selected: &[usize],
+        sample_rate: u32,
+    ) {
+        let frame_size = interleaved.len() / channel_count.max(1);
+        self.input.resize_with(selected.len(), Vec::new);
+        for (data, &ch) in self.input.iter_mut().zip(selected) {
+            data.clear();
+            data.extend((0..frame_size).map(|j| interleaved[j * channel_count + ch]));
+        }
+        self.channels.resize_with(self.input.len(), Vec::new);
+        for (data, input) in self.channels.iter_mut().zip(&self.input) {
+            data.clear();
+            data.extend_from_slice(input);
+        }
+        self.sample_rate = sample_rate;
+        self.frames = None;
+        self.vad = None;
+    }
+
+    /// 单声道样本（下混之前为第 0 声道）
+    pub fn mono(&self) -> &[f32] {
+        self.channels.first().map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn mono_mut(&mut self) -> &mut Vec<f32> {
+        if self.channels.is_empty() {
+            self.channels.push(Vec::new());
+        }
+        &mut self.channels[0]
+    }
+}
+
+/// 处理阶段
+pub trait AudioStage: Send {
+    /// 阶段名称（日志）
+    fn name(&self) -> &'static str;
+
+    fn process(&mut self, block: &mut AudioBlock);
+}
+
+/// 处理阶段类型，CaptureConfig.stages 中的顺序即处理顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageKind {
+    /// 重采样到输出采样率
+    Resample,
+    /// 多声道转单声道
+    Downmix,
+    /// 高通滤波（启用时），可放在任意位置，下混之前按声道分别滤波
+    HighPass,
+    /// 频谱减法降噪（启用时）
+    Denoise,
+    /// 自动增益控制 + 限幅
+    Gain,
+    /// 音量统计（日志）
+    Stats,
+    /// 会话录音（启用录音时）
+    Record,
+    /// 语音活动检测（VadMode 不为 Off 时）
+    Vad,
+    /// 发送给识别会话
+    Send,
+    /// 电平表（audio-level 事件）
+    Meter,
+}
+
+impl StageKind {
+    /// 只处理单声道输出采样率音频的阶段，必须在重采样和下混之后
+    fn needs_mono_output(&self) -> bool {
+        !matches!(self, Self::Resample | Self::Downmix | Self::HighPass)
+    }
+}
+
+pub fn default_stages() -> Vec<StageKind> {
+    use StageKind::*;
+    vec![
+        Resample, Downmix, HighPass, Denoise, Gain, Stats, Record, Vad, Send, Meter,
+    ]
+}
+
+/// 校验处理顺序：每个阶段最多一次，重采样、下混和发送必须存在，单声道阶段在重采样和下混之后，VAD 在发送之前
+pub fn validate_stages(stages: &[StageKind]) -> anyhow::Result<()> {
+    for (i, kind) in stages.iter().enumerate() {
+        if stages[..i].contains(kind) {
+            bail!("处理阶段 {:?} 重复", kind);
+        }
+    }
+    let position = |kind: StageKind| stages.iter().position(|&k| k == kind);
+    let (Some(resample), Some(downmix), Some(send)) = (
+        position(StageKind::Resample),
+        position(StageKind::Downmix),
+        position(StageKind::Send),
+    ) else {
+        bail!("处理链必须包含 resample、downmix 和 send 阶段");
+    };
+    if let Some(kind) = stages
+        .iter()
+        .enumerate()
+        .find(|(i, k)| k.needs_mono_output() && (*i < resample || *i < downmix))
+        .map(|(_, k)| k)
+    {
+        bail!("处理阶段 {:?} 必须在 resample 和 downmix 之后", kind);
+    }
+    if position(StageKind::Vad).is_some_and(|vad| vad > send) {
+        bail!("vad 阶段必须在 send 之前");
+    }
+    Ok(())
+}
+
+/// 构建处理链所需的外部资源（由采集代码提供）
+pub struct ChainResources {
+    pub tx: mpsc::Sender<Vec<f32>>,
+    pub level_meter: Option<LevelMeter>,
+    pub vad: Option<Vad>,
+    pub recorder: Option<RecorderTap>,
+    /// 回调缓冲区溢出计数，电平表上报时读取
+    pub overruns: Arc<AtomicU64>,
+}
+
+/// 处理链：依次运行各阶段，复用同一个 AudioBlock
+pub struct AudioChain {
+    stages: Vec<Box<dyn AudioStage>>,
+    block: AudioBlock,
+    sample_rate_in: u32,
+}
+
+impl AudioChain {
+    /// sample_rate_in 为输入（设备）采样率
+    pub fn new(sample_rate_in: u32) -> Self {
+        Self {
+            stages: Vec::new(),
+            block: AudioBlock::new(sample_rate_in),
+            sample_rate_in,
+        }
+    }
+
+    /// 按 config.stages 的顺序构建；未启用的高通、降噪以及缺少资源的可选阶段（录音、VAD、电平表）直接跳过
+    pub fn build(config: &AudioConfig, resources: ChainResources) -> anyhow::Result<Self> {
+        validate_stages(&config.stages)?;
+        let ChainResources {
+            tx,
+            mut level_meter,
+            mut vad,
+            mut recorder,
+            overruns,
+        } = resources;
+
+        let mut chain = Self::new(config.sample_rate_in);
+        // 重采样器的声道数取决于它在下混之前还是之后，滤波器的采样率取决于它在重采样之前还是之后
+        let mut channels = config.selected_channels.len();
+        let mut sample_rate = config.sample_rate_in;
+        for kind in &config.stages {
+            let stage: Box<dyn AudioStage> = match kind {
+                StageKind::Resample => {
+                    sample_rate = config.sample_rate_out;
+                    Box::new(ResampleStage::new(
+                        config.resampler,
+                        config.sample_rate_in,
+                        config.sample_rate_out,
+                        config.frame_size,
+                        channels,
+                    )?)
+                }
+                StageKind::Downmix => {
+                    channels = 1;
+                    Box::new(DownmixStage::new(Downmixer::new(
+                        config.downmix.clone(),
+                        &config.selected_channels,
+                        config.frame_size,
+                        config.sample_rate_in,
+                    )))
+                }
+                StageKind::HighPass if config.highpass.enabled => {
+                    Box::new(HighPassFilter::new(&config.highpass, sample_rate))
+                }
+                StageKind::Denoise if config.denoise.enabled => {
+                    let denoiser = Denoiser::new(&config.denoise, config.sample_rate_out);
+                    log::info!(
+                        "🔇 降噪强度 {:.2}，处理延迟 {}ms",
+                        config.denoise.strength,
+                        denoiser.latency() as u64 * 1000 / config.sample_rate_out as u64
+                    );
+                    Box::new(denoiser)
+                }
+                StageKind::HighPass | StageKind::Denoise => continue,
+                StageKind::Gain => Box::new(Agc::new(
+                    &config.agc,
+                    config.gain_db,
+                    config.sample_rate_out,
+                )),
+                StageKind::Stats => Box::new(StatsStage::default()),
+                StageKind::Record => match recorder.take() {
+                    Some(tap) => Box::new(tap),
+                    None => continue,
+                },
+                StageKind::Vad => match vad.take() {
+                    Some(vad) => Box::new(vad),
+                    None => continue,
+                },
+                StageKind::Send => Box::new(SendStage::new(tx.clone())),
+                StageKind::Meter => match level_meter.take() {
+                    Some(meter) => Box::new(MeterStage::new(meter, overruns.clone())),
+                    None => continue,
+                },
+            };
+            chain.push(stage);
+        }
+        log::info!("🎛️ 音频处理链: {}", chain.stage_names().join(" → "));
+        Ok(chain)
+    }
+
+    pub fn push(&mut self, stage: Box<dyn AudioStage>) {
+        self.stages.push(stage);
+    }
+
+    pub fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// 处理一帧交错样本（frame_size × channel_count）
+    pub fn process(&mut self, interleaved: &[f32], channel_count: usize, selected: &[usize]) {
+        self.block
+            .load(interleaved, channel_count, selected, self.sample_rate_in);
+        for stage in &mut self.stages {
+            stage.process(&mut self.block);
+            // 阶段失败（如重采样出错）时清空样本，跳过本帧剩余阶段
+            if self.block.channels.is_empty() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::CaptureConfig;
+    use StageKind::*;
+
+    #[test]
+    fn test_validate_stage_order() {
+        assert!(validate_stages(&default_stages()).is_ok());
+        // 先下混再重采样（只重采样一个声道）也可以
+        assert!(validate_stages(&[Downmix, Resample, Gain, Send]).is_ok());
+        assert!(validate_stages(&[Resample, Gain, Downmix, Send]).is_err());
+        assert!(validate_stages(&[Resample, Downmix, Send, Vad]).is_err());
+        assert!(validate_stages(&[Resample, Downmix, Gain, Gain, Send]).is_err());
+        assert!(validate_stages(&[Resample, Downmix]).is_err());
+        // 高通可以在下混之前按声道滤波，降噪只处理单声道
+        assert!(validate_stages(&[HighPass, Resample, Downmix, Send]).is_ok());
+        assert!(validate_stages(&[Resample, Denoise, Downmix, Send]).is_err());
+    }
+
+    #[test]
+    fn test_chain_resamples_and_sends_mono_frames() {
+        let capture = CaptureConfig {
+            stages: vec![Downmix, Resample, Gain, Send],
+            gain_db: 0.0,
+            ..Default::default()
+        };
+        let config = capture.resolve(48000, 2).unwrap();
+        let (tx, mut rx) = mpsc::channel(16);
+        let mut chain = AudioChain::build(
+            &config,
+            ChainResources {
+                tx,
+                level_meter: None,
+                vad: None,
+                recorder: None,
+                overruns: Arc::default(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            chain.stage_names(),
+            vec!["downmix", "resample", "gain", "send"]
+        );
+
+        let interleaved: Vec<f32> = (0..config.frame_size)
+            .flat_map(|i| {
+                let s = 0.3 * (i as f32 * 0.05).sin();
+                [s, s]
+            })
+            .collect();
+        for _ in 0..4 {
+            chain.process(&interleaved, 2, &config.selected_channels);
+        }
+        // 每帧 50ms @16kHz（首帧扣除重采样延迟）
+        let frames: Vec<Vec<f32>> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(frames.len(), 4);
+        assert!(frames.iter().all(|f| (760..=800).contains(&f.len())));
+    }
+}
@@ -0,0 +1,486 @@
+// 内置处理阶段：重采样、下混、高通、降噪、增益、音量统计、录音、VAD、发送、电平表
+use super::{AudioBlock, AudioStage};
+use crate::audio::agc::Agc;
+use crate::audio::config::ResamplerQuality;
+use crate::audio::denoise::Denoiser;
+use crate::audio::downmix::Downmixer;
+use crate::audio::highpass::HighPassFilter;
+use crate::audio::level::LevelMeter;
+use crate::audio::recorder::RecorderTap;
+use crate::audio::vad::Vad;
+use anyhow::Context;
+use rubato::{
+    Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// 重采样到输出采样率
+pub struct ResampleStage {
+    resampler: SincFixedIn<f32>,
+    sample_rate_out: u32,
+}
+
+impl ResampleStage {
+    /// frame_size 为每帧输入样本数（每声道）；channels 为重采样的声道数（在下混之后为 1）
+    pub fn new(
+        quality: ResamplerQuality,
+        sample_rate_in: u32,
+        sample_rate_out: u32,
+        frame_size: usize,
+        channels: usize,
+    ) -> anyhow::Result<Self> {
+        // 初始化重采样器（针对低延迟优化）
+        // 参数说明：
+        // - max_resample_ratio_relative: 最大相对比率，限制动态调整范围（如果不使用动态调整，影响内存分配）
+        //   由于我们使用固定比率，1.2 足够且节省内存（原 2.0）
+        // - sinc_len: 越小越快但质量略降
+        // - interpolation: Linear 速度更快，Cubic 质量更高但慢
+        // - oversampling_factor: 越小越快但质量略降
+        // - f_cutoff: 越小越保守，0.95 更激进但质量略降
+        // 预设：Fast 适合低性能设备；Balanced 为原固定参数；High 适合 48kHz 输出或离线对比
+        let (sinc_len, interpolation, oversampling_factor) = match quality {
+            ResamplerQuality::Fast => (64, SincInterpolationType::Linear, 32),
+            // 128/Linear/80：平衡速度和质量（原 256/160）
+            ResamplerQuality::Balanced => (128, SincInterpolationType::Linear, 80),
+            ResamplerQuality::High => (256, SincInterpolationType::Cubic, 256),
+        };
+        let resampler = SincFixedIn::<f32>::new(
+            sample_rate_out as f64 / sample_rate_in as f64,
+            1.2,
+            SincInterpolationParameters {
+                sinc_len,
+                interpolation,
+                oversampling_factor,
+                f_cutoff: 0.95,
+                window: WindowFunction::BlackmanHarris,
+            },
+            frame_size,
+            channels,
+        )
+        .context("无法创建重采样器")?;
+        Ok(Self {
+            resampler,
+            sample_rate_out,
+        })
+    }
+}
+
+impl AudioStage for ResampleStage {
+    fn name(&self) -> &'static str {
+        "resample"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        match self.resampler.process(&block.channels, None) {
+            Ok(processed) => {
+                block.channels = processed;
+                block.sample_rate = self.sample_rate_out;
+            }
+            Err(e) => {
+                // 清空后处理链跳过本帧
+                log::error!("❌ 重采样失败: {}", e);
+                block.channels.clear();
+            }
+        }
+    }
+}
+
+/// 按配置的下混策略转换为单声道
+pub struct DownmixStage {
+    downmix: Downmixer,
+    frame_count: u64,
+}
+
+impl DownmixStage {
+    pub fn new(downmix: Downmixer) -> Self {
+        Self {
+            downmix,
+            frame_count: 0,
+        }
+    }
+}
+
+impl AudioStage for DownmixStage {
+    fn name(&self) -> &'static str {
+        "downmix"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        let mono = self.downmix.process(&block.channels);
+        block.channels.clear();
+        block.channels.push(mono);
+
+        // 每100帧记录一次声道相关系数，接近 -1 说明左右反相（平均混音会抵消）
+        if self.frame_count % 100 == 0 {
+            if let Some(corr) = self.downmix.correlation() {
+                log::debug!(
+                    "声道相关系数: {:.2}，反相声道: {:?}",
+                    corr,
+                    self.downmix.inverted_channels()
+                );
+            }
+        }
+        self.frame_count += 1;
+    }
+}
+
+/// 高通滤波：每个声道分别滤波
+impl AudioStage for HighPassFilter {
+    fn name(&self) -> &'static str {
+        "highpass"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        for (i, channel) in block.channels.iter_mut().enumerate() {
+            HighPassFilter::process(self, i, channel);
+        }
+    }
+}
+
+/// 降噪（单声道）
+impl AudioStage for Denoiser {
+    fn name(&self) -> &'static str {
+        "denoise"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        Denoiser::process(self, block.mono_mut());
+    }
+}
+
+/// 自动增益控制 + 前瞻限幅（关闭 AGC 时为固定增益 + 限幅）
+impl AudioStage for Agc {
+    fn name(&self) -> &'static str {
+        "gain"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        let amplified = Agc::process(self, block.mono());
+        *block.mono_mut() = amplified;
+        block.gain_db = self.gain_db();
+        block.gain_reduction_db = self.gain_reduction_db();
+    }
+}
+
+/// 音量统计信息
+#[derive(Debug, Default)]
+pub struct VolumeStats {
+    pub max_volume: f32,
+    pub avg_volume: f32,
+    pub frame_count: u64,
+    pub low_volume_count: u64, // 历史低音量帧计数（用于累积统计）
+    pub window_low_count: u64, // 当前窗口（最近100帧）低音量帧计数
+}
+
+/// 音量统计（放在增益之后，反映实际发送给 ASR 的音量）
+#[derive(Default)]
+pub struct StatsStage {
+    stats: VolumeStats,
+}
+
+impl AudioStage for StatsStage {
+    fn name(&self) -> &'static str {
+        "stats"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        update_volume_stats(block.mono(), &mut self.stats);
+    }
+}
+
+// 更新音量统计信息
+fn update_volume_stats(samples: &[f32], stats: &mut VolumeStats) {
+    if samples.is_empty() {
+        return;
+    }
+
+    // 计算 RMS（均方根）音量
+    let sum_sq: f32 = samples.iter().map(|&s| s * s).sum();
+    let rms = (sum_sq / samples.len() as f32).sqrt();
+
+    // 计算峰值音量
+    let peak = samples.iter().map(|&s| s.abs()).fold(0.0f32, f32::max);
+
+    // 更新统计
+    stats.frame_count += 1;
+    stats.max_volume = stats.max_volume.max(peak);
+
+    // 累积平均音量（移动平均）
+    let alpha = 0.1; // 平滑因子
+    stats.avg_volume = alpha * rms + (1.0 - alpha) * stats.avg_volume;
+
+    // 检测低音量（RMS < 0.01 或峰值 < 0.05），同时更新窗口计数
+    if rms < 0.01 || peak < 0.05 {
+        stats.low_volume_count += 1;
+        stats.window_low_count += 1;
+    }
+
+    // 每100帧打印一次音量统计，使用当前窗口（最近100帧）的低音量比例
+    if stats.frame_count % 100 == 0 {
+        // window_low_count 只统计最近100帧，比历史累积比例更能反映当前状态
+        let window_low_ratio = stats.window_low_count as f32 / 100.0 * 100.0;
+        log::debug!(
+            "音量统计 - RMS: {:.4}, 峰值: {:.4}, 最大: {:.4}, 低音量帧: {:.1}%（最近100帧）",
+            rms,
+            peak,
+            stats.max_volume,
+            window_low_ratio
+        );
+
+        // 如果当前窗口低音量帧比例过高，发出警告
+        if window_low_ratio > 50.0 {
+            log::warn!(
+                "⚠️ 检测到高比例低音量帧 ({:.1}%)，建议增加音频增益或检查音频输入源",
+                window_low_ratio
+            );
+        }
+
+        // 如果峰值过低，也发出警告
+        if stats.max_volume < 0.1 {
+            log::warn!(
+                "⚠️ 峰值音量过低 ({:.4})，建议增加音频增益（当前增益可能不够）",
+                stats.max_volume
+            );
+        }
+
+        // 重置窗口计数，开始统计下一个100帧
+        stats.window_low_count = 0;
+    }
+}
+
+/// 录音写入无锁缓冲区（放在 VAD 之前时不受静音门限影响，保留完整音频）
+impl AudioStage for RecorderTap {
+    fn name(&self) -> &'static str {
+        "record"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        self.push(block.mono());
+    }
+}
+
+/// VAD：静音门限模式下只发送语音段（语音开始时补发预录音频）
+impl AudioStage for Vad {
+    fn name(&self) -> &'static str {
+        "vad"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        block.frames = Some(Vad::process(self, block.mono().to_vec()));
+        block.vad = Some(self.state());
+    }
+}
+
+/// 异步发送给识别会话，缓冲区满时丢弃数据
+pub struct SendStage {
+    tx: mpsc::Sender<Vec<f32>>,
+    dropped_frames: u64,
+}
+
+impl SendStage {
+    pub fn new(tx: mpsc::Sender<Vec<f32>>) -> Self {
+        Self {
+            tx,
+            dropped_frames: 0,
+        }
+    }
+}
+
+impl AudioStage for SendStage {
+    fn name(&self) -> &'static str {
+        "send"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        let frames = block
+            .frames
+            .take()
+            .unwrap_or_else(|| vec![block.mono().to_vec()]);
+        for frame in frames {
+            if let Err(e) = self.tx.try_send(frame) {
+                self.dropped_frames += 1;
+                log::warn!("⚠️ 音频数据通道已满，丢弃当前数据块: {}", e);
+            }
+        }
+        block.dropped_frames = self.dropped_frames;
+    }
+}
+
+/// 电平表：累计输入各声道和输出电平，约 20Hz 推送（含丢帧数、回调溢出数和 VAD 状态）
+pub struct MeterStage {
+    meter: LevelMeter,
+    overruns: Arc<AtomicU64>,
+}
+
+impl MeterStage {
+    pub fn new(meter: LevelMeter, overruns: Arc<AtomicU64>) -> Self {
+        Self { meter, overruns }
+    }
+}
+
+impl AudioStage for MeterStage {
+    fn name(&self) -> &'static str {
+        "meter"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        self.meter.accumulate(
+            &block.input,
+            block.mono(),
+            block.gain_db,
+            block.gain_reduction_db,
+        );
+        self.meter.report_if_due(
+            block.dropped_frames,
+            self.overruns.load(Ordering::Relaxed),
+            block.vad,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::agc::AgcConfig;
+    use crate::audio::downmix::DownmixMode;
+    use crate::audio::vad::{VadConfig, VadMode, VadState};
+    use std::sync::Mutex;
+
+    const RATE: u32 = 16000;
+
+    fn sine(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 0.07).sin())
+            .collect()
+    }
+
+    fn block(channels: Vec<Vec<f32>>) -> AudioBlock {
+        let mut block = AudioBlock::new(RATE);
+        block.input = channels.clone();
+        block.channels = channels;
+        block
+    }
+
+    #[test]
+    fn test_resample_changes_rate_and_length() {
+        let mut stage = ResampleStage::new(ResamplerQuality::Fast, 48000, RATE, 2400, 2).unwrap();
+        let mut block = block(vec![sine(2400, 0.5), sine(2400, 0.5)]);
+        block.sample_rate = 48000;
+        stage.process(&mut block);
+        assert_eq!(block.sample_rate, RATE);
+        assert_eq!(block.channels.len(), 2);
+        // 首帧扣除了滤波器延迟，约为输入的 1/3
+        assert!((760..=800).contains(&block.channels[0].len()));
+    }
+
+    #[test]
+    fn test_downmix_outputs_single_channel() {
+        let downmix = Downmixer::new(DownmixMode::Average, &[0, 1], 800, RATE);
+        let mut stage = DownmixStage::new(downmix);
+        let mut block = block(vec![vec![0.4; 800], vec![0.2; 800]]);
+        stage.process(&mut block);
+        assert_eq!(block.channels.len(), 1);
+        assert!(block.mono().iter().all(|&s| (s - 0.3).abs() < 1e-6));
+        // 输入保持不变，供电平表统计
+        assert_eq!(block.input.len(), 2);
+    }
+
+    #[test]
+    fn test_highpass_filters_every_channel() {
+        let mut filter = HighPassFilter::new(&Default::default(), RATE);
+        // 直流偏置被滤除，两个声道各自保存滤波器状态
+        let mut block = block(vec![vec![0.5; 800], vec![-0.5; 800]]);
+        for _ in 0..4 {
+            block.channels = vec![vec![0.5; 800], vec![-0.5; 800]];
+            AudioStage::process(&mut filter, &mut block);
+        }
+        assert!(block.channels[0][799].abs() < 1e-3);
+        assert!(block.channels[1][799].abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_denoise_keeps_frame_length() {
+        let mut denoiser = Denoiser::new(&Default::default(), RATE);
+        let mut block = block(vec![sine(800, 0.2)]);
+        AudioStage::process(&mut denoiser, &mut block);
+        assert_eq!(block.mono().len(), 800);
+    }
+
+    #[test]
+    fn test_gain_reports_gain_on_block() {
+        let config = AgcConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let mut agc = Agc::new(&config, 6.0, RATE);
+        let mut block = block(vec![sine(800, 0.1)]);
+        let before: f32 = block.mono().iter().map(|s| s.abs()).sum();
+        for _ in 0..3 {
+            let frame = sine(800, 0.1);
+            block.channels = vec![frame];
+            AudioStage::process(&mut agc, &mut block);
+        }
+        let after: f32 = block.mono().iter().map(|s| s.abs()).sum();
+        assert_eq!(block.gain_db, 6.0);
+        assert!(after > before * 1.9);
+    }
+
+    #[test]
+    fn test_stats_counts_low_volume_frames() {
+        let mut stage = StatsStage::default();
+        stage.process(&mut block(vec![vec![0.001; 800]]));
+        stage.process(&mut block(vec![sine(800, 0.5)]));
+        assert_eq!(stage.stats.frame_count, 2);
+        assert_eq!(stage.stats.low_volume_count, 1);
+        assert!((stage.stats.max_volume - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_vad_gate_replaces_frames() {
+        let config = VadConfig {
+            mode: VadMode::Gate,
+            ..Default::default()
+        };
+        let mut vad = Vad::new(config, RATE);
+        let mut block = block(vec![vec![0.0; 800]]);
+        AudioStage::process(&mut vad, &mut block);
+        assert_eq!(block.frames, Some(Vec::new()));
+        assert_eq!(block.vad, Some(VadState::Silence));
+    }
+
+    #[test]
+    fn test_send_counts_dropped_frames() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut stage = SendStage::new(tx);
+        let mut block = block(vec![vec![0.1; 800]]);
+        stage.process(&mut block);
+        stage.process(&mut block);
+        assert_eq!(block.dropped_frames, 1);
+        assert_eq!(rx.try_recv().unwrap().len(), 800);
+
+        // VAD 给出的帧优先于当前单声道样本
+        block.frames = Some(vec![vec![0.2; 10], vec![0.3; 10]]);
+        stage.process(&mut block);
+        assert_eq!(rx.try_recv().unwrap(), vec![0.2; 10]);
+        assert_eq!(block.dropped_frames, 2);
+    }
+
+    #[test]
+    fn test_meter_reports_levels_and_overruns() {
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        let sink = levels.clone();
+        let meter = LevelMeter::new(RATE, Box::new(move |l| sink.lock().unwrap().push(l)));
+        let overruns = Arc::new(AtomicU64::new(7));
+        let mut stage = MeterStage::new(meter, overruns);
+        let mut block = block(vec![vec![0.5; 1600], vec![0.25; 1600]]);
+        block.channels = vec![vec![0.5; 1600]];
+        stage.process(&mut block);
+
+        let levels = levels.lock().unwrap();
+        assert_eq!(levels.len(), 1);
+        assert_eq!(levels[0].channels.len(), 2);
+        assert_eq!(levels[0].overrun_samples, 7);
+    }
+}
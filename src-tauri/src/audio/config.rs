@@ -1,6 +1,237 @@
-use rubato::SincFixedIn;
+use crate::audio::agc::AgcConfig;
+use crate::audio::chain::{default_stages, validate_stages, AudioChain, StageKind};
+use crate::audio::denoise::DenoiseConfig;
+use crate::audio::downmix::DownmixMode;
+use crate::audio::highpass::HighPassConfig;
+use crate::audio::recorder::RecordingConfig;
+use crate::audio::vad::VadConfig;
+use crate::audio::watcher::DeviceFallback;
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
+/// 支持的输出采样率（Paraformer 的 sample_rate 参数支持 8k/16k/48k）
+pub const SUPPORTED_SAMPLE_RATES: [u32; 3] = [8000, 16000, 48000];
+
+/// 重采样质量预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResamplerQuality {
+    /// 最低 CPU 占用
+    Fast,
+    /// 速度与质量平衡（原固定参数）
+    #[default]
+    Balanced,
+    /// 最高质量，CPU 占用较高
+    High,
+}
+
+/// 显式指定的设备输入格式（从 get_audio_devices 返回的 supported_configs 中选择）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// 采样格式（"f32"、"i16" 等）；省略时优先 f32
+    #[serde(default)]
+    pub sample_format: Option<String>,
+}
+
+/// 采集配置（前端传入 start_audio_capture / start_capture_session）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureConfig {
+    /// 设备输入格式；省略时使用设备默认配置
+    #[serde(default)]
+    pub input: Option<InputFormat>,
+    /// 增益（dB），0 为原始音量；启用 AGC 时为初始增益
+    #[serde(default = "default_gain_db")]
+    pub gain_db: f32,
+    /// 自动增益控制与限幅器
+    #[serde(default)]
+    pub agc: AgcConfig,
+    /// 输出采样率（8000 / 16000 / 48000）
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    /// 每帧时长（毫秒），越小延迟越低、CPU 占用越高
+    #[serde(default = "default_frame_ms")]
+    pub frame_ms: u32,
+    #[serde(default)]
+    pub resampler: ResamplerQuality,
+    /// 参与混音的输入声道（从 0 开始）；省略时使用全部声道
+    #[serde(default)]
+    pub channels: Option<Vec<u16>>,
+    /// 多声道转单声道策略
+    #[serde(default)]
+    pub downmix: DownmixMode,
+    /// 高通滤波（去除低频隆隆声），默认关闭
+    #[serde(default)]
+    pub highpass: HighPassConfig,
+    /// 降噪（频谱减法），默认关闭
+    #[serde(default)]
+    pub denoise: DenoiseConfig,
+    /// 发送队列长度（帧），识别端处理不及时超出后丢帧
+    #[serde(default = "default_queue_frames")]
+    pub queue_frames: usize,
+    /// 语音活动检测
+    #[serde(default)]
+    pub vad: VadConfig,
+    /// 设备被移除或不可用时的处理方式
+    #[serde(default)]
+    pub fallback: DeviceFallback,
+    /// 会话录音（处理后的音频），省略时不录音
+    #[serde(default)]
+    pub record: Option<RecordingConfig>,
+    /// 处理阶段及顺序；省略时为 重采样 → 下混 → 高通 → 降噪 → 增益 → 统计 → 录音 → VAD → 发送 → 电平表
+    #[serde(default = "default_stages")]
+    pub stages: Vec<StageKind>,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            input: None,
+            gain_db: default_gain_db(),
+            agc: AgcConfig::default(),
+            sample_rate: default_sample_rate(),
+            frame_ms: default_frame_ms(),
+            resampler: ResamplerQuality::default(),
+            channels: None,
+            downmix: DownmixMode::default(),
+            highpass: HighPassConfig::default(),
+            denoise: DenoiseConfig::default(),
+            queue_frames: default_queue_frames(),
+            vad: VadConfig::default(),
+            fallback: DeviceFallback::default(),
+            record: None,
+            stages: default_stages(),
+        }
+    }
+}
+
+fn default_gain_db() -> f32 {
+    // 约 3 倍，与原固定增益一致
+    9.54
+}
+
+fn default_sample_rate() -> u32 {
+    16000
+}
+
+fn default_frame_ms() -> u32 {
+    50
+}
+
+fn default_queue_frames() -> usize {
+    1000
+}
+
+impl CaptureConfig {
+    /// 切换到备用设备时使用的配置：输入格式和声道选择只对原设备有意义，改为设备默认格式和全部声道
+    pub fn for_fallback_device(&self) -> Self {
+        let downmix = match self.downmix {
+            DownmixMode::Channel { .. } => DownmixMode::default(),
+            ref mode => mode.clone(),
+        };
+        Self {
+            input: None,
+            channels: None,
+            downmix,
+            ..self.clone()
+        }
+    }
+
+    /// 校验配置并结合设备参数生成处理配置
+    pub fn resolve(&self, sample_rate_in: u32, channels: u16) -> anyhow::Result<AudioConfig> {
+        if !SUPPORTED_SAMPLE_RATES.contains(&self.sample_rate) {
+            bail!(
+                "不支持的采样率 {}Hz（可选 {:?}）",
+                self.sample_rate,
+                SUPPORTED_SAMPLE_RATES
+            );
+        }
+        if !(10..=200).contains(&self.frame_ms) {
+            bail!("帧时长 {}ms 超出范围（10-200ms）", self.frame_ms);
+        }
+        if !(-20.0..=30.0).contains(&self.gain_db) {
+            bail!("增益 {}dB 超出范围（-20 至 +30dB）", self.gain_db);
+        }
+        if self.agc.enabled && self.agc.min_gain_db > self.agc.max_gain_db {
+            bail!(
+                "AGC 增益范围无效（{}dB > {}dB）",
+                self.agc.min_gain_db,
+                self.agc.max_gain_db
+            );
+        }
+        if self.queue_frames == 0 {
+            bail!("发送队列长度必须大于 0");
+        }
+        if self.highpass.enabled && !(20.0..=500.0).contains(&self.highpass.cutoff_hz) {
+            bail!(
+                "高通截止频率 {}Hz 超出范围（20-500Hz）",
+                self.highpass.cutoff_hz
+            );
+        }
+        if self.highpass.enabled && !(1..=4).contains(&self.highpass.order) {
+            bail!("高通滤波阶数 {} 超出范围（1-4）", self.highpass.order);
+        }
+        if self.denoise.enabled && !(0.0..=1.0).contains(&self.denoise.strength) {
+            bail!("降噪强度 {} 超出范围（0-1）", self.denoise.strength);
+        }
+        validate_stages(&self.stages)?;
+
+        let selected_channels: Vec<usize> = match &self.channels {
+            None => (0..channels as usize).collect(),
+            Some(list) => {
+                if list.is_empty() {
+                    bail!("至少需要选择一个声道");
+                }
+                let mut selected = Vec::with_capacity(list.len());
+                for &ch in list {
+                    if ch >= channels {
+                        bail!("声道 {} 不存在（设备共 {} 个声道）", ch, channels);
+                    }
+                    if selected.contains(&(ch as usize)) {
+                        bail!("声道 {} 重复", ch);
+                    }
+                    selected.push(ch as usize);
+                }
+                selected
+            }
+        };
+
+        match self.downmix {
+            DownmixMode::Channel { index } if !selected_channels.contains(&(index as usize)) => {
+                bail!(
+                    "下混声道 {} 不在选中的声道 {:?} 中",
+                    index,
+                    selected_channels
+                );
+            }
+            DownmixMode::MidSide { side_gain } if !(0.0..=1.0).contains(&side_gain) => {
+                bail!("side_gain {} 超出范围（0-1）", side_gain);
+            }
+            _ => {}
+        }
+
+        Ok(AudioConfig {
+            sample_rate_in,
+            sample_rate_out: self.sample_rate,
+            channels,
+            selected_channels,
+            downmix: self.downmix.clone(),
+            frame_size: (sample_rate_in as u64 * self.frame_ms as u64 / 1000) as usize,
+            gain_db: self.gain_db,
+            agc: self.agc.clone(),
+            resampler: self.resampler,
+            highpass: self.highpass.clone(),
+            denoise: self.denoise.clone(),
+            vad: self.vad.clone(),
+            stages: self.stages.clone(),
+        })
+    }
+}
+
 // 音频处理配置
 pub struct AudioConfig {
     //输入音频采样率（每秒采样点数
@@ -9,31 +240,79 @@ pub struct AudioConfig {
     pub sample_rate_out: u32,
     //输入音频通道数(如1为单声道，2为立体声）
     pub channels: u16,
+    //参与处理的输入声道索引
+    pub selected_channels: Vec<usize>,
+    //多声道转单声道策略
+    pub downmix: DownmixMode,
     //音频帧大小
     pub frame_size: usize,
-    //音频增益（放大倍数，1.0为原始音量，2.0为放大2倍）
-    pub gain: f32,
+    //音频增益（dB，0为原始音量）；启用 AGC 时为初始增益
+    pub gain_db: f32,
+    //自动增益控制与限幅器配置
+    pub agc: AgcConfig,
+    //重采样质量预设
+    pub resampler: ResamplerQuality,
+    //高通滤波配置（默认关闭）
+    pub highpass: HighPassConfig,
+    //降噪配置（默认关闭）
+    pub denoise: DenoiseConfig,
+    //语音活动检测配置（默认关闭）
+    pub vad: VadConfig,
+    //处理阶段及顺序
+    pub stages: Vec<StageKind>,
 }
 
 // 录音状态
 pub struct RecordingState {
-    // 重采样器
-    pub resampler: SincFixedIn<f32>,
-    // 输入音频缓存
+    // 输入音频缓存（交错样本，累计到一帧后交给处理链）
     pub sample_buffer: Vec<f32>,
-    // 输入音频通道数据
-    pub channel_data: Vec<Vec<f32>>,
-    // 发送通道，用于将处理后的音频数据发送到其他组件
+    // 处理链（重采样、下混、增益、VAD、发送等阶段）
+    pub chain: AudioChain,
+    // 发送通道（文件回放据此判断识别端队列是否已满）
     pub tx: mpsc::Sender<Vec<f32>>,
-    // 音量统计（用于监控）
-    pub volume_stats: VolumeStats,
+    // 回调缓冲区溢出丢弃的输入样本数（由音频回调累加）
+    pub overruns: Arc<AtomicU64>,
 }
 
-// 音量统计信息
-pub struct VolumeStats {
-    pub max_volume: f32,
-    pub avg_volume: f32,
-    pub frame_count: u64,
-    pub low_volume_count: u64, // 历史低音量帧计数（用于累积统计）
-    pub window_low_count: u64, // 当前窗口（最近100帧）低音量帧计数
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_defaults() {
+        let config: CaptureConfig = serde_json::from_str("{}").unwrap();
+        let audio = config.resolve(48000, 2).unwrap();
+        assert_eq!(audio.sample_rate_out, 16000);
+        assert_eq!(audio.frame_size, 2400);
+        assert_eq!(audio.selected_channels, vec![0, 1]);
+        assert!((10f32.powf(audio.gain_db / 20.0) - 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_resolve_rejects_invalid() {
+        let invalid = [
+            r#"{"sample_rate": 44100}"#,
+            r#"{"frame_ms": 5}"#,
+            r#"{"gain_db": 60}"#,
+            r#"{"channels": []}"#,
+            r#"{"channels": [2]}"#,
+            r#"{"channels": [1, 1]}"#,
+            r#"{"channels": [0], "downmix": {"mode": "channel", "index": 1}}"#,
+            r#"{"downmix": {"mode": "mid_side", "side_gain": 2}}"#,
+            r#"{"stages": ["resample", "gain", "downmix", "send"]}"#,
+            r#"{"highpass": {"enabled": true, "cutoff_hz": 5}}"#,
+            r#"{"denoise": {"enabled": true, "strength": 1.5}}"#,
+        ];
+        for json in invalid {
+            let config: CaptureConfig = serde_json::from_str(json).unwrap();
+            assert!(config.resolve(44100, 2).is_err(), "{}", json);
+        }
+
+        let config: CaptureConfig =
+            serde_json::from_str(r#"{"sample_rate": 8000, "channels": [1], "gain_db": 0}"#)
+                .unwrap();
+        let audio = config.resolve(44100, 2).unwrap();
+        assert_eq!(audio.selected_channels, vec![1]);
+        assert_eq!(audio.gain_db, 0.0);
+    }
 }
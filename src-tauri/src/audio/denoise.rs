@@ -0,0 +1,291 @@
+// 降噪：短时傅里叶变换 + 频谱减法，运行在下混、重采样之后的单声道信号上
+// 逐频点跟踪噪声功率（能量低于噪声估计数倍时更新，否则缓慢上浮），按过减系数和增益下限计算每个频点的增益，
+// 再用 sqrt-Hann 窗 50% 重叠相加重建；引入一个 FFT 窗长的延迟（16kHz 下为 32ms）
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// 分析窗时长（毫秒），按 2 的幂向上取整为 FFT 长度
+const WINDOW_MS: usize = 32;
+/// 起始的若干帧视为纯噪声，用于初始化噪声估计
+const INIT_FRAMES: u32 = 8;
+/// 频点功率低于噪声估计的该倍数时视为噪声并更新估计
+const NOISE_UPDATE_RATIO: f32 = 4.0;
+/// 噪声估计的平滑系数
+const NOISE_SMOOTHING: f32 = 0.9;
+/// 语音期间噪声估计每帧的上浮比例，使估计能跟上变大的噪声
+const NOISE_RISE: f32 = 1.002;
+/// 增益的时间平滑系数，减轻"音乐噪声"
+const GAIN_SMOOTHING: f32 = 0.4;
+
+/// 降噪配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DenoiseConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 强度（0-1）：越大去噪越多，但语音失真也越明显
+    #[serde(default = "default_strength")]
+    pub strength: f32,
+}
+
+impl Default for DenoiseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strength: default_strength(),
+        }
+    }
+}
+
+fn default_strength() -> f32 {
+    0.5
+}
+
+/// 频谱减法降噪器（流式，每次输出与输入等长）
+pub struct Denoiser {
+    fft: Arc<dyn RealToComplex<f32>>,
+    ifft: Arc<dyn ComplexToReal<f32>>,
+    hop: usize,
+    window: Vec<f32>,
+    /// 过减系数
+    over_subtraction: f32,
+    /// 增益下限（线性）
+    floor: f32,
+    /// 最近一个窗长的输入
+    analysis: Vec<f32>,
+    /// 重叠相加累加器
+    overlap: Vec<f32>,
+    /// 不足一个步长的输入
+    pending_in: Vec<f32>,
+    /// 已重建、尚未输出的样本
+    pending_out: VecDeque<f32>,
+    time: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    noise: Vec<f32>,
+    gains: Vec<f32>,
+    frames: u32,
+}
+
+impl Denoiser {
+    pub fn new(config: &DenoiseConfig, sample_rate: u32) -> Self {
+        let fft_len = (sample_rate as usize * WINDOW_MS / 1000)
+            .next_power_of_two()
+            .max(64);
+        let hop = fft_len / 2;
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(fft_len);
+        let ifft = planner.plan_fft_inverse(fft_len);
+        let strength = config.strength.clamp(0.0, 1.0);
+        // 周期 sqrt-Hann 窗：分析窗 × 合成窗 = Hann，50% 重叠相加恒为 1
+        let window = (0..fft_len)
+            .map(|i| (0.5 - 0.5 * (2.0 * PI * i as f32 / fft_len as f32).cos()).sqrt())
+            .collect();
+        let bins = fft_len / 2 + 1;
+        let scratch_len = fft.get_scratch_len().max(ifft.get_scratch_len());
+        Self {
+            hop,
+            window,
+            over_subtraction: 1.0 + 3.0 * strength,
+            floor: 10f32.powf(-(6.0 + 24.0 * strength) / 20.0),
+            analysis: vec![0.0; fft_len],
+            overlap: vec![0.0; fft_len],
+            pending_in: Vec::with_capacity(hop),
+            // 预先输出一个步长的静音，保证每次调用都能输出与输入等长的样本
+            pending_out: vec![0.0; hop].into(),
+            time: vec![0.0; fft_len],
+            spectrum: vec![Complex::default(); bins],
+            scratch: vec![Complex::default(); scratch_len],
+            noise: vec![0.0; bins],
+            gains: vec![1.0; bins],
+            frames: 0,
+            fft,
+            ifft,
+        }
+    }
+
+    /// 引入的延迟（样本数）
+    pub fn latency(&self) -> usize {
+        self.analysis.len()
+    }
+
+    /// 原地降噪
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for &sample in samples.iter() {
+            self.pending_in.push(sample);
+            if self.pending_in.len() == self.hop {
+                self.process_hop();
+                self.pending_in.clear();
+            }
+        }
+        for sample in samples.iter_mut() {
+            *sample = self.pending_out.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    fn process_hop(&mut self) {
+        let fft_len = self.analysis.len();
+        self.analysis.copy_within(self.hop.., 0);
+        self.analysis[fft_len - self.hop..].copy_from_slice(&self.pending_in);
+
+        for ((t, &x), &w) in self.time.iter_mut().zip(&self.analysis).zip(&self.window) {
+            *t = x * w;
+        }
+        if self
+            .fft
+            .process_with_scratch(&mut self.time, &mut self.spectrum, &mut self.scratch)
+            .is_err()
+        {
+            return;
+        }
+
+        self.frames = self.frames.saturating_add(1);
+        for (k, bin) in self.spectrum.iter_mut().enumerate() {
+            let power = bin.norm_sqr();
+            let noise = &mut self.noise[k];
+            if self.frames <= INIT_FRAMES {
+                *noise += (power - *noise) / self.frames as f32;
+            } else if power < *noise * NOISE_UPDATE_RATIO {
+                *noise = NOISE_SMOOTHING * *noise + (1.0 - NOISE_SMOOTHING) * power;
+            } else {
+                *noise *= NOISE_RISE;
+            }
+
+            let gain = if power > 0.0 {
+                (1.0 - self.over_subtraction * *noise / power)
+                    .max(0.0)
+                    .sqrt()
+                    .max(self.floor)
+            } else {
+                1.0
+            };
+            let smoothed = GAIN_SMOOTHING * self.gains[k] + (1.0 - GAIN_SMOOTHING) * gain;
+            self.gains[k] = smoothed;
+            *bin *= smoothed;
+        }
+        // 实数信号的直流和奈奎斯特频点虚部为 0
+        let last = self.spectrum.len() - 1;
+        self.spectrum[0].im = 0.0;
+        self.spectrum[last].im = 0.0;
+
+        if self
+            .ifft
+            .process_with_scratch(&mut self.spectrum, &mut self.time, &mut self.scratch)
+            .is_err()
+        {
+            return;
+        }
+        let scale = 1.0 / fft_len as f32;
+        for ((acc, &t), &w) in self.overlap.iter_mut().zip(&self.time).zip(&self.window) {
+            *acc += t * w * scale;
+        }
+        self.pending_out.extend(&self.overlap[..self.hop]);
+        self.overlap.copy_within(self.hop.., 0);
+        self.overlap[fft_len - self.hop..].fill(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    /// 噪声测试素材：开头 0.5 秒只有噪声，之后是音节状的浊音（150Hz 基频 + 谐波，4Hz 包络），
+    /// 叠加确定性白噪声，输入 SNR 约 2dB
+    fn noisy_fixture() -> (Vec<f32>, Vec<f32>) {
+        let len = RATE as usize * 4;
+        let clean: Vec<f32> = (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                if t < 0.5 {
+                    return 0.0;
+                }
+                let envelope = (2.0 * PI * 4.0 * t).sin().max(0.0);
+                let voiced: f32 = (1..=20)
+                    .map(|h| (2.0 * PI * 150.0 * h as f32 * t).sin() / h as f32)
+                    .sum();
+                0.1 * envelope * voiced
+            })
+            .collect();
+        let mut seed = 0x2545_f491u32;
+        let noisy = clean
+            .iter()
+            .map(|&s| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                s + 0.06 * (seed as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            })
+            .collect();
+        (clean, noisy)
+    }
+
+    /// 信噪比（dB）：输出相对干净信号的误差，跳过噪声估计收敛前的一秒
+    fn snr_db(clean: &[f32], signal: &[f32]) -> f32 {
+        let (clean, signal) = (&clean[RATE as usize..], &signal[RATE as usize..]);
+        let power: f32 = clean.iter().map(|s| s * s).sum();
+        let error: f32 = clean
+            .iter()
+            .zip(signal)
+            .map(|(c, s)| (c - s) * (c - s))
+            .sum();
+        10.0 * (power / error).log10()
+    }
+
+    #[test]
+    fn test_spectral_subtraction_improves_snr() {
+        let (clean, noisy) = noisy_fixture();
+        let before = snr_db(&clean, &noisy);
+
+        let mut denoiser = Denoiser::new(&DenoiseConfig::default(), RATE);
+        let mut output = noisy.clone();
+        for chunk in output.chunks_mut(800) {
+            denoiser.process(chunk);
+        }
+        // 去掉处理延迟后对齐
+        let aligned = &output[denoiser.latency()..];
+        let after = snr_db(&clean[..aligned.len()], aligned);
+
+        assert!((0.0..5.0).contains(&before), "{}", before);
+        assert!(after - before > 5.0, "{:.1}dB -> {:.1}dB", before, after);
+    }
+
+    #[test]
+    fn test_passes_signal_through_when_noise_free() {
+        let mut denoiser = Denoiser::new(
+            &DenoiseConfig {
+                enabled: true,
+                strength: 0.0,
+            },
+            RATE,
+        );
+        // 开头的静音使噪声估计为 0，之后完整重建输入，只有延迟
+        let input: Vec<f32> = (0..8000)
+            .map(|i| {
+                if i < 2048 {
+                    0.0
+                } else {
+                    0.3 * (i as f32 * 0.1).sin()
+                }
+            })
+            .collect();
+        let mut output = input.clone();
+        for chunk in output.chunks_mut(333) {
+            denoiser.process(chunk);
+        }
+        let latency = denoiser.latency();
+        assert_eq!(output.len(), input.len());
+        assert!(output[..latency].iter().all(|&s| s == 0.0));
+        let max_error = input
+            .iter()
+            .zip(&output[latency..])
+            .skip(latency)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_error < 1e-4, "{}", max_error);
+    }
+}
@@ -0,0 +1,233 @@
+// 多声道转单声道（下混）策略
+// 逐帧选最响声道在声像不同的立体声内容上会每 50ms 来回切换；平均混音在左右声道反相时（loopback 常见）会相互抵消。
+// 因此提供多种可选策略，并持续统计各声道与第 0 声道的相关系数，自动模式据此判断是否需要反相。
+use serde::{Deserialize, Serialize};
+
+/// 相关系数低于该值时判定为反相，对该声道取反后再平均
+const INVERT_BELOW: f32 = -0.5;
+/// 已反相的声道相关系数回升到该值以上时恢复（滞回，避免来回切换）
+const RESTORE_ABOVE: f32 = -0.2;
+/// 相关系数统计的时间常数（秒）
+const CORRELATION_WINDOW_SECS: f32 = 1.0;
+/// 能量低于该值（约 -80dBFS）时视为静音，不更新相关系数
+const SILENCE_POWER: f32 = 1e-8;
+
+/// 下混策略
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DownmixMode {
+    /// 每帧取 RMS 最大的声道（原行为）
+    #[default]
+    Loudest,
+    /// 各声道平均
+    Average,
+    /// 平均，但先对与第 0 声道强负相关的声道反相
+    PhaseAware,
+    /// 固定使用某个输入声道（设备声道索引，需在选中的声道中）
+    Channel { index: u16 },
+    /// 中/侧：mid + side_gain × side，side_gain 为 0 时等同平均，越大越能保留反相和硬声像的内容
+    MidSide {
+        #[serde(default = "default_side_gain")]
+        side_gain: f32,
+    },
+}
+
+fn default_side_gain() -> f32 {
+    0.5
+}
+
+/// 下混器（在音频回调中运行）
+pub struct Downmixer {
+    mode: DownmixMode,
+    /// Channel 模式对应的 channel_data 下标
+    fixed: usize,
+    decay: f32,
+    /// 每个声道与第 0 声道的 (Σxy, Σxx, Σyy)，按指数衰减累计
+    stats: Vec<(f32, f32, f32)>,
+    inverted: Vec<bool>,
+}
+
+impl Downmixer {
+    /// selected_channels 为参与处理的设备声道；frame_size、sample_rate 用于换算相关系数的衰减系数
+    pub fn new(
+        mode: DownmixMode,
+        selected_channels: &[usize],
+        frame_size: usize,
+        sample_rate: u32,
+    ) -> Self {
+        let fixed = match mode {
+            DownmixMode::Channel { index } => selected_channels
+                .iter()
+                .position(|&ch| ch == index as usize)
+                .unwrap_or(0),
+            _ => 0,
+        };
+        let frame_secs = frame_size as f32 / sample_rate.max(1) as f32;
+        Self {
+            mode,
+            fixed,
+            decay: (-frame_secs / CORRELATION_WINDOW_SECS).exp(),
+            stats: vec![(0.0, 0.0, 0.0); selected_channels.len()],
+            inverted: vec![false; selected_channels.len()],
+        }
+    }
+
+    /// 第 1 声道与第 0 声道的相关系数（-1 反相，0 无关，1 同相）；不足两个声道时为 None
+    pub fn correlation(&self) -> Option<f32> {
+        self.stats.get(1).map(|s| correlation(*s))
+    }
+
+    /// 当前被反相的声道（channel_data 下标）
+    pub fn inverted_channels(&self) -> Vec<usize> {
+        (0..self.inverted.len())
+            .filter(|&i| self.inverted[i])
+            .collect()
+    }
+
+    pub fn process(&mut self, channels: &[Vec<f32>]) -> Vec<f32> {
+        if channels.len() <= 1 {
+            return channels.first().cloned().unwrap_or_default();
+        }
+        self.update_correlation(channels);
+
+        match self.mode {
+            DownmixMode::Loudest => loudest(channels).clone(),
+            DownmixMode::Average => average(channels, &[]),
+            DownmixMode::PhaseAware => average(channels, &self.inverted),
+            DownmixMode::Channel { .. } => channels[self.fixed.min(channels.len() - 1)].clone(),
+            DownmixMode::MidSide { side_gain } => channels[0]
+                .iter()
+                .zip(&channels[1])
+                .map(|(&l, &r)| (l + r) * 0.5 + side_gain * (l - r) * 0.5)
+                .collect(),
+        }
+    }
+
+    fn update_correlation(&mut self, channels: &[Vec<f32>]) {
+        let reference = &channels[0];
+        for (i, channel) in channels.iter().enumerate().take(self.stats.len()).skip(1) {
+            let (mut xy, mut xx, mut yy) = (0.0f32, 0.0f32, 0.0f32);
+            for (&x, &y) in reference.iter().zip(channel) {
+                xy += x * y;
+                xx += x * x;
+                yy += y * y;
+            }
+            let n = reference.len().max(1) as f32;
+            if xx / n < SILENCE_POWER || yy / n < SILENCE_POWER {
+                // 任一声道静音时相关系数没有意义，保持原状态
+                continue;
+            }
+            let s = &mut self.stats[i];
+            *s = (
+                s.0 * self.decay + xy,
+                s.1 * self.decay + xx,
+                s.2 * self.decay + yy,
+            );
+
+            let corr = correlation(*s);
+            if !self.inverted[i] && corr < INVERT_BELOW {
+                self.inverted[i] = true;
+                log::info!(
+                    "🔀 声道 {} 与声道 0 反相（相关系数 {:.2}），下混时取反",
+                    i,
+                    corr
+                );
+            } else if self.inverted[i] && corr > RESTORE_ABOVE {
+                self.inverted[i] = false;
+                log::info!("🔀 声道 {} 相位恢复（相关系数 {:.2}）", i, corr);
+            }
+        }
+    }
+}
+
+fn correlation((xy, xx, yy): (f32, f32, f32)) -> f32 {
+    let denom = (xx * yy).sqrt();
+    if denom <= f32::EPSILON {
+        0.0
+    } else {
+        (xy / denom).clamp(-1.0, 1.0)
+    }
+}
+
+fn loudest(channels: &[Vec<f32>]) -> &Vec<f32> {
+    // 各声道长度相同，直接比较能量即可
+    channels
+        .iter()
+        .max_by(|a, b| {
+            let ea: f32 = a.iter().map(|&s| s * s).sum();
+            let eb: f32 = b.iter().map(|&s| s * s).sum();
+            ea.partial_cmp(&eb).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(&channels[0])
+}
+
+fn average(channels: &[Vec<f32>], inverted: &[bool]) -> Vec<f32> {
+    let scale = 1.0 / channels.len() as f32;
+    let mut out = vec![0.0; channels[0].len()];
+    for (i, channel) in channels.iter().enumerate() {
+        let sign = if inverted.get(i).copied().unwrap_or(false) {
+            -scale
+        } else {
+            scale
+        };
+        for (o, &s) in out.iter_mut().zip(channel) {
+            *o += s * sign;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(len: usize, amp: f32) -> Vec<f32> {
+        (0..len).map(|i| amp * (i as f32 * 0.05).sin()).collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_phase_aware_recovers_inverted_channel() {
+        let left = sine(800, 0.5);
+        let right: Vec<f32> = left.iter().map(|s| -s).collect();
+        let channels = vec![left.clone(), right];
+
+        let mut average = Downmixer::new(DownmixMode::Average, &[0, 1], 800, 16000);
+        assert!(rms(&average.process(&channels)) < 1e-4);
+        assert!(average.correlation().unwrap() < -0.99);
+
+        let mut auto = Downmixer::new(DownmixMode::PhaseAware, &[0, 1], 800, 16000);
+        let out = auto.process(&channels);
+        assert_eq!(auto.inverted_channels(), vec![1]);
+        assert!((rms(&out) - rms(&left)).abs() < 1e-3);
+
+        // 恢复同相后取消反相
+        let in_phase = vec![left.clone(), left.clone()];
+        for _ in 0..40 {
+            auto.process(&in_phase);
+        }
+        assert!(auto.inverted_channels().is_empty());
+        assert!(auto.correlation().unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_fixed_channel_and_mid_side() {
+        let left = sine(400, 0.5);
+        let right = vec![0.0; 400];
+        let channels = vec![left.clone(), right];
+
+        // 设备声道 3 对应 channel_data[1]
+        let mut fixed = Downmixer::new(DownmixMode::Channel { index: 3 }, &[1, 3], 400, 16000);
+        assert!(fixed.process(&channels).iter().all(|&s| s == 0.0));
+
+        let mut mid_side =
+            Downmixer::new(DownmixMode::MidSide { side_gain: 1.0 }, &[0, 1], 400, 16000);
+        assert_eq!(mid_side.process(&channels), left);
+
+        let mode: DownmixMode = serde_json::from_str(r#"{"mode": "mid_side"}"#).unwrap();
+        assert_eq!(mode, DownmixMode::MidSide { side_gain: 0.5 });
+    }
+}
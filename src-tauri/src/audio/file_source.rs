@@ -0,0 +1,344 @@
+// 文件音频源：WAV 直接解码，其他音视频文件经 ffmpeg 解码为 float32 交错样本，
+// 按实时或加速节奏送入与设备采集相同的处理管线（重采样 / 下混 / 增益），用于演示、回归测试和本地媒体的实时字幕
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// ffmpeg 解码的默认输出格式（之后由处理管线重采样、下混）
+pub const DEFAULT_DECODE_SAMPLE_RATE: u32 = 48000;
+pub const DEFAULT_DECODE_CHANNELS: u16 = 2;
+
+/// 每次读取约 10ms 音频
+const CHUNK_MS: u32 = 10;
+/// 识别端发送队列已满时的等待间隔
+const BACKPRESSURE_WAIT: Duration = Duration::from_millis(5);
+
+/// 文件音频源配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSourceConfig {
+    /// 音视频文件路径
+    pub path: String,
+    /// 播放速度：1.0 为实时，大于 1 加速，0 表示不限速（只受识别端消费速度限制）
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+/// 文件解码方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileDecoder {
+    /// hound 能直接读取的 PCM / float WAV，格式由文件头决定
+    Wav { sample_rate: u32, channels: u16 },
+    /// 其他格式交给 ffmpeg 解码，输出格式可任意指定
+    Ffmpeg,
+}
+
+impl FileDecoder {
+    /// 按文件头判断解码方式：hound 无法解析时（压缩格式、非 WAV）使用 ffmpeg
+    pub fn detect(path: &Path) -> anyhow::Result<Self> {
+        if !path.is_file() {
+            return Err(anyhow!("文件不存在: {}", path.display()));
+        }
+        Ok(match hound::WavReader::open(path) {
+            Ok(reader) => {
+                let spec = reader.spec();
+                Self::Wav {
+                    sample_rate: spec.sample_rate,
+                    channels: spec.channels,
+                }
+            }
+            Err(_) => Self::Ffmpeg,
+        })
+    }
+}
+
+/// 已打开的文件解码器，输出 float32 交错样本
+pub struct FileReader {
+    kind: ReaderKind,
+    sample_rate: u32,
+    channels: u16,
+}
+
+enum ReaderKind {
+    Wav {
+        reader: hound::WavReader<BufReader<File>>,
+        /// 整数样本的归一化系数，浮点 WAV 为 None
+        int_scale: Option<f32>,
+    },
+    Ffmpeg {
+        child: Child,
+        stdout: ChildStdout,
+        /// 管道读取不保证按 4 字节对齐，剩余的半个样本留到下次
+        pending: Vec<u8>,
+    },
+}
+
+impl FileReader {
+    pub fn wav(path: &Path) -> anyhow::Result<Self> {
+        let reader = hound::WavReader::open(path)
+            .with_context(|| format!("无法读取 WAV 文件 {}", path.display()))?;
+        let spec = reader.spec();
+        let int_scale = match spec.sample_format {
+            hound::SampleFormat::Float => None,
+            hound::SampleFormat::Int => Some(1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32),
+        };
+        Ok(Self {
+            kind: ReaderKind::Wav { reader, int_scale },
+            sample_rate: spec.sample_rate,
+            channels: spec.channels,
+        })
+    }
+
+    /// ffmpeg 为 sidecar 或系统 ffmpeg 的命令，这里追加解码参数后启动
+    pub fn ffmpeg(
+        mut ffmpeg: Command,
+        path: &Path,
+        sample_rate: u32,
+        channels: u16,
+    ) -> anyhow::Result<Self> {
+        let mut child = ffmpeg
+            .arg("-nostdin")
+            .args(["-v", "error", "-i"])
+            .arg(path)
+            .args(["-vn", "-f", "f32le", "-acodec", "pcm_f32le"])
+            .args(["-ar", &sample_rate.to_string()])
+            .args(["-ac", &channels.to_string()])
+            .arg("-")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("无法启动 ffmpeg")?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("无法读取 ffmpeg 输出"))?;
+        Ok(Self {
+            kind: ReaderKind::Ffmpeg {
+                child,
+                stdout,
+                pending: Vec::new(),
+            },
+            sample_rate,
+            channels,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// 读取最多 max_samples 个样本（交错）到 out，返回 0 表示文件结束
+    fn read(&mut self, out: &mut Vec<f32>, max_samples: usize) -> anyhow::Result<usize> {
+        out.clear();
+        match &mut self.kind {
+            ReaderKind::Wav {
+                reader,
+                int_scale: None,
+            } => {
+                for sample in reader.samples::<f32>().take(max_samples) {
+                    out.push(sample?);
+                }
+            }
+            ReaderKind::Wav {
+                reader,
+                int_scale: Some(scale),
+            } => {
+                for sample in reader.samples::<i32>().take(max_samples) {
+                    out.push(sample? as f32 * *scale);
+                }
+            }
+            ReaderKind::Ffmpeg {
+                stdout, pending, ..
+            } => {
+                let mut buf = vec![0u8; max_samples * 4];
+                while out.is_empty() {
+                    let n = stdout.read(&mut buf).context("读取 ffmpeg 输出失败")?;
+                    if n == 0 {
+                        break;
+                    }
+                    pending.extend_from_slice(&buf[..n]);
+                    let whole = pending.len() / 4 * 4;
+                    out.extend(
+                        pending[..whole]
+                            .chunks_exact(4)
+                            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                    );
+                    pending.drain(..whole);
+                }
+            }
+        }
+        Ok(out.len())
+    }
+}
+
+impl Drop for FileReader {
+    fn drop(&mut self) {
+        if let ReaderKind::Ffmpeg { child, .. } = &mut self.kind {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// 文件回放：在独立线程中按节奏读取文件并回调，丢弃即停止
+pub struct FilePlayback {
+    stopping: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FilePlayback {
+    /// can_send 返回 false 时（识别端队列已满）暂停读取，加速回放也不会丢帧
+    /// on_data 在回放线程中调用；读完或出错时调用一次 on_end（主动停止时不调用）
+    pub fn start(
+        mut reader: FileReader,
+        speed: f32,
+        can_send: impl Fn() -> bool + Send + 'static,
+        mut on_data: impl FnMut(&[f32]) + Send + 'static,
+        on_end: impl FnOnce(anyhow::Result<Duration>) + Send + 'static,
+    ) -> Self {
+        let stopping = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let stopping = stopping.clone();
+            thread::spawn(move || {
+                let channels = reader.channels().max(1) as usize;
+                let sample_rate = reader.sample_rate().max(1);
+                let chunk = (sample_rate * CHUNK_MS / 1000).max(1) as usize * channels;
+                let mut samples = Vec::with_capacity(chunk);
+                let mut frames = 0u64;
+                let mut started = Instant::now();
+                let result = loop {
+                    if stopping.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if !can_send() {
+                        // 等待期间不计入回放时钟，避免之后突发追赶
+                        thread::sleep(BACKPRESSURE_WAIT);
+                        started += BACKPRESSURE_WAIT;
+                        continue;
+                    }
+                    match reader.read(&mut samples, chunk) {
+                        Ok(0) => break Ok(frames_to_duration(frames, sample_rate)),
+                        Ok(n) => {
+                            on_data(&samples);
+                            frames += (n / channels) as u64;
+                        }
+                        Err(e) => break Err(e),
+                    }
+                    if speed > 0.0 {
+                        let due = started + frames_to_duration(frames, sample_rate).div_f32(speed);
+                        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                            thread::sleep(wait);
+                        }
+                    }
+                };
+                if !stopping.load(Ordering::SeqCst) {
+                    on_end(result);
+                }
+            })
+        };
+        Self {
+            stopping,
+            worker: Some(worker),
+        }
+    }
+}
+
+impl Drop for FilePlayback {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn frames_to_duration(frames: u64, sample_rate: u32) -> Duration {
+    Duration::from_secs_f64(frames as f64 / sample_rate as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn write_wav(path: &Path, sample_rate: u32, frames: usize) {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..frames {
+            writer.write_sample(i as i16).unwrap();
+            writer.write_sample(-16384i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn play(path: &Path, speed: f32) -> (Vec<f32>, Duration, Duration) {
+        let reader = FileReader::wav(path).unwrap();
+        let (data_tx, data_rx) = mpsc::channel();
+        let (end_tx, end_rx) = mpsc::channel();
+        let started = Instant::now();
+        let _playback = FilePlayback::start(
+            reader,
+            speed,
+            || true,
+            move |data| data_tx.send(data.to_vec()).unwrap(),
+            move |result| end_tx.send(result.unwrap()).unwrap(),
+        );
+        let played = end_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let elapsed = started.elapsed();
+        (data_rx.try_iter().flatten().collect(), played, elapsed)
+    }
+
+    #[test]
+    fn test_wav_playback_decodes_all_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stereo.wav");
+        write_wav(&path, 8000, 1000);
+
+        assert_eq!(
+            FileDecoder::detect(&path).unwrap(),
+            FileDecoder::Wav {
+                sample_rate: 8000,
+                channels: 2
+            }
+        );
+        let (samples, played, _) = play(&path, 0.0);
+        assert_eq!(samples.len(), 2000);
+        assert_eq!(samples[2], 1.0 / 32768.0);
+        assert_eq!(samples[3], -0.5);
+        assert_eq!(played, Duration::from_millis(125));
+    }
+
+    #[test]
+    fn test_playback_follows_speed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pace.wav");
+        // 0.4 秒音频
+        write_wav(&path, 8000, 3200);
+
+        let (_, _, realtime) = play(&path, 1.0);
+        assert!(realtime >= Duration::from_millis(380));
+        let (_, _, accelerated) = play(&path, 4.0);
+        assert!(accelerated >= Duration::from_millis(90));
+        assert!(accelerated < realtime);
+    }
+}
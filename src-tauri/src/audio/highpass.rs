@@ -0,0 +1,161 @@
+// 高通滤波：级联二阶 Butterworth 节（RBJ biquad），去除风扇、空调、桌面震动等低频隆隆声
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// 高通滤波配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighPassConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 截止频率（Hz），越高去除的低频越多（人声基频通常在 85Hz 以上）
+    #[serde(default = "default_cutoff_hz")]
+    pub cutoff_hz: f32,
+    /// 强度：级联的二阶节数（1-4），每节衰减斜率 12dB/倍频程
+    #[serde(default = "default_order")]
+    pub order: u32,
+}
+
+impl Default for HighPassConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cutoff_hz: default_cutoff_hz(),
+            order: default_order(),
+        }
+    }
+}
+
+fn default_cutoff_hz() -> f32 {
+    80.0
+}
+
+fn default_order() -> u32 {
+    2
+}
+
+/// 二阶高通节（Direct Form I）
+#[derive(Debug, Clone)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn high_pass(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let w0 = 2.0 * PI * cutoff_hz / sample_rate as f32;
+        // Q = 1/√2：通带最平坦
+        let alpha = w0.sin() / 2.0 * std::f32::consts::SQRT_2;
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// 高通滤波处理阶段，每个声道独立保存滤波器状态
+pub struct HighPassFilter {
+    prototype: Vec<Biquad>,
+    channels: Vec<Vec<Biquad>>,
+}
+
+impl HighPassFilter {
+    /// sample_rate 为滤波器所在位置的采样率（重采样之前为设备采样率）
+    pub fn new(config: &HighPassConfig, sample_rate: u32) -> Self {
+        Self {
+            prototype: vec![
+                Biquad::high_pass(config.cutoff_hz, sample_rate);
+                config.order.clamp(1, 4) as usize
+            ],
+            channels: Vec::new(),
+        }
+    }
+
+    /// 原地滤波第 channel 个声道
+    pub fn process(&mut self, channel: usize, samples: &mut [f32]) {
+        if self.channels.len() <= channel {
+            self.channels.resize(channel + 1, self.prototype.clone());
+        }
+        let sections = &mut self.channels[channel];
+        for sample in samples {
+            *sample = sections.iter_mut().fold(*sample, |x, s| s.process(x));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    /// Goertzel 算法计算单一频率的功率
+    fn power_at(samples: &[f32], freq: f32) -> f32 {
+        let coeff = 2.0 * (2.0 * PI * freq / RATE as f32).cos();
+        let (mut s1, mut s2) = (0.0f32, 0.0f32);
+        for &x in samples {
+            let s = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s;
+        }
+        (s1 * s1 + s2 * s2 - coeff * s1 * s2) / samples.len() as f32
+    }
+
+    #[test]
+    fn test_removes_hum_and_keeps_voice_band() {
+        // 1kHz "语音" + 40Hz 低频嗡声（比语音高 6dB）
+        let voice = tone(1000.0, 0.1, RATE as usize);
+        let hum = tone(40.0, 0.2, RATE as usize);
+        let mut noisy: Vec<f32> = voice.iter().zip(&hum).map(|(v, h)| v + h).collect();
+        let snr_db = |s: &[f32]| {
+            let s = &s[RATE as usize / 2..];
+            10.0 * (power_at(s, 1000.0) / power_at(s, 40.0)).log10()
+        };
+        let before = snr_db(&noisy);
+
+        let mut filter = HighPassFilter::new(&HighPassConfig::default(), RATE);
+        for chunk in noisy.chunks_mut(800) {
+            filter.process(0, chunk);
+        }
+        let after = snr_db(&noisy);
+
+        assert!(before < -5.0, "{}", before);
+        assert!(after - before > 20.0, "{} -> {}", before, after);
+        // 通带几乎无衰减
+        let gain = power_at(&noisy[8000..], 1000.0) / power_at(&voice[8000..], 1000.0);
+        assert!((gain - 1.0).abs() < 0.05, "{}", gain);
+    }
+}
@@ -0,0 +1,272 @@
+// 电平表：按固定间隔（约 20Hz）汇总采集管线各阶段的电平，通过回调交给调用方推送 audio-level 事件
+use crate::audio::vad::VadState;
+use serde::Serialize;
+
+/// 前端监听的电平事件名称
+pub const AUDIO_LEVEL_EVENT: &str = "audio-level";
+
+/// 电平上报间隔（毫秒）
+pub const LEVEL_INTERVAL_MS: u64 = 50;
+
+/// 输入样本绝对值达到该值视为削波
+const CLIP_LEVEL: f32 = 0.999;
+
+/// 单个输入声道的电平
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelLevel {
+    pub peak_db: f32,
+    pub rms_db: f32,
+}
+
+/// 一个上报周期内的电平汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioLevel {
+    /// 混音前各输入声道（仅选中的声道）的电平
+    pub channels: Vec<ChannelLevel>,
+    /// 增益和限幅后（发送给 ASR）的 RMS（dBFS）
+    pub rms_db: f32,
+    /// 增益和限幅后的峰值（dBFS）
+    pub peak_db: f32,
+    /// 当前 AGC 增益（dB）
+    pub gain_db: f32,
+    /// 周期内限幅器的最大增益衰减（dB）
+    pub gain_reduction_db: f32,
+    /// 周期内输入削波的样本数
+    pub clip_count: u64,
+    /// 累计因发送队列已满而丢弃的帧数
+    pub dropped_frames: u64,
+    /// 累计因回调缓冲区溢出（处理线程跟不上）而丢弃的输入样本数
+    pub overrun_samples: u64,
+    /// VAD 状态（未启用 VAD 时省略）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vad: Option<VadState>,
+}
+
+impl AudioLevel {
+    /// 合并多个周期的电平（RMS 按能量平均，峰值取最大），用于设备预览的整段汇总
+    pub fn combine(levels: &[AudioLevel]) -> Option<AudioLevel> {
+        let last = levels.last()?;
+        let channels = (0..last.channels.len())
+            .map(|i| {
+                let items: Vec<&ChannelLevel> =
+                    levels.iter().filter_map(|l| l.channels.get(i)).collect();
+                ChannelLevel {
+                    peak_db: items.iter().map(|c| c.peak_db).fold(-100.0, f32::max),
+                    rms_db: power_mean_db(items.iter().map(|c| c.rms_db)),
+                }
+            })
+            .collect();
+        Some(AudioLevel {
+            channels,
+            rms_db: power_mean_db(levels.iter().map(|l| l.rms_db)),
+            peak_db: levels.iter().map(|l| l.peak_db).fold(-100.0, f32::max),
+            gain_db: last.gain_db,
+            gain_reduction_db: levels
+                .iter()
+                .map(|l| l.gain_reduction_db)
+                .fold(0.0, f32::max),
+            clip_count: levels.iter().map(|l| l.clip_count).sum(),
+            dropped_frames: last.dropped_frames,
+            overrun_samples: last.overrun_samples,
+            vad: last.vad,
+        })
+    }
+}
+
+/// 发送给前端的电平事件
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioLevelEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(flatten)]
+    pub level: AudioLevel,
+}
+
+/// 电平上报回调（在音频线程中调用，应尽快返回）
+pub type LevelListener = Box<dyn FnMut(AudioLevel) + Send>;
+
+#[derive(Default)]
+struct Accumulator {
+    samples: usize,
+    sum_sq: f64,
+    peak: f32,
+}
+
+impl Accumulator {
+    fn add(&mut self, samples: &[f32]) {
+        self.samples += samples.len();
+        self.sum_sq += samples.iter().map(|&s| (s * s) as f64).sum::<f64>();
+        self.peak = samples.iter().fold(self.peak, |p, s| p.max(s.abs()));
+    }
+
+    fn rms_db(&self) -> f32 {
+        if self.samples == 0 {
+            return -100.0;
+        }
+        to_db((self.sum_sq / self.samples as f64).sqrt() as f32)
+    }
+
+    fn peak_db(&self) -> f32 {
+        to_db(self.peak)
+    }
+}
+
+/// 电平表
+pub struct LevelMeter {
+    interval_samples: usize,
+    output: Accumulator,
+    channels: Vec<Accumulator>,
+    gain_db: f32,
+    gain_reduction_db: f32,
+    clip_count: u64,
+    listener: LevelListener,
+}
+
+impl LevelMeter {
+    /// sample_rate 为输出（发送给 ASR）音频的采样率，按输出样本数计算上报间隔
+    pub fn new(sample_rate: u32, listener: LevelListener) -> Self {
+        Self {
+            interval_samples: (sample_rate as u64 * LEVEL_INTERVAL_MS / 1000) as usize,
+            output: Accumulator::default(),
+            channels: Vec::new(),
+            gain_db: 0.0,
+            gain_reduction_db: 0.0,
+            clip_count: 0,
+            listener,
+        }
+    }
+
+    /// 累计一帧：混音前的各声道输入，以及增益/限幅后的输出
+    pub fn accumulate(
+        &mut self,
+        input: &[Vec<f32>],
+        output: &[f32],
+        gain_db: f32,
+        gain_reduction_db: f32,
+    ) {
+        if self.channels.len() != input.len() {
+            self.channels = input.iter().map(|_| Accumulator::default()).collect();
+        }
+        for (acc, channel) in self.channels.iter_mut().zip(input) {
+            acc.add(channel);
+            self.clip_count += channel.iter().filter(|s| s.abs() >= CLIP_LEVEL).count() as u64;
+        }
+        self.output.add(output);
+        self.gain_db = gain_db;
+        self.gain_reduction_db = self.gain_reduction_db.max(gain_reduction_db);
+    }
+
+    /// 达到上报间隔时回调并重新计数
+    pub fn report_if_due(
+        &mut self,
+        dropped_frames: u64,
+        overrun_samples: u64,
+        vad: Option<VadState>,
+    ) {
+        if self.output.samples < self.interval_samples {
+            return;
+        }
+        let level = AudioLevel {
+            channels: self
+                .channels
+                .iter()
+                .map(|c| ChannelLevel {
+                    peak_db: c.peak_db(),
+                    rms_db: c.rms_db(),
+                })
+                .collect(),
+            rms_db: self.output.rms_db(),
+            peak_db: self.output.peak_db(),
+            gain_db: self.gain_db,
+            gain_reduction_db: self.gain_reduction_db,
+            clip_count: self.clip_count,
+            dropped_frames,
+            overrun_samples,
+            vad,
+        };
+        (self.listener)(level);
+
+        self.output = Accumulator::default();
+        self.channels
+            .iter_mut()
+            .for_each(|c| *c = Accumulator::default());
+        self.gain_reduction_db = 0.0;
+        self.clip_count = 0;
+    }
+}
+
+fn to_db(value: f32) -> f32 {
+    (20.0 * value.max(1e-5).log10()).max(-100.0)
+}
+
+fn power_mean_db(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0f64, 0usize), |(sum, n), db| {
+        (sum + 10f64.powf(db as f64 / 10.0), n + 1)
+    });
+    if count == 0 {
+        return -100.0;
+    }
+    ((10.0 * (sum / count as f64).log10()) as f32).max(-100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_meter_reports_at_interval_with_channel_levels() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let mut meter = LevelMeter::new(16000, Box::new(move |l| sink.lock().unwrap().push(l)));
+
+        // 1 秒 48kHz 输入：左声道满幅方波（削波），右声道静音；每帧 25ms
+        for _ in 0..40 {
+            let left: Vec<f32> = (0..1200)
+                .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+                .collect();
+            let right = vec![0.0; 1200];
+            meter.accumulate(&[left, right], &[0.5; 400], 6.0, 1.5);
+            meter.report_if_due(3, 0, Some(VadState::Speech));
+        }
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 20);
+        let level = &reports[0];
+        assert_eq!(level.channels.len(), 2);
+        assert!(level.channels[0].peak_db.abs() < 0.01);
+        assert_eq!(level.channels[1].rms_db, -100.0);
+        assert!((level.rms_db - to_db(0.5)).abs() < 0.01);
+        assert_eq!(level.clip_count, 1200 * 2);
+        assert_eq!(level.dropped_frames, 3);
+        assert_eq!(level.gain_reduction_db, 1.5);
+        assert_eq!(level.vad, Some(VadState::Speech));
+    }
+
+    #[test]
+    fn test_combine_levels() {
+        let level = |rms_db: f32, peak_db: f32, clips: u64| AudioLevel {
+            channels: vec![ChannelLevel { peak_db, rms_db }],
+            rms_db,
+            peak_db,
+            gain_db: 0.0,
+            gain_reduction_db: 0.0,
+            clip_count: clips,
+            dropped_frames: 0,
+            overrun_samples: 0,
+            vad: None,
+        };
+        assert!(AudioLevel::combine(&[]).is_none());
+        let combined =
+            AudioLevel::combine(&[level(-20.0, -6.0, 1), level(-100.0, -30.0, 2)]).unwrap();
+        // 能量平均：一半时间 -20dB、一半静音 ≈ -23dB
+        assert!(
+            (combined.rms_db + 23.01).abs() < 0.05,
+            "{}",
+            combined.rms_db
+        );
+        assert_eq!(combined.peak_db, -6.0);
+        assert_eq!(combined.channels[0].peak_db, -6.0);
+        assert_eq!(combined.clip_count, 3);
+    }
+}
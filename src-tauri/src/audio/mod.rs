@@ -1,7 +1,22 @@
+pub mod agc;
+pub mod chain;
 pub mod config;
+pub mod denoise;
+pub mod downmix;
+pub mod dsp_worker;
+pub mod file_source;
+pub mod highpass;
+pub mod level;
 pub mod processor;
+#[cfg(target_os = "linux")]
+pub mod pulse;
+pub mod recorder;
+pub mod ring;
+pub mod vad;
+pub mod watcher;
 
-pub use config::{AudioConfig, RecordingState, VolumeStats};
+pub use config::{AudioConfig, CaptureConfig, InputFormat, RecordingState, ResamplerQuality};
 pub use processor::{
-    find_device_by_name, find_loopback_device, get_audio_devices, process_audio_data, AudioDevice,
+    device_id_of, find_device_by_id, find_device_by_name, find_loopback_device, get_audio_devices,
+    is_loopback_device, process_audio_data, select_input_config, AudioDevice,
 };
@@ -1,180 +1,99 @@
-use crate::audio::config::{AudioConfig, RecordingState, VolumeStats};
+use crate::audio::config::{AudioConfig, InputFormat, RecordingState};
 // use crate::utils::file; // 调试时启用文件写入，会降低性能
+use anyhow::{anyhow, Context};
 use cpal::traits::{DeviceTrait, HostTrait};
-use cpal::Sample;
-use rubato::Resampler;
+use cpal::{Sample, SupportedStreamConfig, SupportedStreamConfigRange};
 use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AudioDevice {
-    pub name: String,
-    /// "microphone" | "loopback"
-    pub device_type: String,
-    pub is_default: bool,
+use std::collections::HashMap;
+
+/// 设备类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Microphone,
+    Loopback,
+    /// 单个应用的音频输出（目前仅 Linux 的 PulseAudio / PipeWire 支持）
+    Application,
 }
 
-// 在重采样后对样本进行音量调整
-fn apply_gain(samples: &[f32], gain: f32) -> Vec<f32> {
-    samples
-        .iter()
-        .map(|&s| {
-            // 放大后限制在[-1.0, 1.0]范围内，避免失真
-            (s * gain).clamp(-1.0, 1.0)
-        })
-        .collect()
+/// 设备支持的一种输入流配置（采样率为连续范围）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupportedInputConfig {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// 采样格式（"f32"、"i16" 等）
+    pub sample_format: String,
 }
 
-// 更新音量统计信息（应在 gain 应用之后调用，统计实际发送给 ASR 的音量）
-fn update_volume_stats(samples: &[f32], stats: &mut VolumeStats) {
-    if samples.is_empty() {
-        return;
+impl SupportedInputConfig {
+    fn from_range(range: &SupportedStreamConfigRange) -> Self {
+        Self {
+            channels: range.channels(),
+            min_sample_rate: range.min_sample_rate().0,
+            max_sample_rate: range.max_sample_rate().0,
+            sample_format: range.sample_format().to_string(),
+        }
     }
 
-    // 计算 RMS（均方根）音量
-    let sum_sq: f32 = samples.iter().map(|&s| s * s).sum();
-    let rms = (sum_sq / samples.len() as f32).sqrt();
-
-    // 计算峰值音量
-    let peak = samples.iter().map(|&s| s.abs()).fold(0.0f32, f32::max);
-
-    // 更新统计
-    stats.frame_count += 1;
-    stats.max_volume = stats.max_volume.max(peak);
-
-    // 累积平均音量（移动平均）
-    let alpha = 0.1; // 平滑因子
-    stats.avg_volume = alpha * rms + (1.0 - alpha) * stats.avg_volume;
-
-    // 检测低音量（RMS < 0.01 或峰值 < 0.05），同时更新窗口计数
-    if rms < 0.01 || peak < 0.05 {
-        stats.low_volume_count += 1;
-        stats.window_low_count += 1;
+    /// 是否满足指定的输入格式（未指定采样格式时不比较）
+    pub fn matches(&self, format: &InputFormat) -> bool {
+        let format_ok = match &format.sample_format {
+            Some(sample_format) => *sample_format == self.sample_format,
+            None => true,
+        };
+        format_ok
+            && self.channels == format.channels
+            && (self.min_sample_rate..=self.max_sample_rate).contains(&format.sample_rate)
     }
+}
 
-    // 每100帧打印一次音量统计，使用当前窗口（最近100帧）的低音量比例
-    if stats.frame_count % 100 == 0 {
-        // window_low_count 只统计最近100帧，比历史累积比例更能反映当前状态
-        let window_low_ratio = stats.window_low_count as f32 / 100.0 * 100.0;
-        log::debug!(
-            "音量统计 - RMS: {:.4}, 峰值: {:.4}, 最大: {:.4}, 低音量帧: {:.1}%（最近100帧）",
-            rms,
-            peak,
-            stats.max_volume,
-            window_low_ratio
-        );
-
-        // 如果当前窗口低音量帧比例过高，发出警告
-        if window_low_ratio > 50.0 {
-            log::warn!(
-                "⚠️ 检测到高比例低音量帧 ({:.1}%)，建议增加音频增益或检查音频输入源",
-                window_low_ratio
-            );
-        }
-
-        // 如果峰值过低，也发出警告
-        if stats.max_volume < 0.1 {
-            log::warn!(
-                "⚠️ 峰值音量过低 ({:.4})，建议增加音频增益（当前增益可能不够）",
-                stats.max_volume
-            );
-        }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioDevice {
+    /// 稳定标识（音频后端:设备名，同名设备按枚举顺序追加 #2、#3…），可用于区分两个相同型号的设备
+    pub id: String,
+    pub name: String,
+    /// 音频后端（WASAPI / ALSA / CoreAudio 等）
+    pub host_api: String,
+    pub device_type: DeviceKind,
+    pub is_default: bool,
+    /// 应用的进程 ID（仅 Application 类型）
+    pub pid: Option<u32>,
+    /// 设备支持的全部输入流配置
+    pub supported_configs: Vec<SupportedInputConfig>,
+}
 
-        // 重置窗口计数，开始统计下一个100帧
-        stats.window_low_count = 0;
-    }
+/// 可用于采集的设备及其稳定 ID
+struct CaptureDevice {
+    id: String,
+    name: String,
+    kind: DeviceKind,
+    is_default: bool,
+    device: cpal::Device,
 }
 
 pub fn process_audio_data<T>(input: &[T], state: &mut RecordingState, config: &AudioConfig)
 where
     T: Sample,
 {
-    let samples = input
-        .iter()
-        .map(|s| s.to_float_sample().to_sample::<f32>())
-        .collect::<Vec<f32>>();
-
-    // 文件写入操作移到后台，避免在音频回调中阻塞
-    // file::write_original_audio(&samples); // 调试时启用，会降低性能
-
-    // 累计样本数据
-    state.sample_buffer.extend_from_slice(&samples);
+    // 累计样本数据（直接转换写入，不分配临时缓冲区）
+    state.sample_buffer.extend(
+        input
+            .iter()
+            .map(|s| s.to_float_sample().to_sample::<f32>()),
+    );
 
     // 处理累积的样本：循环直到缓冲区中不足一帧为止
     // 每次回调可能带来多帧数据，必须用 while 全部处理
-    while state.sample_buffer.len() >= config.frame_size * config.channels as usize {
-        // 分离通道
-        for i in 0..config.channels as usize {
-            state.channel_data[i].clear();
-            for j in 0..config.frame_size {
-                state.channel_data[i].push(state.sample_buffer[j * config.channels as usize + i]);
-            }
-        }
-
-        // TODO-1: 每100帧记录一次原始各声道峰值，用于诊断相位抵消
-        // 若 ch0_peak ≈ ch1_peak 但 post_mix_peak ≈ 0，则确认为相位抵消
-        if state.volume_stats.frame_count % 100 == 0 {
-            for (i, ch) in state.channel_data.iter().enumerate() {
-                let ch_peak = ch.iter().map(|&s| s.abs()).fold(0.0f32, f32::max);
-                log::debug!("原始声道[{}] 峰值: {:.4}", i, ch_peak);
-            }
-        }
-
-        // 重采样
-        let resampler = &mut state.resampler;
-        match resampler.process(&state.channel_data, None) {
-            Ok(processed) => {
-                // 强制转换为单声道（取 RMS 最大声道，避免相位抵消）
-                let mono_samples = mix_to_mono(&processed);
-
-                // 应用音量增益（根据配置调整）
-                let amplified_samples = if config.gain != 1.0 {
-                    apply_gain(&mono_samples, config.gain)
-                } else {
-                    mono_samples
-                };
-
-                // TODO-3: 在 gain 应用之后统计，反映实际发送给 ASR 的音量
-                update_volume_stats(&amplified_samples, &mut state.volume_stats);
-
-                // 异步发送, 缓冲区满时丢弃数据（发送放大后的音频）
-                if let Err(e) = state.tx.try_send(amplified_samples) {
-                    eprintln!("警告: 音频数据通道已满，丢弃当前数据块: {:?}", e);
-                }
-            }
-            Err(e) => eprintln!("Error resampling: {}", e),
-        }
+    let frame_samples = config.frame_size * config.channels as usize;
+    while state.sample_buffer.len() >= frame_samples {
+        state.chain.process(
+            &state.sample_buffer[..frame_samples],
+            config.channels as usize,
+            &config.selected_channels,
+        );
         // TODO-1: 只移除已处理的样本，保留缓冲区中多余的数据供下次处理
-        let consumed = config.frame_size * config.channels as usize;
-        state.sample_buffer.drain(..consumed);
-    }
-}
-
-// 多通道转单声道：选取 RMS 最大的声道，而非平均所有声道。
-// 平均混音在左右声道相位相反时会完全抵消（loopback 设备常见），导致静音。
-// 取 RMS 最大声道可保留最强信号，不受相位关系影响。
-fn mix_to_mono(channels: &[Vec<f32>]) -> Vec<f32> {
-    if channels.is_empty() {
-        return Vec::new();
-    }
-
-    if channels.len() == 1 {
-        return channels[0].clone();
-    }
-
-    // 计算各声道 RMS，选信号最强的那路
-    let best_channel = channels
-        .iter()
-        .enumerate()
-        .max_by(|(_, a), (_, b)| {
-            let rms_a: f32 = (a.iter().map(|&s| s * s).sum::<f32>() / a.len() as f32).sqrt();
-            let rms_b: f32 = (b.iter().map(|&s| s * s).sum::<f32>() / b.len() as f32).sqrt();
-            rms_a.partial_cmp(&rms_b).unwrap_or(std::cmp::Ordering::Equal)
-        })
-        .map(|(_, ch)| ch);
-
-    match best_channel {
-        Some(ch) => ch.clone(),
-        None => Vec::new(),
+        state.sample_buffer.drain(..frame_samples);
     }
 }
 
@@ -225,7 +144,7 @@ impl DeviceError {
 
 /// 检查设备是否是环回设备（用于系统音频捕获）
 /// 返回优先级：数字越小优先级越高
-fn is_loopback_device(device: &cpal::Device) -> Option<u8> {
+pub fn is_loopback_device(device: &cpal::Device) -> Option<u8> {
     let name = device.name().ok()?;
     let name_lower = name.to_lowercase();
     
@@ -303,22 +222,23 @@ pub fn find_loopback_device() -> Result<cpal::Device, DeviceError> {
         .ok_or_else(|| DeviceError::loopback_not_found())
 }
 
-/// 获取所有可用的音频输入设备列表
-pub fn get_audio_devices() -> Vec<AudioDevice> {
-    let host = cpal::default_host();
-    let mut device_list: Vec<AudioDevice> = Vec::new();
+/// 枚举可用于采集的设备：全部输入设备，Windows 上还包括可做环回采集的输出设备
+/// get_audio_devices 与 find_device_by_id 共用，保证 ID 的分配方式一致
+fn capture_devices(host: &cpal::Host) -> Vec<CaptureDevice> {
+    let host_api = host.id().name();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut next_id = |name: &str| {
+        let ordinal = seen.entry(name.to_string()).or_insert(0);
+        *ordinal += 1;
+        device_id(host_api, name, *ordinal)
+    };
+    let mut list = Vec::new();
 
     let default_in_name = host
         .default_input_device()
         .and_then(|d| d.name().ok())
         .unwrap_or_default();
 
-    #[cfg(target_os = "windows")]
-    let default_out_name = host
-        .default_output_device()
-        .and_then(|d| d.name().ok())
-        .unwrap_or_default();
-
     // 第一步：扫描所有 Input 设备
     // 覆盖范围：
     //   macOS  → 麦克风 + BlackHole Input 端 / Soundflower
@@ -329,21 +249,23 @@ pub fn get_audio_devices() -> Vec<AudioDevice> {
             if let Ok(name) = device.name() {
                 let name_lower = name.to_lowercase();
 
-                let device_type = if name.ends_with(".monitor")
+                let kind = if name.ends_with(".monitor")
                     || name_lower.contains("blackhole")
                     || name_lower.contains("soundflower")
                     || name_lower.contains("vb-cable")
                     || name_lower.contains("loopback")
                 {
-                    "loopback"
+                    DeviceKind::Loopback
                 } else {
-                    "microphone"
+                    DeviceKind::Microphone
                 };
 
-                device_list.push(AudioDevice {
+                list.push(CaptureDevice {
+                    id: next_id(&name),
                     is_default: name == default_in_name,
                     name,
-                    device_type: device_type.to_string(),
+                    kind,
+                    device,
                 });
             }
         }
@@ -352,17 +274,27 @@ pub fn get_audio_devices() -> Vec<AudioDevice> {
     // 第二步：仅 Windows 需要额外扫描 Output 设备
     // 注意：最终采集是通过输入流实现，因此这里也验证默认输入配置可用
     #[cfg(target_os = "windows")]
-    if let Ok(devices) = host.output_devices() {
-        for device in devices {
-            if let Ok(name) = device.name() {
-                // 去重：防止 VB-Cable 等虚拟声卡在 Input 和 Output 列表中都出现
-                if !device_list.iter().any(|d| d.name == name) {
+    {
+        let default_out_name = host
+            .default_output_device()
+            .and_then(|d| d.name().ok())
+            .unwrap_or_default();
+        let input_count = list.len();
+        if let Ok(devices) = host.output_devices() {
+            for device in devices {
+                if let Ok(name) = device.name() {
+                    // 去重：防止 VB-Cable 等虚拟声卡在 Input 和 Output 列表中都出现
+                    if list[..input_count].iter().any(|d| d.name == name) {
+                        continue;
+                    }
                     // 只有能作为输入流使用的设备才加入列表（避免 UI 选了但无法启动）
                     if device.default_input_config().is_ok() {
-                        device_list.push(AudioDevice {
+                        list.push(CaptureDevice {
+                            id: next_id(&name),
                             is_default: name == default_out_name,
                             name,
-                            device_type: "loopback".to_string(),
+                            kind: DeviceKind::Loopback,
+                            device,
                         });
                     }
                 }
@@ -370,7 +302,134 @@ pub fn get_audio_devices() -> Vec<AudioDevice> {
         }
     }
 
-    device_list
+    list
+}
+
+fn device_id(host_api: &str, name: &str, ordinal: usize) -> String {
+    if ordinal <= 1 {
+        format!("{}:{}", host_api, name)
+    } else {
+        format!("{}:{}#{}", host_api, name, ordinal)
+    }
+}
+
+/// 获取所有可用的音频输入设备列表
+pub fn get_audio_devices() -> Vec<AudioDevice> {
+    let host = cpal::default_host();
+    let host_api = host.id().name().to_string();
+
+    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+    let mut devices: Vec<AudioDevice> = capture_devices(&host)
+        .into_iter()
+        .map(|d| AudioDevice {
+            supported_configs: d
+                .device
+                .supported_input_configs()
+                .map(|configs| configs.map(|c| SupportedInputConfig::from_range(&c)).collect())
+                .unwrap_or_default(),
+            id: d.id,
+            name: d.name,
+            host_api: host_api.clone(),
+            device_type: d.kind,
+            is_default: d.is_default,
+            pid: None,
+        })
+        .collect();
+
+    #[cfg(target_os = "linux")]
+    {
+        devices.extend(pulse_monitor_devices());
+        devices.extend(pulse_application_devices());
+    }
+
+    devices
+}
+
+/// Linux：通过 PulseAudio / PipeWire 列出各输出设备的 monitor（ALSA 经常不暴露 .monitor 设备）
+#[cfg(target_os = "linux")]
+fn pulse_monitor_devices() -> Vec<AudioDevice> {
+    use crate::audio::pulse;
+
+    let sinks = match pulse::list_sinks() {
+        Ok(sinks) => sinks,
+        Err(e) => {
+            log::debug!("PulseAudio 不可用，跳过 monitor 设备: {}", e);
+            return Vec::new();
+        }
+    };
+    let default_sink = pulse::default_sink_name().unwrap_or_default();
+    sinks
+        .into_iter()
+        .map(|sink| AudioDevice {
+            id: sink.device_id(),
+            name: sink.monitor_name(),
+            host_api: pulse::HOST_API.to_string(),
+            device_type: DeviceKind::Loopback,
+            is_default: sink.name == default_sink,
+            pid: None,
+            // parec 可按任意采样率和声道数输出，这里报告 sink 的原生格式
+            supported_configs: vec![SupportedInputConfig {
+                channels: sink.channels,
+                min_sample_rate: sink.sample_rate,
+                max_sample_rate: sink.sample_rate,
+                sample_format: "f32".to_string(),
+            }],
+        })
+        .collect()
+}
+
+/// Linux：正在播放音频的应用（每个进程一项），可只采集该应用的声音
+#[cfg(target_os = "linux")]
+fn pulse_application_devices() -> Vec<AudioDevice> {
+    use crate::audio::pulse;
+
+    pulse::list_sink_inputs()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|input| AudioDevice {
+            id: input.device_id(),
+            name: input.display_name(),
+            host_api: pulse::HOST_API.to_string(),
+            device_type: DeviceKind::Application,
+            is_default: false,
+            pid: input.pid,
+            supported_configs: vec![SupportedInputConfig {
+                channels: input.channels,
+                min_sample_rate: input.sample_rate,
+                max_sample_rate: input.sample_rate,
+                sample_format: "f32".to_string(),
+            }],
+        })
+        .collect()
+}
+
+/// 根据稳定 ID（get_audio_devices 返回的 id）查找设备
+pub fn find_device_by_id(device_id: &str) -> Option<cpal::Device> {
+    capture_devices(&cpal::default_host())
+        .into_iter()
+        .find(|d| d.id == device_id && d.device.default_input_config().is_ok())
+        .map(|d| d.device)
+}
+
+/// 设备的稳定 ID（同名设备视为第一个，与 find_device_by_name 的选择一致）
+pub fn device_id_of(device: &cpal::Device) -> Option<String> {
+    let name = device.name().ok()?;
+    Some(device_id(cpal::default_host().id().name(), &name, 1))
+}
+
+/// 设备是否仍然存在（只枚举设备，不探测支持的格式）；无法枚举时返回 None
+pub fn device_exists(device_id: &str) -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    if crate::audio::pulse::is_device_id(device_id) {
+        let sinks = crate::audio::pulse::list_sinks().ok()?;
+        return Some(sinks.iter().any(|sink| sink.device_id() == device_id));
+    }
+    let devices = capture_devices(&cpal::default_host());
+    // 枚举失败时可能返回空列表，此时无法判断
+    if devices.is_empty() {
+        return None;
+    }
+    Some(devices.iter().any(|d| d.id == device_id))
 }
 
 /// 根据设备名称查找设备
@@ -393,6 +452,36 @@ pub fn find_device_by_name(device_name: &str) -> Option<cpal::Device> {
     None
 }
 
+/// 选择输入流配置：未指定时使用设备默认配置，否则从设备支持的配置中查找（未指定采样格式时优先 f32）
+pub fn select_input_config(
+    device: &cpal::Device,
+    format: Option<&InputFormat>,
+) -> anyhow::Result<SupportedStreamConfig> {
+    let Some(format) = format else {
+        return device
+            .default_input_config()
+            .context("无法获取默认输入配置");
+    };
+    let mut candidates: Vec<SupportedStreamConfigRange> = device
+        .supported_input_configs()
+        .context("无法获取设备支持的输入配置")?
+        .filter(|range| SupportedInputConfig::from_range(range).matches(format))
+        .collect();
+    candidates.sort_by_key(|range| range.sample_format() != cpal::SampleFormat::F32);
+    candidates
+        .into_iter()
+        .next()
+        .map(|range| range.with_sample_rate(cpal::SampleRate(format.sample_rate)))
+        .ok_or_else(|| {
+            anyhow!(
+                "设备不支持输入格式 {}Hz / {} 声道 / {}",
+                format.sample_rate,
+                format.channels,
+                format.sample_format.as_deref().unwrap_or("任意采样格式")
+            )
+        })
+}
+
 /// 验证重采样后的音频数据
 /// 注意：此函数在音频回调中被调用，应尽可能快速执行
 /// 目前已被禁用以避免性能问题，仅在需要调试时启用
@@ -408,3 +497,38 @@ fn validate_resampled_audio(samples: &[f32]) {
     let _has_audio = samples.iter().any(|&s| s != 0.0);
     // 移除了所有日志输出和复杂验证，以保持回调函数快速执行
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_ids_distinguish_duplicates() {
+        assert_eq!(device_id("ALSA", "USB Mic", 1), "ALSA:USB Mic");
+        assert_eq!(device_id("ALSA", "USB Mic", 2), "ALSA:USB Mic#2");
+        assert_eq!(
+            serde_json::to_string(&DeviceKind::Loopback).unwrap(),
+            r#""loopback""#
+        );
+    }
+
+    #[test]
+    fn test_supported_config_matches_format() {
+        let config = SupportedInputConfig {
+            channels: 2,
+            min_sample_rate: 44100,
+            max_sample_rate: 48000,
+            sample_format: "i16".to_string(),
+        };
+        let format = |sample_rate, channels, sample_format: Option<&str>| InputFormat {
+            sample_rate,
+            channels,
+            sample_format: sample_format.map(str::to_string),
+        };
+        assert!(config.matches(&format(48000, 2, None)));
+        assert!(config.matches(&format(44100, 2, Some("i16"))));
+        assert!(!config.matches(&format(48000, 2, Some("f32"))));
+        assert!(!config.matches(&format(16000, 2, None)));
+        assert!(!config.matches(&format(48000, 1, None)));
+    }
+}
@@ -0,0 +1,558 @@
+// Linux 系统音频采集后端：通过 pactl / parec（PulseAudio 与 PipeWire 的 pipewire-pulse 都提供）
+// 枚举输出设备（sink）并采集其 monitor 源，不依赖 ALSA 是否暴露 .monitor 设备；
+// 也可以只采集某个应用的播放流（sink-input），避免通知音、音乐等混入识别
+// 采集到的 float32 交错样本交给调用方回调，和 cpal 设备走同一条 RecordingState 处理管线
+use anyhow::{anyhow, bail, Context};
+use std::io::Read;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 设备列表中显示的音频后端名称
+pub const HOST_API: &str = "PulseAudio";
+
+/// 设备 ID 前缀（后接 monitor 源名称）
+const ID_PREFIX: &str = "pulse:";
+/// 应用 ID 前缀（后接进程 ID）
+const APP_ID_PREFIX: &str = "pulse-app:";
+/// 没有进程 ID 的播放流的 ID 前缀（后接 sink-input 序号）
+const STREAM_ID_PREFIX: &str = "pulse-stream:";
+
+/// 每次从 parec 读取的字节数（约 20ms 的 48kHz 立体声 float32）
+const READ_CHUNK_BYTES: usize = 7680;
+/// 应用的播放流消失后，重新查找该进程播放流的间隔
+const STREAM_RESOLVE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 输出设备（sink）
+#[derive(Debug, Clone, PartialEq)]
+pub struct PulseSink {
+    pub name: String,
+    pub description: String,
+    /// monitor 源名称（parec --device 使用）
+    pub monitor_source: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl PulseSink {
+    /// 显示名称（与 PulseAudio 对 monitor 源的命名一致）
+    pub fn monitor_name(&self) -> String {
+        format!("Monitor of {}", self.description)
+    }
+
+    pub fn device_id(&self) -> String {
+        format!("{}{}", ID_PREFIX, self.monitor_source)
+    }
+}
+
+/// 应用的播放流（sink-input）
+#[derive(Debug, Clone, PartialEq)]
+pub struct PulseSinkInput {
+    pub index: u32,
+    /// application.name
+    pub application: String,
+    /// application.process.id（部分客户端不提供）
+    pub pid: Option<u32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl PulseSinkInput {
+    /// 显示名称，带进程 ID 以区分同一程序的多个实例
+    pub fn display_name(&self) -> String {
+        match self.pid {
+            Some(pid) => format!("{} (PID {})", self.application, pid),
+            None => format!("{} (#{})", self.application, self.index),
+        }
+    }
+
+    /// 有进程 ID 时按进程标识（应用重新创建播放流后仍然有效），否则按 sink-input 序号
+    pub fn device_id(&self) -> String {
+        match self.pid {
+            Some(pid) => format!("{}{}", APP_ID_PREFIX, pid),
+            None => format!("{}{}", STREAM_ID_PREFIX, self.index),
+        }
+    }
+}
+
+/// parec 的采集对象
+#[derive(Debug, Clone, PartialEq)]
+pub enum PulseTarget {
+    /// 输出设备的 monitor 源
+    Monitor(String),
+    /// 单个应用的播放流；有进程 ID 时播放流关闭后按进程重新查找
+    SinkInput { index: u32, pid: Option<u32> },
+}
+
+impl PulseTarget {
+    /// parec 参数和日志中的采集对象描述
+    fn describe(&self) -> (String, String) {
+        match self {
+            PulseTarget::Monitor(source) => (
+                format!("--device={}", source),
+                format!("monitor 源 {}", source),
+            ),
+            PulseTarget::SinkInput { index, .. } => (
+                format!("--monitor-stream={}", index),
+                format!("播放流 #{}", index),
+            ),
+        }
+    }
+}
+
+/// 列出所有输出设备
+pub fn list_sinks() -> anyhow::Result<Vec<PulseSink>> {
+    Ok(parse_sinks(&pactl(&["list", "sinks"])?))
+}
+
+/// 默认输出设备名称
+pub fn default_sink_name() -> anyhow::Result<String> {
+    // pactl get-default-sink 需要 PulseAudio 15+，pactl info 在各版本中都有 Default Sink
+    parse_default_sink(&pactl(&["info"])?).ok_or_else(|| anyhow!("没有默认输出设备"))
+}
+
+/// 默认输出设备
+pub fn default_sink() -> anyhow::Result<PulseSink> {
+    let name = default_sink_name()?;
+    list_sinks()?
+        .into_iter()
+        .find(|sink| sink.name == name)
+        .ok_or_else(|| anyhow!("找不到默认输出设备 {}", name))
+}
+
+/// 是否是本后端分配的设备 ID
+pub fn is_device_id(id: &str) -> bool {
+    id.starts_with(ID_PREFIX)
+}
+
+/// 是否是应用播放流的 ID
+pub fn is_application_id(id: &str) -> bool {
+    id.starts_with(APP_ID_PREFIX) || id.starts_with(STREAM_ID_PREFIX)
+}
+
+/// 列出正在播放的应用音频流（同一进程的多个流只保留第一个）
+pub fn list_sink_inputs() -> anyhow::Result<Vec<PulseSinkInput>> {
+    let mut inputs = parse_sink_inputs(&pactl(&["list", "sink-inputs"])?);
+    let mut seen = Vec::new();
+    inputs.retain(|input| match input.pid {
+        Some(pid) if seen.contains(&pid) => false,
+        Some(pid) => {
+            seen.push(pid);
+            true
+        }
+        None => true,
+    });
+    Ok(inputs)
+}
+
+/// 按进程 ID 查找应用的播放流
+pub fn find_sink_input_by_pid(pid: u32) -> Option<PulseSinkInput> {
+    list_sink_inputs()
+        .ok()?
+        .into_iter()
+        .find(|input| input.pid == Some(pid))
+}
+
+/// 按应用 ID（list_sink_inputs 返回的 device_id）查找播放流
+pub fn find_sink_input(id: &str) -> Option<PulseSinkInput> {
+    if let Some(pid) = id.strip_prefix(APP_ID_PREFIX) {
+        return find_sink_input_by_pid(pid.parse().ok()?);
+    }
+    let index: u32 = id.strip_prefix(STREAM_ID_PREFIX)?.parse().ok()?;
+    list_sink_inputs()
+        .ok()?
+        .into_iter()
+        .find(|input| input.index == index)
+}
+
+/// 按设备 ID 或显示名称查找输出设备
+pub fn find_sink(id_or_name: &str) -> Option<PulseSink> {
+    list_sinks().ok()?.into_iter().find(|sink| {
+        sink.device_id() == id_or_name
+            || sink.monitor_name() == id_or_name
+            || sink.monitor_source == id_or_name
+    })
+}
+
+fn pactl(args: &[&str]) -> anyhow::Result<String> {
+    // 固定 C locale，输出字段名不随系统语言变化
+    let output = Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .context("无法执行 pactl（需要 PulseAudio 或 pipewire-pulse）")?;
+    if !output.status.success() {
+        bail!(
+            "pactl {} 失败: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 解析 `pactl list sinks` 的输出
+fn parse_sinks(output: &str) -> Vec<PulseSink> {
+    let mut sinks = Vec::new();
+    let mut current: Option<PulseSink> = None;
+    for line in output.lines() {
+        if line.starts_with("Sink #") {
+            sinks.extend(current.take());
+            current = Some(PulseSink {
+                name: String::new(),
+                description: String::new(),
+                monitor_source: String::new(),
+                sample_rate: 48000,
+                channels: 2,
+            });
+            continue;
+        }
+        let (Some(sink), Some((key, value))) = (current.as_mut(), line.trim().split_once(": "))
+        else {
+            continue;
+        };
+        match key {
+            "Name" => sink.name = value.to_string(),
+            "Description" => sink.description = value.to_string(),
+            "Monitor Source" => sink.monitor_source = value.to_string(),
+            "Sample Specification" => {
+                let (rate, channels) = parse_sample_spec(value);
+                sink.sample_rate = rate.unwrap_or(sink.sample_rate);
+                sink.channels = channels.unwrap_or(sink.channels);
+            }
+            _ => {}
+        }
+    }
+    sinks.extend(current);
+    sinks.retain(|sink| !sink.name.is_empty() && !sink.monitor_source.is_empty());
+    sinks
+}
+
+/// 解析 `pactl list sink-inputs` 的输出
+fn parse_sink_inputs(output: &str) -> Vec<PulseSinkInput> {
+    let mut inputs = Vec::new();
+    let mut current: Option<PulseSinkInput> = None;
+    for line in output.lines() {
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            inputs.extend(current.take());
+            current = index.trim().parse().ok().map(|index| PulseSinkInput {
+                index,
+                application: String::new(),
+                pid: None,
+                sample_rate: 48000,
+                channels: 2,
+            });
+            continue;
+        }
+        let Some(input) = current.as_mut() else {
+            continue;
+        };
+        let line = line.trim();
+        if let Some(spec) = line.strip_prefix("Sample Specification: ") {
+            let (rate, channels) = parse_sample_spec(spec);
+            input.sample_rate = rate.unwrap_or(input.sample_rate);
+            input.channels = channels.unwrap_or(input.channels);
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            // 属性值带引号：application.name = "Firefox"
+            let value = value.trim_matches('"');
+            match key {
+                "application.name" => input.application = value.to_string(),
+                "application.process.id" => input.pid = value.parse().ok(),
+                _ => {}
+            }
+        }
+    }
+    inputs.extend(current);
+    for input in &mut inputs {
+        if input.application.is_empty() {
+            input.application = "Unknown".to_string();
+        }
+    }
+    inputs
+}
+
+/// 解析采样规格，例如 "float32le 2ch 48000Hz"，返回 (采样率, 声道数)
+fn parse_sample_spec(spec: &str) -> (Option<u32>, Option<u16>) {
+    let mut rate = None;
+    let mut channels = None;
+    for token in spec.split_whitespace() {
+        if let Some(ch) = token.strip_suffix("ch") {
+            channels = ch.parse().ok();
+        } else if let Some(hz) = token.strip_suffix("Hz") {
+            rate = hz.parse().ok();
+        }
+    }
+    (rate, channels)
+}
+
+/// 从 `pactl info` 的输出中取默认 sink 名称
+fn parse_default_sink(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Default Sink: "))
+        .map(|name| name.trim().to_string())
+}
+
+/// 正在运行的 parec 采集进程；丢弃时结束进程并等待读取线程退出
+pub struct PulseCapture {
+    /// 当前的 parec 进程（应用重新创建播放流后由读取线程替换）
+    child: Arc<Mutex<Child>>,
+    stopping: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl PulseCapture {
+    /// 采集 monitor 源或应用播放流，按 sample_rate / channels 输出 float32 交错样本
+    /// on_data 在读取线程中调用；进程意外退出时调用一次 on_exit。
+    /// 按进程采集的播放流关闭（暂停、切换曲目）不算退出：等待该进程重新创建播放流后继续采集
+    pub fn start(
+        target: &PulseTarget,
+        sample_rate: u32,
+        channels: u16,
+        mut on_data: impl FnMut(&[f32]) + Send + 'static,
+        on_exit: impl FnOnce(String) + Send + 'static,
+    ) -> anyhow::Result<Self> {
+        let (child, mut stdout) = spawn_parec(target, sample_rate, channels)?;
+        let child = Arc::new(Mutex::new(child));
+        let (_, mut source) = target.describe();
+        let pid = match target {
+            PulseTarget::SinkInput { pid, .. } => *pid,
+            PulseTarget::Monitor(_) => None,
+        };
+
+        let stopping = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let stopping = stopping.clone();
+            let child = child.clone();
+            thread::spawn(move || {
+                let mut buf = vec![0u8; READ_CHUNK_BYTES];
+                let mut pending: Vec<u8> = Vec::with_capacity(READ_CHUNK_BYTES + 4);
+                let mut samples: Vec<f32> = Vec::with_capacity(READ_CHUNK_BYTES / 4);
+                let reason = loop {
+                    match stdout.read(&mut buf) {
+                        Ok(0) => {
+                            let ended = format!("{} 的采集进程已退出", source);
+                            let Some(pid) = pid.filter(|_| !stopping.load(Ordering::SeqCst)) else {
+                                break ended;
+                            };
+                            log::info!("⏸️ {}，等待进程 {} 重新创建播放流", ended, pid);
+                            let _ = lock_child(&child).wait();
+                            pending.clear();
+                            match wait_for_stream(pid, sample_rate, channels, &stopping) {
+                                Ok(Some((target, next_child, next_stdout))) => {
+                                    let mut current = lock_child(&child);
+                                    *current = next_child;
+                                    if stopping.load(Ordering::SeqCst) {
+                                        // 启动期间 Drop 已结束旧进程，新进程在这里结束
+                                        let _ = current.kill();
+                                        let _ = current.wait();
+                                        return;
+                                    }
+                                    drop(current);
+                                    stdout = next_stdout;
+                                    (_, source) = target.describe();
+                                    log::info!("▶️ 继续采集进程 {} 的{}", pid, source);
+                                }
+                                Ok(None) => return,
+                                Err(e) => {
+                                    break format!("重新采集进程 {} 的播放流失败: {}", pid, e)
+                                }
+                            }
+                        }
+                        Ok(n) => {
+                            // 管道读取不保证按 4 字节对齐，剩余的半个样本留到下次
+                            pending.extend_from_slice(&buf[..n]);
+                            let whole = pending.len() / 4 * 4;
+                            samples.clear();
+                            samples.extend(
+                                pending[..whole]
+                                    .chunks_exact(4)
+                                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                            );
+                            pending.drain(..whole);
+                            on_data(&samples);
+                        }
+                        Err(e) => break format!("读取{}失败: {}", source, e),
+                    }
+                };
+                if !stopping.load(Ordering::SeqCst) {
+                    on_exit(reason);
+                }
+            })
+        };
+
+        Ok(Self {
+            child,
+            stopping,
+            reader: Some(reader),
+        })
+    }
+}
+
+impl Drop for PulseCapture {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        {
+            let mut child = lock_child(&self.child);
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+fn lock_child(child: &Mutex<Child>) -> std::sync::MutexGuard<'_, Child> {
+    child.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 启动 parec，返回进程及其标准输出
+fn spawn_parec(
+    target: &PulseTarget,
+    sample_rate: u32,
+    channels: u16,
+) -> anyhow::Result<(Child, ChildStdout)> {
+    let (target_arg, _) = target.describe();
+    let mut child = Command::new("parec")
+        .arg(target_arg)
+        .arg("--format=float32le")
+        .arg(format!("--rate={}", sample_rate))
+        .arg(format!("--channels={}", channels))
+        .arg("--raw")
+        .arg("--latency-msec=20")
+        .arg("--client-name=VocoSphere")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("无法启动 parec（需要 PulseAudio 或 pipewire-pulse）")?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("无法读取 parec 输出"))?;
+    Ok((child, stdout))
+}
+
+/// 等待进程重新创建播放流并启动 parec；停止采集时返回 None，进程退出时返回错误
+fn wait_for_stream(
+    pid: u32,
+    sample_rate: u32,
+    channels: u16,
+    stopping: &AtomicBool,
+) -> anyhow::Result<Option<(PulseTarget, Child, ChildStdout)>> {
+    loop {
+        if stopping.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+        if !std::path::Path::new(&format!("/proc/{}", pid)).exists() {
+            bail!("进程已退出");
+        }
+        if let Some(input) = find_sink_input_by_pid(pid) {
+            let target = PulseTarget::SinkInput {
+                index: input.index,
+                pid: Some(pid),
+            };
+            // 沿用原来的输出格式，parec 负责转换，处理管线无需重建
+            let (child, stdout) = spawn_parec(&target, sample_rate, channels)?;
+            return Ok(Some((target, child, stdout)));
+        }
+        thread::sleep(STREAM_RESOLVE_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINKS: &str = "Sink #0
+\tState: RUNNING
+\tName: alsa_output.pci-0000_00_1f.3.analog-stereo
+\tDescription: Built-in Audio Analog Stereo
+\tDriver: PipeWire
+\tSample Specification: s32le 2ch 48000Hz
+\tMonitor Source: alsa_output.pci-0000_00_1f.3.analog-stereo.monitor
+\tProperties:
+\t\tdevice.description = \"Built-in Audio Analog Stereo\"
+Sink #57
+\tName: vocosphere_null
+\tDescription: Null Output
+\tSample Specification: float32le 1ch 44100Hz
+\tMonitor Source: vocosphere_null.monitor
+";
+
+    const SINK_INPUTS: &str = "Sink Input #71
+\tDriver: PipeWire
+\tSink: 56
+\tSample Specification: float32le 2ch 44100Hz
+\tProperties:
+\t\tapplication.name = \"Firefox\"
+\t\tapplication.process.id = \"4242\"
+\t\tmedia.name = \"AudioStream\"
+Sink Input #90
+\tSink: 56
+\tSample Specification: s16le 1ch 16000Hz
+\tProperties:
+\t\tapplication.name = \"speech-dispatcher\"
+";
+
+    #[test]
+    fn test_parse_pactl_output() {
+        let sinks = parse_sinks(SINKS);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(
+            sinks[0].monitor_name(),
+            "Monitor of Built-in Audio Analog Stereo"
+        );
+        assert_eq!(
+            sinks[0].device_id(),
+            "pulse:alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"
+        );
+        assert_eq!((sinks[0].sample_rate, sinks[0].channels), (48000, 2));
+        assert_eq!((sinks[1].sample_rate, sinks[1].channels), (44100, 1));
+
+        let inputs = parse_sink_inputs(SINK_INPUTS);
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].display_name(), "Firefox (PID 4242)");
+        assert_eq!(inputs[0].device_id(), "pulse-app:4242");
+        assert_eq!((inputs[0].sample_rate, inputs[0].channels), (44100, 2));
+        assert_eq!(inputs[1].pid, None);
+        assert_eq!(inputs[1].device_id(), "pulse-stream:90");
+
+        let info = "Server Name: PulseAudio (on PipeWire 1.0.5)\nDefault Sink: vocosphere_null\nDefault Source: vocosphere_null.monitor\n";
+        assert_eq!(parse_default_sink(info).as_deref(), Some("vocosphere_null"));
+    }
+
+    /// 需要运行中的 PulseAudio / PipeWire（可无声卡）：
+    /// 创建 null sink 并采集其 monitor，运行 cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn test_capture_null_sink_monitor() {
+        let module = pactl(&[
+            "load-module",
+            "module-null-sink",
+            "sink_name=vocosphere_test_null",
+        ])
+        .expect("需要 PulseAudio / PipeWire");
+        let sink = find_sink("vocosphere_test_null.monitor").expect("null sink 未出现");
+
+        let received = Arc::new(std::sync::Mutex::new(0usize));
+        let counter = received.clone();
+        let capture = PulseCapture::start(
+            &PulseTarget::Monitor(sink.monitor_source.clone()),
+            16000,
+            2,
+            move |data| *counter.lock().unwrap() += data.len(),
+            |reason| panic!("{}", reason),
+        )
+        .unwrap();
+        thread::sleep(std::time::Duration::from_millis(500));
+        drop(capture);
+        let _ = pactl(&["unload-module", module.trim()]);
+
+        // null sink 没有播放时 monitor 输出静音，但仍按实时速率产生样本
+        assert!(*received.lock().unwrap() >= 16000 * 2 / 4);
+    }
+}
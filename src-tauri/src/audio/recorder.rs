@@ -0,0 +1,309 @@
+// 会话录音：处理后（重采样、下混、增益之后）的单声道音频经无锁环形缓冲区交给后台线程写文件，
+// 音频回调不会因磁盘 IO 或编码而阻塞；FLAC / Opus 由 ffmpeg 编码
+use crate::audio::ring::SampleRing;
+use crate::utils;
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 缓冲区容量（秒），写入线程卡顿超过该时长才会丢样本
+const RING_SECONDS: usize = 10;
+/// 写入线程的轮询间隔
+const WRITE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 录音文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingFormat {
+    /// 16 位 PCM WAV
+    #[default]
+    Wav,
+    /// 无损压缩（ffmpeg）
+    Flac,
+    /// 有损压缩，适合长时间会议（ffmpeg）
+    Opus,
+}
+
+impl RecordingFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Opus => "opus",
+        }
+    }
+
+    /// 是否需要 ffmpeg 编码
+    pub fn needs_encoder(&self) -> bool {
+        *self != Self::Wav
+    }
+}
+
+/// 录音配置（CaptureConfig.record，省略时不录音）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    /// 保存目录（用户选择），不存在时自动创建
+    pub directory: String,
+    #[serde(default)]
+    pub format: RecordingFormat,
+}
+
+/// 已保存的录音
+#[derive(Debug, Clone, Serialize)]
+pub struct Recording {
+    pub path: PathBuf,
+    pub duration_ms: u64,
+    /// 缓冲区满而丢弃的样本数
+    pub dropped_samples: u64,
+}
+
+/// 音频回调侧的录音入口；可复制，切换设备后新的音频流继续写入同一文件
+#[derive(Clone)]
+pub struct RecorderTap {
+    ring: Arc<SampleRing>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RecorderTap {
+    /// 写入处理后的样本（不阻塞），缓冲区满时丢弃并计数
+    pub fn push(&self, samples: &[f32]) {
+        let written = self.ring.push(samples);
+        if written < samples.len() {
+            self.dropped
+                .fetch_add((samples.len() - written) as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 会话录音器：持有写入线程，finish 后得到录音文件
+pub struct SessionRecorder {
+    tap: RecorderTap,
+    path: PathBuf,
+    sample_rate: u32,
+    stopping: Arc<AtomicBool>,
+    worker: Option<JoinHandle<anyhow::Result<u64>>>,
+}
+
+impl SessionRecorder {
+    /// 在 config.directory 下创建 "{会话 ID}_{时间}.{扩展名}" 并启动写入线程
+    /// ffmpeg 为 sidecar 命令，FLAC / Opus 格式必需
+    pub fn start(
+        config: &RecordingConfig,
+        session_id: &str,
+        sample_rate: u32,
+        ffmpeg: Option<Command>,
+    ) -> anyhow::Result<Self> {
+        let directory = Path::new(&config.directory);
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("无法创建录音目录 {}", directory.display()))?;
+        let path = directory.join(file_name(
+            session_id,
+            &chrono::Local::now().format("%Y%m%d-%H%M%S").to_string(),
+            config.format,
+        ));
+        let mut sink = match (config.format, ffmpeg) {
+            (RecordingFormat::Wav, _) => Sink::wav(&path, sample_rate)?,
+            (format, Some(ffmpeg)) => Sink::encoder(ffmpeg, &path, sample_rate, format)?,
+            (format, None) => return Err(anyhow!("{:?} 录音需要 ffmpeg", format)),
+        };
+
+        let ring = Arc::new(SampleRing::new(sample_rate as usize * RING_SECONDS));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let ring = ring.clone();
+            let stopping = stopping.clone();
+            thread::spawn(move || {
+                let mut buf = vec![0.0f32; sample_rate as usize];
+                let mut written = 0u64;
+                loop {
+                    // 先读取停止标志，保证停止前写入的样本都被取出
+                    let stop = stopping.load(Ordering::SeqCst);
+                    loop {
+                        let n = ring.pop(&mut buf);
+                        if n == 0 {
+                            break;
+                        }
+                        sink.write(&buf[..n])?;
+                        written += n as u64;
+                    }
+                    if stop {
+                        break;
+                    }
+                    thread::sleep(WRITE_INTERVAL);
+                }
+                sink.finish()?;
+                Ok(written)
+            })
+        };
+        log::info!("⏺️ 会话录音: {}", path.display());
+
+        Ok(Self {
+            tap: RecorderTap {
+                ring,
+                dropped: Arc::default(),
+            },
+            path,
+            sample_rate,
+            stopping,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn tap(&self) -> RecorderTap {
+        self.tap.clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 停止写入线程，写完缓冲区中的剩余样本并关闭文件
+    pub fn finish(mut self) -> anyhow::Result<Recording> {
+        self.stopping.store(true, Ordering::SeqCst);
+        let written = self
+            .worker
+            .take()
+            .ok_or_else(|| anyhow!("录音已结束"))?
+            .join()
+            .map_err(|_| anyhow!("录音线程异常退出"))??;
+        Ok(Recording {
+            path: self.path.clone(),
+            duration_ms: written * 1000 / self.sample_rate.max(1) as u64,
+            dropped_samples: self.tap.dropped.load(Ordering::Relaxed),
+        })
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// 录音文件名：会话 ID 中不能用于文件名的字符替换为下划线
+fn file_name(session_id: &str, timestamp: &str, format: RecordingFormat) -> String {
+    let session: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_{}.{}", session, timestamp, format.extension())
+}
+
+enum Sink {
+    Wav(hound::WavWriter<BufWriter<std::fs::File>>),
+    Encoder { child: Child, stdin: ChildStdin },
+}
+
+impl Sink {
+    fn wav(path: &Path, sample_rate: u32) -> anyhow::Result<Self> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let writer = utils::file::create_wav_writer(&path.to_string_lossy(), spec)
+            .with_context(|| format!("无法创建录音文件 {}", path.display()))?;
+        Ok(Self::Wav(writer))
+    }
+
+    fn encoder(
+        mut ffmpeg: Command,
+        path: &Path,
+        sample_rate: u32,
+        format: RecordingFormat,
+    ) -> anyhow::Result<Self> {
+        let codec: &[&str] = match format {
+            RecordingFormat::Opus => &["-c:a", "libopus", "-b:a", "32k"],
+            _ => &["-c:a", "flac"],
+        };
+        let mut child = ffmpeg
+            .args(["-y", "-v", "error", "-f", "f32le"])
+            .args(["-ar", &sample_rate.to_string(), "-ac", "1", "-i", "-"])
+            .args(codec)
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("无法启动 ffmpeg 录音编码")?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("无法写入 ffmpeg 输入"))?;
+        Ok(Self::Encoder { child, stdin })
+    }
+
+    fn write(&mut self, samples: &[f32]) -> anyhow::Result<()> {
+        match self {
+            Self::Wav(writer) => {
+                for &sample in samples {
+                    writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+                }
+            }
+            Self::Encoder { stdin, .. } => {
+                let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+                stdin.write_all(&bytes).context("写入 ffmpeg 失败")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> anyhow::Result<()> {
+        match self {
+            Self::Wav(writer) => utils::file::save_wav_writer(writer)?,
+            Self::Encoder { mut child, stdin } => {
+                // 关闭输入后 ffmpeg 写完文件尾并退出
+                drop(stdin);
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(anyhow!("ffmpeg 录音编码失败: {}", status));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_wav_from_tap() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = RecordingConfig {
+            directory: dir.path().join("meetings").to_string_lossy().to_string(),
+            format: RecordingFormat::Wav,
+        };
+        let recorder = SessionRecorder::start(&config, "mic/1", 16000, None).unwrap();
+        let tap = recorder.tap();
+        for _ in 0..10 {
+            tap.push(&[0.5; 1600]);
+        }
+        let recording = recorder.finish().unwrap();
+
+        assert_eq!(recording.duration_ms, 1000);
+        assert_eq!(recording.dropped_samples, 0);
+        let name = recording.path.file_name().unwrap().to_string_lossy();
+        assert!(name.starts_with("mic_1_") && name.ends_with(".wav"));
+        let reader = hound::WavReader::open(&recording.path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(reader.len(), 16000);
+    }
+}
@@ -0,0 +1,121 @@
+// 无锁环形缓冲区：音频回调写入、后台线程读取，双方都不会阻塞，缓冲区在创建时一次性分配
+// 同一时刻只允许一个写入者和一个读取者；并发的第二个写入者（如切换设备时新旧流短暂重叠）直接放弃写入
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+pub struct SampleRing {
+    buf: Box<[UnsafeCell<f32>]>,
+    /// 读位置（单调递增，取模后为下标）
+    head: AtomicUsize,
+    /// 写位置
+    tail: AtomicUsize,
+    writing: AtomicBool,
+    reading: AtomicBool,
+}
+
+// 写入者只修改 [tail, head + capacity) 区间，读取者只读取 [head, tail) 区间，两者不重叠
+unsafe impl Sync for SampleRing {}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buf: (0..capacity.max(1)).map(|_| UnsafeCell::new(0.0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            writing: AtomicBool::new(false),
+            reading: AtomicBool::new(false),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// 写入样本，空间不足时只写入能放下的部分，返回实际写入数
+    pub fn push(&self, samples: &[f32]) -> usize {
+        self.push_from(samples.iter().copied())
+    }
+
+    /// 边转换边写入（如把设备的整数样本转为 f32），避免中间缓冲区
+    pub fn push_from(&self, samples: impl ExactSizeIterator<Item = f32>) -> usize {
+        if self.writing.swap(true, Ordering::Acquire) {
+            return 0;
+        }
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        let free = self.capacity() - tail.wrapping_sub(head);
+        let n = samples.len().min(free);
+        for (i, sample) in samples.take(n).enumerate() {
+            let index = tail.wrapping_add(i) % self.capacity();
+            // SAFETY: 该位置在读取者可见范围之外，且写入者唯一（writing 标志）
+            unsafe { *self.buf[index].get() = sample };
+        }
+        self.tail.store(tail.wrapping_add(n), Ordering::Release);
+        self.writing.store(false, Ordering::Release);
+        n
+    }
+
+    /// 读取最多 out.len() 个样本，返回实际读取数
+    pub fn pop(&self, out: &mut [f32]) -> usize {
+        if self.reading.swap(true, Ordering::Acquire) {
+            return 0;
+        }
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        let n = out.len().min(tail.wrapping_sub(head));
+        for (i, sample) in out[..n].iter_mut().enumerate() {
+            let index = head.wrapping_add(i) % self.capacity();
+            // SAFETY: 该位置已由写入者发布（tail 的 Release），读取者唯一（reading 标志）
+            *sample = unsafe { *self.buf[index].get() };
+        }
+        self.head.store(head.wrapping_add(n), Ordering::Release);
+        self.reading.store(false, Ordering::Release);
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_push_pop_wraps_and_reports_overflow() {
+        let ring = SampleRing::new(4);
+        assert_eq!(ring.push(&[1.0, 2.0, 3.0]), 3);
+        let mut out = [0.0; 2];
+        assert_eq!(ring.pop(&mut out), 2);
+        assert_eq!(out, [1.0, 2.0]);
+        // 跨越末尾写入，超出容量的部分被丢弃
+        assert_eq!(ring.push(&[4.0, 5.0, 6.0, 7.0]), 3);
+        let mut out = [0.0; 8];
+        assert_eq!(ring.pop(&mut out), 4);
+        assert_eq!(&out[..4], &[3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(ring.pop(&mut out), 0);
+    }
+
+    #[test]
+    fn test_concurrent_producer_consumer_keeps_order() {
+        let ring = Arc::new(SampleRing::new(64));
+        let producer = {
+            let ring = ring.clone();
+            thread::spawn(move || {
+                let mut next = 0u32;
+                while next < 10_000 {
+                    let chunk: Vec<f32> =
+                        (next..(next + 7).min(10_000)).map(|v| v as f32).collect();
+                    next += ring.push(&chunk) as u32;
+                }
+            })
+        };
+        let mut received = Vec::new();
+        let mut out = [0.0; 16];
+        while received.len() < 10_000 {
+            let n = ring.pop(&mut out);
+            received.extend_from_slice(&out[..n]);
+        }
+        producer.join().unwrap();
+        assert!(received.iter().enumerate().all(|(i, &v)| v == i as f32));
+    }
+}
@@ -0,0 +1,414 @@
+// 语音活动检测（VAD）：位于重采样/增益之后、发送到 ASR 之前
+// 能量检测 + 起始确认（attack）+ 拖尾保持（hangover），静音期间缓存少量预录音频，
+// 语音开始时一并发送，避免句首被截断
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 前端监听的 VAD 状态事件名称
+pub const VAD_STATE_EVENT: &str = "vad-state";
+
+/// VAD 工作模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VadMode {
+    /// 关闭：所有音频直接发送
+    #[default]
+    Off,
+    /// 仅检测并推送 vad-state 事件，音频照常发送
+    Monitor,
+    /// 静音门限：只发送语音段（含预录和拖尾），被丢弃的静音仍计入识别结果的时间轴
+    Gate,
+    /// 静音门限 + 静音持续 pause_after_ms 后结束识别任务，检测到语音时开启新任务
+    PauseAsr,
+}
+
+/// VAD 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VadConfig {
+    #[serde(default)]
+    pub mode: VadMode,
+    /// 语音能量阈值（dBFS）
+    #[serde(default = "default_threshold_db")]
+    pub threshold_db: f32,
+    /// 语音需高出噪声基底的幅度（dB），用于适应底噪较高的环境
+    #[serde(default = "default_noise_margin_db")]
+    pub noise_margin_db: f32,
+    /// 连续检测到语音达到该时长才判定语音开始（毫秒）
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: u64,
+    /// 能量降低后继续保持语音状态的时长（毫秒）
+    #[serde(default = "default_hangover_ms")]
+    pub hangover_ms: u64,
+    /// 语音开始前补发的音频时长（毫秒），不小于 attack_ms
+    #[serde(default = "default_pre_roll_ms")]
+    pub pre_roll_ms: u64,
+    /// PauseAsr 模式下静音持续多久后结束识别任务（毫秒）
+    #[serde(default = "default_pause_after_ms")]
+    pub pause_after_ms: u64,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            mode: VadMode::default(),
+            threshold_db: default_threshold_db(),
+            noise_margin_db: default_noise_margin_db(),
+            attack_ms: default_attack_ms(),
+            hangover_ms: default_hangover_ms(),
+            pre_roll_ms: default_pre_roll_ms(),
+            pause_after_ms: default_pause_after_ms(),
+        }
+    }
+}
+
+fn default_threshold_db() -> f32 {
+    -45.0
+}
+
+fn default_noise_margin_db() -> f32 {
+    9.0
+}
+
+fn default_attack_ms() -> u64 {
+    100
+}
+
+fn default_hangover_ms() -> u64 {
+    600
+}
+
+fn default_pre_roll_ms() -> u64 {
+    300
+}
+
+fn default_pause_after_ms() -> u64 {
+    5000
+}
+
+/// 语音/静音状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VadState {
+    Silence,
+    Speech,
+}
+
+/// VAD 状态变化
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VadEvent {
+    /// 语音开始；skipped_samples 为上次语音结束后被丢弃的静音样本数
+    SpeechStart { level_db: f32, skipped_samples: u64 },
+    /// 语音结束（拖尾保持结束）
+    SpeechEnd { level_db: f32 },
+    /// 静音超过 pause_after_ms（仅 PauseAsr 模式）
+    PauseAsr { level_db: f32 },
+}
+
+/// 发送给前端的 VAD 状态事件
+#[derive(Debug, Clone, Serialize)]
+pub struct VadStateEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub state: VadState,
+    /// 识别任务是否因静音暂停
+    pub asr_paused: bool,
+    pub level_db: f32,
+}
+
+/// 单帧语音判定（默认能量检测，可替换为 WebRTC 风格或小模型检测器）
+pub trait VoiceDetector: Send {
+    /// 返回 (是否为语音, 帧能量 dBFS)
+    fn detect(&mut self, frame: &[f32]) -> (bool, f32);
+}
+
+/// 能量检测器：RMS 高于绝对阈值且高于自适应噪声基底
+pub struct EnergyDetector {
+    threshold_db: f32,
+    noise_margin_db: f32,
+    noise_floor_db: f32,
+}
+
+impl EnergyDetector {
+    pub fn new(threshold_db: f32, noise_margin_db: f32) -> Self {
+        Self {
+            threshold_db,
+            noise_margin_db,
+            noise_floor_db: threshold_db - noise_margin_db,
+        }
+    }
+}
+
+impl VoiceDetector for EnergyDetector {
+    fn detect(&mut self, frame: &[f32]) -> (bool, f32) {
+        let level_db = rms_db(frame);
+        let speech =
+            level_db > self.threshold_db && level_db > self.noise_floor_db + self.noise_margin_db;
+        // 噪声基底：下降快、上升慢，语音帧不参与更新
+        if level_db < self.noise_floor_db {
+            self.noise_floor_db = 0.7 * self.noise_floor_db + 0.3 * level_db;
+        } else if !speech {
+            self.noise_floor_db = 0.98 * self.noise_floor_db + 0.02 * level_db;
+        }
+        (speech, level_db)
+    }
+}
+
+/// 帧 RMS（dBFS），静音帧下限 -100 dB
+pub fn rms_db(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return -100.0;
+    }
+    let rms = (frame.iter().map(|&s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+    (20.0 * rms.max(1e-5).log10()).max(-100.0)
+}
+
+/// VAD 状态变化回调（在音频线程中调用，应尽快返回）
+pub type VadListener = Box<dyn FnMut(VadEvent) + Send>;
+
+/// VAD 处理阶段
+pub struct Vad {
+    config: VadConfig,
+    detector: Box<dyn VoiceDetector>,
+    sample_rate: u32,
+    state: VadState,
+    /// 静音状态下连续语音帧时长（attack 判定）
+    speech_run_ms: u64,
+    /// 最近一次语音帧之后的静音时长
+    silence_run_ms: u64,
+    /// 静音期间的预录缓存
+    pre_roll: VecDeque<Vec<f32>>,
+    pre_roll_samples: usize,
+    /// 上次语音结束后被丢弃的样本数
+    skipped_samples: u64,
+    asr_paused: bool,
+    listener: Option<VadListener>,
+}
+
+impl Vad {
+    pub fn new(config: VadConfig, sample_rate: u32) -> Self {
+        let detector = Box::new(EnergyDetector::new(
+            config.threshold_db,
+            config.noise_margin_db,
+        ));
+        Self::with_detector(config, sample_rate, detector)
+    }
+
+    pub fn with_detector(
+        mut config: VadConfig,
+        sample_rate: u32,
+        detector: Box<dyn VoiceDetector>,
+    ) -> Self {
+        config.pre_roll_ms = config.pre_roll_ms.max(config.attack_ms);
+        Self {
+            config,
+            detector,
+            sample_rate,
+            state: VadState::Silence,
+            speech_run_ms: 0,
+            silence_run_ms: 0,
+            pre_roll: VecDeque::new(),
+            pre_roll_samples: 0,
+            skipped_samples: 0,
+            asr_paused: false,
+            listener: None,
+        }
+    }
+
+    /// 设置状态变化回调
+    pub fn with_listener(mut self, listener: VadListener) -> Self {
+        self.listener = Some(listener);
+        self
+    }
+
+    pub fn state(&self) -> VadState {
+        self.state
+    }
+
+    /// 处理一帧音频，返回应发送给 ASR 的帧
+    pub fn process(&mut self, frame: Vec<f32>) -> Vec<Vec<f32>> {
+        if self.config.mode == VadMode::Off {
+            return vec![frame];
+        }
+
+        let frame_ms = frame.len() as u64 * 1000 / self.sample_rate as u64;
+        let (speech, level_db) = self.detector.detect(&frame);
+        let gating = matches!(self.config.mode, VadMode::Gate | VadMode::PauseAsr);
+
+        match self.state {
+            VadState::Silence => {
+                self.speech_run_ms = if speech {
+                    self.speech_run_ms + frame_ms
+                } else {
+                    0
+                };
+                if self.speech_run_ms >= self.config.attack_ms {
+                    self.state = VadState::Speech;
+                    self.silence_run_ms = 0;
+                    self.asr_paused = false;
+                    let skipped_samples = std::mem::take(&mut self.skipped_samples);
+                    self.notify(VadEvent::SpeechStart {
+                        level_db,
+                        skipped_samples,
+                    });
+                    if !gating {
+                        return vec![frame];
+                    }
+                    // 补发预录音频（包含 attack 期间的帧）
+                    let mut frames: Vec<Vec<f32>> = self.pre_roll.drain(..).collect();
+                    self.pre_roll_samples = 0;
+                    frames.push(frame);
+                    return frames;
+                }
+
+                self.silence_run_ms += frame_ms;
+                if self.config.mode == VadMode::PauseAsr
+                    && !self.asr_paused
+                    && self.silence_run_ms >= self.config.pause_after_ms
+                {
+                    self.asr_paused = true;
+                    self.notify(VadEvent::PauseAsr { level_db });
+                }
+                if !gating {
+                    return vec![frame];
+                }
+                self.push_pre_roll(frame);
+                Vec::new()
+            }
+            VadState::Speech => {
+                if speech {
+                    self.silence_run_ms = 0;
+                } else {
+                    self.silence_run_ms += frame_ms;
+                    if self.silence_run_ms >= self.config.hangover_ms {
+                        self.state = VadState::Silence;
+                        self.speech_run_ms = 0;
+                        self.notify(VadEvent::SpeechEnd { level_db });
+                    }
+                }
+                vec![frame]
+            }
+        }
+    }
+
+    fn push_pre_roll(&mut self, frame: Vec<f32>) {
+        let max_samples = (self.config.pre_roll_ms * self.sample_rate as u64 / 1000) as usize;
+        self.pre_roll_samples += frame.len();
+        self.pre_roll.push_back(frame);
+        while self.pre_roll_samples > max_samples {
+            match self.pre_roll.pop_front() {
+                Some(old) => {
+                    self.pre_roll_samples -= old.len();
+                    self.skipped_samples += old.len() as u64;
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn notify(&mut self, event: VadEvent) {
+        log::debug!("VAD: {:?}", event);
+        if let Some(listener) = self.listener.as_mut() {
+            listener(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const RATE: u32 = 16000;
+    /// 50ms 帧
+    const FRAME: usize = 800;
+
+    fn tone(amplitude: f32) -> Vec<f32> {
+        (0..FRAME)
+            .map(|i| amplitude * (i as f32 * 0.1).sin())
+            .collect()
+    }
+
+    fn config(mode: VadMode) -> VadConfig {
+        VadConfig {
+            mode,
+            attack_ms: 100,
+            hangover_ms: 300,
+            pre_roll_ms: 200,
+            pause_after_ms: 1000,
+            ..VadConfig::default()
+        }
+    }
+
+    fn recording_vad(mode: VadMode) -> (Vad, Arc<Mutex<Vec<VadEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let vad = Vad::new(config(mode), RATE)
+            .with_listener(Box::new(move |e| sink.lock().unwrap().push(e)));
+        (vad, events)
+    }
+
+    #[test]
+    fn test_gate_drops_silence_and_keeps_pre_roll() {
+        let (mut vad, events) = recording_vad(VadMode::Gate);
+        for _ in 0..20 {
+            assert!(vad.process(tone(0.0)).is_empty());
+        }
+        // 第一帧语音尚在 attack 期内，不发送
+        assert!(vad.process(tone(0.3)).is_empty());
+        // attack 达到 100ms：补发预录（4 帧 = 200ms，含上一帧语音）和当前帧
+        let frames = vad.process(tone(0.3));
+        assert_eq!(frames.len(), 5);
+        assert_eq!(vad.state, VadState::Speech);
+
+        // hangover 期间仍然发送
+        for _ in 0..5 {
+            assert_eq!(vad.process(tone(0.0)).len(), 1);
+        }
+        assert_eq!(vad.state, VadState::Speech);
+        assert_eq!(vad.process(tone(0.0)).len(), 1);
+        assert_eq!(vad.state, VadState::Silence);
+        assert!(vad.process(tone(0.0)).is_empty());
+
+        let events = events.lock().unwrap();
+        assert!(matches!(
+            events[0],
+            VadEvent::SpeechStart { skipped_samples, .. } if skipped_samples == 17 * FRAME as u64
+        ));
+        assert!(matches!(events[1], VadEvent::SpeechEnd { .. }));
+    }
+
+    #[test]
+    fn test_pause_asr_after_long_silence() {
+        let (mut vad, events) = recording_vad(VadMode::PauseAsr);
+        for _ in 0..4 {
+            vad.process(tone(0.3));
+        }
+        for _ in 0..30 {
+            vad.process(tone(0.0));
+        }
+        assert!(vad.asr_paused);
+        vad.process(tone(0.3));
+        vad.process(tone(0.3));
+        assert!(!vad.asr_paused);
+
+        let events = events.lock().unwrap();
+        let pauses = events
+            .iter()
+            .filter(|e| matches!(e, VadEvent::PauseAsr { .. }))
+            .count();
+        assert_eq!(pauses, 1);
+        assert!(matches!(events.last(), Some(VadEvent::SpeechStart { .. })));
+    }
+
+    #[test]
+    fn test_monitor_forwards_everything() {
+        let (mut vad, events) = recording_vad(VadMode::Monitor);
+        for _ in 0..3 {
+            assert_eq!(vad.process(tone(0.0)).len(), 1);
+        }
+        for _ in 0..3 {
+            assert_eq!(vad.process(tone(0.3)).len(), 1);
+        }
+        assert_eq!(events.lock().unwrap().len(), 1);
+    }
+}
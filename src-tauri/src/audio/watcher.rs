@@ -0,0 +1,148 @@
+// 设备热插拔监视：采集线程定期轮询，检测音频流错误以及当前设备被移除（USB 耳机拔出、蓝牙断开等）
+use crate::audio::processor;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// 检查设备是否仍然存在的间隔
+const DEVICE_SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 当前设备失效后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceFallback {
+    /// 停止采集，识别会话发送完剩余音频后正常结束
+    #[default]
+    Stop,
+    /// 切换到系统默认输入设备
+    DefaultInput,
+    /// 切换到环回设备（按 find_loopback_device 的优先级）
+    Loopback,
+}
+
+/// 设备失效原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceLossReason {
+    /// 音频流报告设备不可用
+    StreamError,
+    /// 重新枚举时设备已不存在
+    DeviceRemoved,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceLoss {
+    pub reason: DeviceLossReason,
+    pub message: String,
+}
+
+/// 设备监视器
+pub struct DeviceWatcher {
+    /// 设备的稳定 ID（为空时不检查设备是否存在）
+    device_id: Option<String>,
+    /// 显示名称（日志和提示用）
+    device_name: String,
+    errors_tx: mpsc::Sender<cpal::StreamError>,
+    errors: mpsc::Receiver<cpal::StreamError>,
+    next_scan: Instant,
+}
+
+impl Default for DeviceWatcher {
+    fn default() -> Self {
+        let (errors_tx, errors) = mpsc::channel();
+        Self {
+            device_id: None,
+            device_name: String::new(),
+            errors_tx,
+            errors,
+            next_scan: Instant::now() + DEVICE_SCAN_INTERVAL,
+        }
+    }
+}
+
+impl DeviceWatcher {
+    /// 传给音频流错误回调的发送端
+    pub fn error_sender(&self) -> mpsc::Sender<cpal::StreamError> {
+        self.errors_tx.clone()
+    }
+
+    /// 开始监视（新）设备：按稳定 ID 检测移除，同名设备互不影响
+    pub fn watch(&mut self, device_id: String, device_name: String) {
+        self.device_id = Some(device_id);
+        self.device_name = device_name;
+        self.next_scan = Instant::now() + DEVICE_SCAN_INTERVAL;
+    }
+
+    /// 只检查音频流错误，不检查设备是否存在（文件音频源、应用播放流）
+    pub fn watch_stream(&mut self, name: String) {
+        self.device_id = None;
+        self.device_name = name;
+    }
+
+    /// 丢弃已关闭的旧音频流遗留的错误
+    pub fn clear_errors(&mut self) {
+        while self.errors.try_recv().is_ok() {}
+    }
+
+    /// 检查流错误，到达间隔时检查设备是否仍然存在；设备失效时返回原因
+    pub fn poll(&mut self) -> Option<DeviceLoss> {
+        while let Ok(err) = self.errors.try_recv() {
+            match err {
+                cpal::StreamError::DeviceNotAvailable => {
+                    return Some(DeviceLoss {
+                        reason: DeviceLossReason::StreamError,
+                        message: format!("设备 {} 不可用", self.device_name),
+                    });
+                }
+                // 其他后端错误（如缓冲区溢出）通常可以自行恢复，设备真正消失时由枚举检测
+                err => log::warn!("❌ 音频流错误 [{}]：{}", self.device_name, err),
+            }
+        }
+
+        if self.device_id.is_none() || Instant::now() < self.next_scan {
+            return None;
+        }
+        self.next_scan = Instant::now() + DEVICE_SCAN_INTERVAL;
+        let exists = self.device_id.as_deref().and_then(processor::device_exists);
+        self.check_device(exists)
+    }
+
+    /// exists 为 None 表示无法枚举设备，此时无法判断，等待下次检查
+    fn check_device(&self, exists: Option<bool>) -> Option<DeviceLoss> {
+        if exists != Some(false) {
+            return None;
+        }
+        Some(DeviceLoss {
+            reason: DeviceLossReason::DeviceRemoved,
+            message: format!("设备 {} 已被移除", self.device_name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_removal_and_stream_errors() {
+        let mut watcher = DeviceWatcher::default();
+        watcher.watch("ALSA:USB Headset#2".to_string(), "USB Headset".to_string());
+
+        assert!(watcher.check_device(Some(true)).is_none());
+        assert!(watcher.check_device(None).is_none());
+        let loss = watcher.check_device(Some(false)).unwrap();
+        assert_eq!(loss.reason, DeviceLossReason::DeviceRemoved);
+        assert_eq!(loss.message, "设备 USB Headset 已被移除");
+
+        let errors = watcher.error_sender();
+        errors.send(cpal::StreamError::DeviceNotAvailable).unwrap();
+        assert_eq!(
+            watcher.poll().map(|l| l.reason),
+            Some(DeviceLossReason::StreamError)
+        );
+
+        errors.send(cpal::StreamError::DeviceNotAvailable).unwrap();
+        watcher.clear_errors();
+        assert!(watcher.poll().is_none());
+    }
+}
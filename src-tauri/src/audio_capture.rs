@@ -1,24 +1,289 @@
 use anyhow::{anyhow, Context};
-use cpal::traits::{DeviceTrait, StreamTrait};
-use cpal::{Device, InputCallbackInfo, StreamConfig};
-use hound::WavSpec;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, InputCallbackInfo, StreamConfig, SupportedStreamConfig};
 use log::{info, warn};
-use rubato::{SincFixedIn, SincInterpolationParameters, WindowFunction};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
-use tauri::async_runtime;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::{self, JoinHandle};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use uuid::Uuid;
 
 // 导入 crate 中的其他模块
+use crate::app_state;
 use crate::asr::config::AsrProviderConfig;
+use crate::asr::error::AsrError;
+use crate::asr::events::{AsrResultEvent, AudioSource, ResultOrigin};
 use crate::asr::provider::{CloudAsrProvider, LocalAsrProvider};
+use crate::asr::session::{AsrSession, SessionPauser, SessionStatus};
+use crate::asr::subtitle::srt;
 use crate::asr::AsrProvider;
 use crate::audio;
-use crate::utils;
+use crate::audio::chain::{AudioChain, ChainResources};
+use crate::audio::downmix::DownmixMode;
+use crate::audio::dsp_worker::{self, CallbackInput, DspWorker};
+use crate::audio::file_source::{self, FileDecoder, FilePlayback, FileReader, FileSourceConfig};
+use crate::audio::level::{AudioLevel, AudioLevelEvent, LevelMeter, AUDIO_LEVEL_EVENT};
+use crate::audio::recorder::{RecorderTap, Recording, SessionRecorder};
+use crate::audio::vad::{
+    Vad, VadConfig, VadEvent, VadMode, VadState, VadStateEvent, VAD_STATE_EVENT,
+};
+use crate::audio::watcher::{DeviceFallback, DeviceLoss, DeviceLossReason, DeviceWatcher};
+use crate::audio::{CaptureConfig, InputFormat, ResamplerQuality};
 
-// 全局录音状态标志（线程安全，编译时初始化）
-static IS_RECORDING: AtomicBool = AtomicBool::new(false);
+/// 旧版单会话命令（start_audio_capture）使用的会话 ID
+const DEFAULT_SESSION_ID: &str = "default";
+
+/// 设备预览推送 audio-level 事件时使用的会话 ID
+const PROBE_SESSION_ID: &str = "probe";
+/// 设备预览时长范围（秒）
+const MIN_PROBE_SECONDS: f32 = 0.5;
+const MAX_PROBE_SECONDS: f32 = 10.0;
+
+/// 设备失效后已切换到备用设备（识别会话继续）
+const AUDIO_DEVICE_CHANGED_EVENT: &str = "audio-device-changed";
+/// 设备失效且无法切换，采集已停止
+const AUDIO_CAPTURE_ERROR_EVENT: &str = "audio-capture-error";
+/// 会话结束，录音和识别文本已保存
+const RECORDING_SAVED_EVENT: &str = "recording-saved";
+
+/// 音频流实际使用的参数（校验并结合设备能力后的结果，回传给前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamParams {
+    pub input_sample_rate: u32,
+    pub input_channels: u16,
+    pub sample_format: String,
+    pub sample_rate: u32,
+    /// 实际帧时长（按输入采样率取整后的帧大小换算）
+    pub frame_ms: f32,
+    pub frame_size: usize,
+    pub gain_db: f32,
+    /// 是否启用自动增益控制（gain_db 为初始增益）
+    pub agc: bool,
+    pub channels: Vec<usize>,
+    pub downmix: DownmixMode,
+    pub resampler: ResamplerQuality,
+    /// 高通截止频率（Hz，未启用时为 None）
+    pub highpass_hz: Option<f32>,
+    /// 降噪强度（未启用时为 None）
+    pub denoise: Option<f32>,
+    pub queue_frames: usize,
+    pub vad: VadMode,
+}
+
+impl StreamParams {
+    fn new(
+        audio_config: &audio::AudioConfig,
+        capture: &CaptureConfig,
+        input_config: &SupportedStreamConfig,
+    ) -> Self {
+        Self {
+            input_sample_rate: audio_config.sample_rate_in,
+            input_channels: audio_config.channels,
+            sample_format: format!("{:?}", input_config.sample_format()),
+            sample_rate: audio_config.sample_rate_out,
+            frame_ms: audio_config.frame_size as f32 * 1000.0 / audio_config.sample_rate_in as f32,
+            frame_size: audio_config.frame_size,
+            gain_db: audio_config.gain_db,
+            agc: audio_config.agc.enabled,
+            channels: audio_config.selected_channels.clone(),
+            downmix: audio_config.downmix.clone(),
+            resampler: audio_config.resampler,
+            highpass_hz: audio_config
+                .highpass
+                .enabled
+                .then_some(audio_config.highpass.cutoff_hz),
+            denoise: audio_config
+                .denoise
+                .enabled
+                .then_some(audio_config.denoise.strength),
+            queue_frames: capture.queue_frames,
+            vad: audio_config.vad.mode,
+        }
+    }
+
+    /// 用于状态栏显示的简要描述
+    fn summary(&self) -> String {
+        format!(
+            "{}Hz {}声道 → {}Hz，帧 {:.0}ms，增益 {:+.1}dB{}",
+            self.input_sample_rate,
+            self.channels.len(),
+            self.sample_rate,
+            self.frame_ms,
+            self.gain_db,
+            if self.agc { "（自动）" } else { "" }
+        )
+    }
+}
+
+/// 设备预览结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeReport {
+    pub device_name: String,
+    pub params: StreamParams,
+    /// 整段预览的电平汇总（未收到任何音频时为空）
+    pub level: Option<AudioLevel>,
+}
+
+/// 切换设备事件
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceChangedEvent {
+    pub session_id: String,
+    pub previous_device: String,
+    pub device_name: String,
+    pub reason: DeviceLossReason,
+    pub params: StreamParams,
+}
+
+/// 采集错误事件（采集已停止，识别会话处理完已发送的音频后结束）
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioCaptureErrorEvent {
+    pub session_id: String,
+    pub device_name: String,
+    pub reason: DeviceLossReason,
+    pub message: String,
+}
+
+/// 录音保存事件：录音文件与同名的 SRT 识别文本
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSavedEvent {
+    pub session_id: String,
+    pub audio_path: String,
+    /// 识别文本写入失败时为空
+    pub transcript_path: Option<String>,
+    pub duration_ms: u64,
+    pub dropped_samples: u64,
+}
+
+/// 会话当前使用的设备（切换设备时由采集线程更新）
+#[derive(Debug, Clone)]
+struct ActiveDevice {
+    /// 稳定 ID（设备监视用），文件音频源为空
+    device_id: Option<String>,
+    device_name: String,
+    params: StreamParams,
+    /// 采集的是单个应用的播放流
+    application: bool,
+}
+
+impl ActiveDevice {
+    /// 按设备 ID 监视移除；文件和应用播放流只检查流错误
+    /// （应用暂停时播放流会暂时消失，由 parec 采集端等待其重新出现）
+    fn watch(&self, watcher: &mut DeviceWatcher) {
+        match &self.device_id {
+            Some(id) if !self.application => watcher.watch(id.clone(), self.device_name.clone()),
+            _ => watcher.watch_stream(self.device_name.clone()),
+        }
+    }
+}
+
+/// 捕获会话信息（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureSessionInfo {
+    pub session_id: String,
+    pub source: AudioSource,
+    pub device_name: String,
+    pub status: SessionStatus,
+    pub params: StreamParams,
+    /// 会话录音文件（未启用录音时为空）
+    pub recording_path: Option<String>,
+}
+
+/// 正在进行的捕获会话：采集线程 + 识别会话
+struct CaptureSession {
+    source: AudioSource,
+    active: Arc<std::sync::Mutex<ActiveDevice>>,
+    recording_path: Option<String>,
+    /// 采集线程的停止标志
+    stop_flag: Arc<AtomicBool>,
+    /// 采集线程句柄（线程内持有输入流和 RecordingState，结束时返回保存的录音）
+    worker: JoinHandle<Option<Recording>>,
+    session: AsrSession,
+}
+
+impl CaptureSession {
+    fn info(&self, session_id: &str) -> CaptureSessionInfo {
+        let active = lock_active(&self.active);
+        CaptureSessionInfo {
+            session_id: session_id.to_string(),
+            source: self.source,
+            device_name: active.device_name,
+            status: self.session.status().borrow().clone(),
+            params: active.params,
+            recording_path: self.recording_path.clone(),
+        }
+    }
+
+    /// 停止采集，再等待识别会话发送完剩余音频并收到全部最终结果；启用录音时把最终结果写到录音旁
+    async fn shutdown(self, session_id: &str) -> Result<Vec<AsrResultEvent>, AsrError> {
+        // 采集线程退出时释放音频发送端，会话读空 channel 后发送 finish-task
+        self.stop_flag.store(true, Ordering::SeqCst);
+        let recording = self.worker.await.unwrap_or_else(|e| {
+            warn!("等待采集线程退出失败: {}", e);
+            None
+        });
+        self.session.stop();
+        let results = self.session.join().await;
+        if let Some(recording) = recording {
+            link_transcript(
+                session_id,
+                recording,
+                results.as_deref().unwrap_or_default(),
+            );
+        }
+        results
+    }
+}
+
+/// 把识别结果写成与录音同名的 SRT，并推送 recording-saved 事件
+fn link_transcript(session_id: &str, recording: Recording, results: &[AsrResultEvent]) {
+    let transcript = recording.path.with_extension("srt");
+    let transcript_path = match srt::write_srt(results, &transcript) {
+        Ok(()) => Some(transcript.to_string_lossy().to_string()),
+        Err(e) => {
+            warn!("❌ 写入识别文本 {} 失败: {}", transcript.display(), e);
+            None
+        }
+    };
+    info!(
+        "💾 [{}] 录音已保存: {}（{:.1}秒）",
+        session_id,
+        recording.path.display(),
+        recording.duration_ms as f32 / 1000.0
+    );
+    let payload = RecordingSavedEvent {
+        session_id: session_id.to_string(),
+        audio_path: recording.path.to_string_lossy().to_string(),
+        transcript_path,
+        duration_ms: recording.duration_ms,
+        dropped_samples: recording.dropped_samples,
+    };
+    if let Err(err) = app_state::emit_event(RECORDING_SAVED_EVENT, &payload) {
+        warn!("发送录音保存事件失败: {}", err);
+    }
+}
+
+/// 采集线程启动成功后交给命令端的内容
+struct StartedCapture {
+    session: AsrSession,
+    source: AudioSource,
+    active: Arc<std::sync::Mutex<ActiveDevice>>,
+    recording_path: Option<String>,
+}
+
+/// 已打开的采集流（cpal::Stream 不是 Send，只能留在采集线程中）
+struct OpenedCapture {
+    stream: InputStream,
+    started: StartedCapture,
+    setup: StreamSetup,
+    recorder: Option<SessionRecorder>,
+}
+
+// 捕获会话注册表（按会话 ID 索引，每个会话独立的设备流、RecordingState 和 ASR Provider）
+static SESSIONS: Mutex<BTreeMap<String, CaptureSession>> = Mutex::const_new(BTreeMap::new());
 
 /// 获取可用的音频输入设备列表
 #[tauri::command]
@@ -26,151 +291,422 @@ pub fn get_audio_devices() -> Result<Vec<audio::AudioDevice>, String> {
     Ok(audio::get_audio_devices())
 }
 
-/// 启动音频捕获和实时语音识别
-/// 前端可以通过 invoke('start_audio_capture', {config: {...}, deviceName: "..."}) 调用此函数
+/// 设备预览：不启动识别，短时采集并推送 audio-level 事件（session_id 为 "probe"），返回整段电平汇总
+/// 前端可以通过 invoke('probe_audio_device', {deviceId: "...", seconds: 3}) 调用
 #[tauri::command]
-pub async fn start_audio_capture(
+pub async fn probe_audio_device(
+    device_name: Option<String>,
+    device_id: Option<String>,
+    pid: Option<u32>,
+    seconds: f32,
+) -> Result<ProbeReport, String> {
+    let seconds = seconds.clamp(MIN_PROBE_SECONDS, MAX_PROBE_SECONDS);
+    let device = DeviceSelector {
+        id: device_id,
+        name: device_name,
+        pid,
+        file: None,
+    };
+    async_runtime::spawn_blocking(move || run_probe(device, seconds))
+        .await
+        .map_err(|e| format!("设备预览线程异常退出: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// 列出所有捕获会话（含已自行结束但尚未停止的会话）
+#[tauri::command]
+pub async fn list_capture_sessions() -> Vec<CaptureSessionInfo> {
+    SESSIONS
+        .lock()
+        .await
+        .iter()
+        .map(|(id, capture)| capture.info(id))
+        .collect()
+}
+
+/// 启动一个捕获会话，可与其他会话并发（如麦克风与系统音频同时识别）
+/// 前端可以通过 invoke('start_capture_session', {config, deviceId, sessionId, source, capture}) 调用
+/// 设备优先按 device_id（get_audio_devices 返回的 id）查找，其次按 device_name；都省略时使用环回设备
+/// 指定 pid 时只采集该进程的音频输出（目前仅 Linux 支持，也可以传入 application 类型设备的 id）
+/// session_id 省略时自动生成；source 省略时按设备类型推断（环回设备为 system）；capture 省略时使用默认采集参数
+#[tauri::command]
+pub async fn start_capture_session(
     config: AsrProviderConfig,
     device_name: Option<String>,
-) -> Result<String, String> {
-    // 使用 compare_exchange 原子化地"检查并设置"
-    // 如果当前是 false（未在录音），则设置为 true（开始录音）
-    match IS_RECORDING.compare_exchange(
-        false,            // 期望值：当前应该是 false
-        true,             // 新值：设置为 true
-        Ordering::SeqCst, // 成功时的内存顺序
-        Ordering::SeqCst, // 失败时的内存顺序
-    ) {
-        Ok(_) => {
-            // 成功：之前是 false，现在已设置为 true
-            info!("开始音频捕获，配置: {:?}, 设备: {:?}", config, device_name);
-
-            // 在后台任务中执行音频捕获
-            async_runtime::spawn_blocking(move || {
-                let result = async_runtime::block_on(run_audio_capture(config, device_name));
-                match result {
-                    Ok(_) => {
-                        info!("音频捕获正常结束");
-                        IS_RECORDING.store(false, Ordering::SeqCst);
-                    }
-                    Err(e) => {
-                        warn!("音频捕获错误: {}", e);
-                        IS_RECORDING.store(false, Ordering::SeqCst);
-                    }
+    device_id: Option<String>,
+    pid: Option<u32>,
+    session_id: Option<String>,
+    source: Option<AudioSource>,
+    capture: Option<CaptureConfig>,
+) -> Result<CaptureSessionInfo, String> {
+    let device = DeviceSelector {
+        id: device_id,
+        name: device_name,
+        pid,
+        file: None,
+    };
+    start_session(config, device, session_id, source, capture).await
+}
+
+/// 以音视频文件作为音频源启动捕获会话：经过与设备采集相同的处理管线，按 file.speed 的节奏送入识别
+/// 前端可以通过 invoke('start_file_session', {config, file: {path, speed}, sessionId, capture}) 调用
+/// WAV 直接解码，其他格式经 ffmpeg sidecar 解码（capture.input 可指定解码输出格式）；文件读完后会话自行结束
+#[tauri::command]
+pub async fn start_file_session(
+    config: AsrProviderConfig,
+    file: FileSourceConfig,
+    session_id: Option<String>,
+    capture: Option<CaptureConfig>,
+) -> Result<CaptureSessionInfo, String> {
+    let device = DeviceSelector {
+        file: Some(file),
+        ..Default::default()
+    };
+    start_session(config, device, session_id, Some(AudioSource::File), capture).await
+}
+
+async fn start_session(
+    config: AsrProviderConfig,
+    device: DeviceSelector,
+    session_id: Option<String>,
+    source: Option<AudioSource>,
+    capture: Option<CaptureConfig>,
+) -> Result<CaptureSessionInfo, String> {
+    let session_id =
+        session_id.unwrap_or_else(|| Uuid::new_v4().simple().to_string()[..8].to_string());
+    reap_finished(&session_id).await?;
+
+    let capture = capture.unwrap_or_default();
+    info!(
+        "开始音频捕获 [{}]，配置: {:?}, 设备: {:?}, 采集参数: {:?}",
+        session_id, config, device, capture
+    );
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let (ready_tx, ready_rx) = oneshot::channel::<Result<StartedCapture, String>>();
+
+    // 在后台线程中执行音频捕获
+    let worker = {
+        let stop_flag = stop_flag.clone();
+        let session_id = session_id.clone();
+        async_runtime::spawn_blocking(move || {
+            match run_audio_capture(
+                config,
+                device,
+                &session_id,
+                source,
+                capture,
+                stop_flag,
+                ready_tx,
+            ) {
+                Ok(recording) => {
+                    info!("音频捕获 [{}] 正常结束", session_id);
+                    recording
                 }
-            });
+                Err(e) => {
+                    warn!("音频捕获 [{}] 错误: {}", session_id, e);
+                    None
+                }
+            }
+        })
+    };
 
-            Ok("音频捕获已启动".to_string())
+    let started = match ready_rx.await {
+        Ok(Ok(started)) => started,
+        Ok(Err(e)) => {
+            let _ = worker.await;
+            return Err(e);
         }
         Err(_) => {
-            // 失败：已经是 true（已在运行）
-            Err("音频捕获已经在运行中".to_string())
+            let _ = worker.await;
+            return Err("音频捕获线程异常退出".to_string());
         }
+    };
+
+    let capture = CaptureSession {
+        source: started.source,
+        active: started.active,
+        recording_path: started.recording_path,
+        stop_flag,
+        worker,
+        session: started.session,
+    };
+    let mut sessions = SESSIONS.lock().await;
+    if sessions.contains_key(&session_id) {
+        // 启动期间同名会话已被其他请求抢先注册
+        drop(sessions);
+        capture.session.cancel();
+        let _ = capture.shutdown(&session_id).await;
+        return Err(format!("捕获会话 {} 已经在运行中", session_id));
     }
+    let info = capture.info(&session_id);
+    sessions.insert(session_id, capture);
+    Ok(info)
+}
+
+/// 停止指定的捕获会话，返回识别的句子数
+#[tauri::command]
+pub async fn stop_capture_session(session_id: String) -> Result<String, String> {
+    let Some(capture) = SESSIONS.lock().await.remove(&session_id) else {
+        return Err(format!("捕获会话 {} 未运行", session_id));
+    };
+    info!("停止音频捕获 [{}]...", session_id);
+    let results = capture
+        .shutdown(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!(
+        "✅ 识别会话 [{}] 已结束，共 {} 条最终结果",
+        session_id,
+        results.len()
+    );
+    Ok(format!("音频捕获已停止，共识别 {} 句", results.len()))
+}
+
+/// 启动音频捕获和实时语音识别（单会话，兼容旧接口）
+/// 前端可以通过 invoke('start_audio_capture', {config: {...}, deviceId: "...", capture: {...}}) 调用此函数
+/// capture.input 可指定从 get_audio_devices 的 supported_configs 中选择的输入格式，省略时使用设备默认配置
+/// pid 指定时只采集该进程的音频输出（见 start_capture_session）
+/// 设备打开和识别任务建立完成后才返回，返回值包含实际使用的采集参数；启动失败时直接返回错误
+#[tauri::command]
+pub async fn start_audio_capture(
+    config: AsrProviderConfig,
+    device_name: Option<String>,
+    device_id: Option<String>,
+    pid: Option<u32>,
+    capture: Option<CaptureConfig>,
+) -> Result<String, String> {
+    start_capture_session(
+        config,
+        device_name,
+        device_id,
+        pid,
+        Some(DEFAULT_SESSION_ID.to_string()),
+        None,
+        capture,
+    )
+    .await
+    .map(|info| format!("音频捕获已启动（{}）", info.params.summary()))
 }
 
-/// 停止音频捕获
+/// 停止全部音频捕获会话
 /// 前端可以通过 invoke('stop_audio_capture') 调用此函数
 #[tauri::command]
-pub fn stop_audio_capture() -> Result<String, String> {
-    // swap：原子操作，设置新值并返回旧值
-    let was_recording = IS_RECORDING.swap(false, Ordering::SeqCst);
+pub async fn stop_audio_capture() -> Result<String, String> {
+    let sessions = std::mem::take(&mut *SESSIONS.lock().await);
+    if sessions.is_empty() {
+        return Err("音频捕获未运行".to_string());
+    }
+    info!("停止全部音频捕获（{} 个会话）...", sessions.len());
 
-    if was_recording {
-        // 之前是 true（正在录音），已成功停止
-        info!("停止音频捕获...");
+    let mut total = 0;
+    let mut errors = Vec::new();
+    for (session_id, capture) in sessions {
+        match capture.shutdown(&session_id).await {
+            Ok(results) => total += results.len(),
+            Err(e) => errors.push(format!("[{}] {}", session_id, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(format!("音频捕获已停止，共识别 {} 句", total))
+}
 
-        // 注意：音频文件会在 run_audio_capture() 结束时自动保存
-        Ok("音频捕获已停止".to_string())
-    } else {
-        // 之前是 false（未在录音）
-        Err("音频捕获未运行".to_string())
+/// 检查会话 ID 是否可用；同名会话已自行结束（如不可恢复的错误）时回收它
+async fn reap_finished(session_id: &str) -> Result<(), String> {
+    let mut sessions = SESSIONS.lock().await;
+    let finished = match sessions.get(session_id) {
+        None => return Ok(()),
+        Some(capture) if !capture.session.is_finished() => {
+            return Err(format!("捕获会话 {} 已经在运行中", session_id));
+        }
+        Some(_) => sessions.remove(session_id),
+    };
+    // 收尾（等待线程、写录音和字幕）可能较慢，在锁外进行，不阻塞其他会话
+    drop(sessions);
+    if let Some(capture) = finished {
+        let _ = capture.shutdown(session_id).await;
     }
+    Ok(())
 }
 
-/// 音频捕获的实际实现
-async fn run_audio_capture(
+/// 音频捕获的实际实现（运行在独立线程中，持有 cpal::Stream 直到停止）
+fn run_audio_capture(
     config: AsrProviderConfig,
-    device_name: Option<String>,
-) -> anyhow::Result<()> {
-    // 根据设备名称查找设备，如果未指定则使用默认环回设备
-    let device: Device = if let Some(name) = device_name {
-        audio::find_device_by_name(&name).ok_or_else(|| anyhow::anyhow!("找不到设备: {}", name))?
-    } else {
-        audio::find_loopback_device().map_err(|e| {
-            // 将结构化错误转换为 JSON 字符串，前端可以解析
-            anyhow::anyhow!(
-                "{}",
-                serde_json::to_string(&e).unwrap_or_else(|_| e.message.clone())
-            )
-        })?
+    device: DeviceSelector,
+    session_id: &str,
+    source: Option<AudioSource>,
+    capture: CaptureConfig,
+    stop_flag: Arc<AtomicBool>,
+    ready: oneshot::Sender<Result<StartedCapture, String>>,
+) -> anyhow::Result<Option<Recording>> {
+    let mut watcher = DeviceWatcher::default();
+    let opened = match async_runtime::block_on(open_capture(
+        config,
+        device,
+        session_id,
+        source,
+        capture,
+        watcher.error_sender(),
+    )) {
+        Ok(opened) => opened,
+        Err(e) => {
+            let _ = ready.send(Err(e.to_string()));
+            return Err(e);
+        }
     };
-    info!("找到设备：{}", device.name()?);
+    let OpenedCapture {
+        stream,
+        started,
+        setup,
+        recorder,
+    } = opened;
+
+    let status = started.session.status();
+    let active = started.active.clone();
+    lock_active(&active).watch(&mut watcher);
+    if let Err(Ok(started)) = ready.send(Ok(started)) {
+        // 命令端已放弃等待，取消刚建立的会话
+        started.session.cancel();
+        return Err(anyhow!("启动请求已取消"));
+    }
+    info!("✅ 音频流 [{}] 已启动，等待停止指令...", session_id);
+
+    // 等待停止信号或识别会话自行结束
+    // 注意：这里使用 thread::sleep 而非 tokio::time::sleep 是因为：
+    // 1. cpal::Stream 不是 Send，无法跨 await 点或移动到其他线程
+    // 2. 必须在创建 stream 的同一线程中持有它直到停止
+    // 3. 100ms 的轮询间隔对性能影响很小
+    let mut stream = Some(stream);
+    while !stop_flag.load(Ordering::Relaxed) {
+        if status.borrow().is_terminal() {
+            warn!(
+                "⚠️ 识别会话 [{}] 已结束（{:?}），停止音频捕获",
+                session_id,
+                *status.borrow()
+            );
+            break;
+        }
+        if stream.as_ref().is_some_and(InputStream::is_finished) {
+            info!("📄 [{}] 文件音频已全部送入识别会话", session_id);
+            break;
+        }
+        // 设备失效时先关闭旧流，再按配置切换设备；识别会话的发送端由 setup 持有，不会中断
+        if let Some(loss) = watcher.poll() {
+            drop(stream.take());
+            match fail_over(&setup, &active, &mut watcher, loss) {
+                Some(new_stream) => stream = Some(new_stream),
+                None => break,
+            }
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    info!("⏹️  [{}] 收到停止信号，正在清理资源...", session_id);
+
+    // 停止音频流（同时释放音频发送端）
+    drop(stream);
+
+    // 音频流已停止，写完录音缓冲区中的剩余音频
+    let recording = recorder.and_then(|recorder| {
+        recorder
+            .finish()
+            .inspect_err(|e| warn!("❌ 保存录音失败: {}", e))
+            .ok()
+    });
+
+    info!("✅ 音频捕获 [{}] 已完全停止", session_id);
+    Ok(recording)
+}
+
+/// 设备预览的实际实现（运行在独立线程中，采集的音频直接丢弃）
+fn run_probe(device: DeviceSelector, seconds: f32) -> anyhow::Result<ProbeReport> {
+    let device = find_device(&device)?;
+    let device_name = device.name()?;
+    let input_config = device.input_config(None)?;
+    let capture = CaptureConfig::default();
+    let audio_config = capture.resolve(input_config.sample_rate().0, input_config.channels())?;
+    let params = StreamParams::new(&audio_config, &capture, &input_config);
+    info!(
+        "🔍 预览设备 {}（{}秒）: {}",
+        device_name,
+        seconds,
+        params.summary()
+    );
+
+    let levels = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let level_meter = build_level_meter(
+        audio_config.sample_rate_out,
+        PROBE_SESSION_ID,
+        Some(levels.clone()),
+    );
+    let (tx, mut rx) = mpsc::channel::<Vec<f32>>(capture.queue_frames);
+    let recording_state = new_recording_state(&audio_config, tx, level_meter, None, None)?;
+    // 预览时长很短，不处理设备失效
+    let (errors, _) = std::sync::mpsc::channel();
+    let stream = start_input_stream(
+        &device,
+        &input_config,
+        recording_state,
+        audio_config,
+        errors,
+    )?;
+
+    let deadline = Instant::now() + Duration::from_secs_f32(seconds);
+    while Instant::now() < deadline {
+        // 预览不识别，读空 channel 避免丢帧计数
+        while rx.try_recv().is_ok() {}
+        thread::sleep(Duration::from_millis(20));
+    }
+    drop(stream);
+
+    let levels = levels.lock().map(|l| l.clone()).unwrap_or_default();
+    Ok(ProbeReport {
+        device_name,
+        params,
+        level: AudioLevel::combine(&levels),
+    })
+}
+
+/// 打开设备、建立识别会话并启动音频流
+async fn open_capture(
+    config: AsrProviderConfig,
+    device: DeviceSelector,
+    session_id: &str,
+    source: Option<AudioSource>,
+    capture: CaptureConfig,
+    errors: std::sync::mpsc::Sender<cpal::StreamError>,
+) -> anyhow::Result<OpenedCapture> {
+    let device = find_device(&device)?;
+    let device_name = device.name()?;
+    info!("找到设备：{}", device_name);
+    let source = source.unwrap_or(device.default_source());
 
     /*
-    获取设备的默认输出配置
+    获取设备的输入配置（未指定 capture.input 时为默认配置）
     example:
         SupportedStreamConfig { channels: 2, sample_rate: SampleRate(48000), buffer_size: Range { min: 15, max: 4096 }, sample_format: F32 }
      */
-    let default_input_config = device.default_input_config().expect("无法获取默认输入配置");
-    info!("默认输入配置：{:?}", default_input_config);
+    let default_input_config = device.input_config(capture.input.as_ref())?;
+    info!("输入配置：{:?}", default_input_config);
 
     // 原始采样率
     let default_rate = default_input_config.sample_rate().0;
     // 原始通道数
     let default_channel_count: u16 = default_input_config.channels().into();
 
-    // 输出目录（debug 和 release 都定义，避免作用域问题）
-    let output_dir = "../audio_output";
-
-    // WAV 文件写入器（仅在 debug 模式启用）
-    #[cfg(debug_assertions)]
-    let (original_writer, verification_writer) = {
-        std::fs::create_dir_all(output_dir)?;
-        info!("📁 调试模式：音频文件将保存到 {}", output_dir);
-
-        // 创建原始音频文件写入器
-        let original_spec = WavSpec {
-            channels: default_channel_count,
-            sample_rate: default_rate,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
-        let original_path = format!("{}/original_output.wav", output_dir);
-        let original = utils::file::create_wav_writer(&original_path, original_spec)?;
-
-        // 创建验证音频文件写入器
-        let verification_spec = WavSpec {
-            channels: 1,
-            sample_rate: 16000,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-        let verification_path = format!("{}/verification_output.wav", output_dir);
-        let verification = utils::file::create_wav_writer(&verification_path, verification_spec)?;
-
-        (original, verification)
-    };
-
-    #[cfg(not(debug_assertions))]
-    info!("🚀 生产模式：WAV 文件写入已禁用（提升性能）");
-
-    // 音频配置（frame_size=800，约50ms延迟，已优化）
-    let audio_config = audio::AudioConfig {
-        sample_rate_in: default_rate,    // 输入采样率
-        sample_rate_out: 16000,          // 阿里云ASR要求的采样率
-        channels: default_channel_count, // 通道数
-        frame_size: 800,                 // 帧大小（优化为50ms延迟）
-        gain: 3.0,                       // 音频增益（放大3倍提高识别准确性）
-    };
+    // 校验采集配置并结合设备参数生成处理配置
+    let audio_config = capture.resolve(default_rate, default_channel_count)?;
 
     info!(
-        "🔊 音频配置: {}Hz→{}Hz, {}通道, 帧大小={}, 增益={}x",
+        "🔊 音频配置: {}Hz→{}Hz, {}通道（使用 {:?}）, 帧大小={}, 增益={:+.1}dB, 重采样={:?}",
         audio_config.sample_rate_in,
         audio_config.sample_rate_out,
         audio_config.channels,
+        audio_config.selected_channels,
         audio_config.frame_size,
-        audio_config.gain
+        audio_config.gain_db,
+        audio_config.resampler
     );
 
     // 计算重采样比例和延迟
@@ -186,147 +722,713 @@ async fn run_audio_capture(
     );
 
     if input_duration_ms > 100 {
-        warn!("⚠️  延迟较高，可减小 frame_size 以优化");
+        warn!("⚠️  延迟较高，可减小 frame_ms 以优化");
     }
 
-    let resampler = init_resampler(&audio_config, resample_ratio);
-    let (tx, rx) = mpsc::channel::<Vec<f32>>(1000);
+    let (tx, rx) = mpsc::channel::<Vec<f32>>(capture.queue_frames);
 
     info!("🤖 ASR: 启动语音识别，配置: {:?}", config);
     let provider: Box<dyn AsrProvider> = match config {
-        AsrProviderConfig::Cloud(c) => Box::new(CloudAsrProvider::new(c)),
+        AsrProviderConfig::Cloud(c) => Box::new(CloudAsrProvider::new(*c)),
         AsrProviderConfig::Local(c) => Box::new(LocalAsrProvider::new(c)),
     };
-    provider.recognize_stream(rx).await?;
+    let session = provider
+        .recognize_stream(
+            rx,
+            audio_config.sample_rate_out,
+            ResultOrigin::new(session_id, source),
+        )
+        .await?;
+
+    if audio_config.vad.mode != VadMode::Off {
+        info!("🗣️ 启用语音活动检测: {:?}", audio_config.vad);
+    }
+
+    // 会话录音：处理后的音频写入用户选择的目录
+    let recorder = capture
+        .record
+        .as_ref()
+        .map(|record| {
+            let ffmpeg = record
+                .format
+                .needs_encoder()
+                .then(ffmpeg_command)
+                .transpose()?;
+            SessionRecorder::start(record, session_id, audio_config.sample_rate_out, ffmpeg)
+        })
+        .transpose()
+        .inspect_err(|_| session.cancel())?;
 
     info!("🎙️  开始捕获音频...");
 
-    // send + sync
-    let mut recording_state = audio::RecordingState {
-        resampler,
-        sample_buffer: Vec::with_capacity(audio_config.frame_size * audio_config.channels as usize),
-        channel_data: vec![
-            Vec::with_capacity(audio_config.frame_size);
-            audio_config.channels as usize
-        ],
+    let setup = StreamSetup {
+        session_id: session_id.to_string(),
+        capture,
         tx,
-        volume_stats: audio::VolumeStats {
-            max_volume: 0.0,
-            avg_volume: 0.0,
-            frame_count: 0,
-            low_volume_count: 0,
-            window_low_count: 0,
+        pauser: session.pauser(),
+        recorder: recorder.as_ref().map(SessionRecorder::tap),
+    };
+    let (stream, params) = setup
+        .open(&device, errors)
+        .inspect_err(|_| session.cancel())?;
+
+    Ok(OpenedCapture {
+        stream,
+        started: StartedCapture {
+            session,
+            source,
+            active: Arc::new(std::sync::Mutex::new(ActiveDevice {
+                device_id: device.id(),
+                device_name,
+                params,
+                application: device.is_application(),
+            })),
+            recording_path: recorder
+                .as_ref()
+                .map(|r| r.path().to_string_lossy().to_string()),
         },
+        setup,
+        recorder,
+    })
+}
+
+/// 重新打开音频流所需的上下文（切换设备时复用同一识别会话的发送端和暂停控制）
+struct StreamSetup {
+    session_id: String,
+    capture: CaptureConfig,
+    tx: mpsc::Sender<Vec<f32>>,
+    pauser: SessionPauser,
+    /// 切换设备后继续写入同一录音文件
+    recorder: Option<RecorderTap>,
+}
+
+impl StreamSetup {
+    /// 按设备能力解析采集配置，建立处理管线并启动音频流
+    fn open(
+        &self,
+        device: &InputDevice,
+        errors: std::sync::mpsc::Sender<cpal::StreamError>,
+    ) -> anyhow::Result<(InputStream, StreamParams)> {
+        let input_config = device.input_config(self.capture.input.as_ref())?;
+        let audio_config = self
+            .capture
+            .resolve(input_config.sample_rate().0, input_config.channels())?;
+        let params = StreamParams::new(&audio_config, &self.capture, &input_config);
+
+        let vad = (audio_config.vad.mode != VadMode::Off).then(|| {
+            build_vad(
+                &audio_config.vad,
+                audio_config.sample_rate_out,
+                &self.session_id,
+                self.pauser.clone(),
+            )
+        });
+        let level_meter = build_level_meter(audio_config.sample_rate_out, &self.session_id, None);
+        let recording_state = new_recording_state(
+            &audio_config,
+            self.tx.clone(),
+            level_meter,
+            vad,
+            self.recorder.clone(),
+        )?;
+        let stream =
+            start_input_stream(device, &input_config, recording_state, audio_config, errors)?;
+        Ok((stream, params))
+    }
+}
+
+/// 当前设备失效：按配置切换到备用设备并继续识别；不能切换时推送错误事件并返回 None（停止采集）
+fn fail_over(
+    setup: &StreamSetup,
+    active: &std::sync::Mutex<ActiveDevice>,
+    watcher: &mut DeviceWatcher,
+    loss: DeviceLoss,
+) -> Option<InputStream> {
+    let session_id = setup.session_id.as_str();
+    let previous = lock_active(active);
+    let previous_device = previous.device_name;
+    // 采集应用音频时不切换到麦克风，避免把用户的声音混入识别
+    let fallback = match setup.capture.fallback {
+        DeviceFallback::DefaultInput if previous.application => DeviceFallback::Stop,
+        fallback => fallback,
     };
+    warn!(
+        "⚠️ [{}] {}，处理方式: {:?}",
+        session_id, loss.message, fallback
+    );
+    watcher.clear_errors();
+
+    let switched = find_fallback_device(fallback).and_then(|device| {
+        let device_name = device.name()?;
+        let (device_id, application) = (device.id(), device.is_application());
+        let fallback_setup = StreamSetup {
+            session_id: setup.session_id.clone(),
+            capture: setup.capture.for_fallback_device(),
+            tx: setup.tx.clone(),
+            pauser: setup.pauser.clone(),
+            recorder: setup.recorder.clone(),
+        };
+        let (stream, params) = fallback_setup.open(&device, watcher.error_sender())?;
+        let switched = ActiveDevice {
+            device_id,
+            device_name,
+            params,
+            application,
+        };
+        Ok((switched, stream))
+    });
+
+    match switched {
+        Ok((switched, stream)) => {
+            info!(
+                "🔁 [{}] 已切换到设备 {}（{}）",
+                session_id,
+                switched.device_name,
+                switched.params.summary()
+            );
+            switched.watch(watcher);
+            let payload = AudioDeviceChangedEvent {
+                session_id: session_id.to_string(),
+                previous_device,
+                device_name: switched.device_name.clone(),
+                reason: loss.reason,
+                params: switched.params.clone(),
+            };
+            if let Err(err) = app_state::emit_event(AUDIO_DEVICE_CHANGED_EVENT, &payload) {
+                warn!("发送设备切换事件失败: {}", err);
+            }
+            *active.lock().unwrap_or_else(|e| e.into_inner()) = switched;
+            Some(stream)
+        }
+        Err(e) => {
+            warn!("❌ [{}] 无法切换设备，停止音频捕获: {}", session_id, e);
+            let payload = AudioCaptureErrorEvent {
+                session_id: session_id.to_string(),
+                device_name: previous_device,
+                reason: loss.reason,
+                message: format!("{}；{}", loss.message, e),
+            };
+            if let Err(err) = app_state::emit_event(AUDIO_CAPTURE_ERROR_EVENT, &payload) {
+                warn!("发送采集错误事件失败: {}", err);
+            }
+            None
+        }
+    }
+}
+
+/// 查找备用设备
+fn find_fallback_device(fallback: DeviceFallback) -> anyhow::Result<InputDevice> {
+    match fallback {
+        DeviceFallback::Stop => Err(anyhow!("未配置备用设备")),
+        DeviceFallback::DefaultInput => cpal::default_host()
+            .default_input_device()
+            .context("没有可用的默认输入设备")
+            .and_then(InputDevice::cpal),
+        DeviceFallback::Loopback => find_device(&DeviceSelector::default()),
+    }
+}
+
+fn lock_active(active: &std::sync::Mutex<ActiveDevice>) -> ActiveDevice {
+    // 采集线程只做简单赋值，锁中毒时数据仍然可用
+    active.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 前端指定的设备
+#[derive(Debug, Clone, Default)]
+struct DeviceSelector {
+    /// 稳定 ID（get_audio_devices 返回的 id），优先使用
+    id: Option<String>,
+    /// 显示名称（同名设备时取第一个）
+    name: Option<String>,
+    /// 只采集该进程的音频输出
+    pid: Option<u32>,
+    /// 以音视频文件作为音频源
+    file: Option<FileSourceConfig>,
+}
+
+/// 采集设备：cpal 设备、音视频文件，或 Linux 上经 PulseAudio / PipeWire 采集的输出设备 monitor / 应用播放流
+enum InputDevice {
+    Cpal {
+        device: Device,
+        /// 稳定 ID（get_audio_devices 返回的 id）
+        id: String,
+    },
+    File {
+        config: FileSourceConfig,
+        decoder: FileDecoder,
+    },
+    #[cfg(target_os = "linux")]
+    Pulse {
+        id: String,
+        name: String,
+        target: audio::pulse::PulseTarget,
+        sample_rate: u32,
+        channels: u16,
+    },
+}
+
+impl InputDevice {
+    #[cfg(target_os = "linux")]
+    fn pulse_monitor(sink: audio::pulse::PulseSink) -> Self {
+        Self::Pulse {
+            id: sink.device_id(),
+            name: sink.monitor_name(),
+            target: audio::pulse::PulseTarget::Monitor(sink.monitor_source),
+            sample_rate: sink.sample_rate,
+            channels: sink.channels,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn pulse_application(input: audio::pulse::PulseSinkInput) -> Self {
+        Self::Pulse {
+            id: input.device_id(),
+            name: input.display_name(),
+            target: audio::pulse::PulseTarget::SinkInput {
+                index: input.index,
+                pid: input.pid,
+            },
+            sample_rate: input.sample_rate,
+            channels: input.channels,
+        }
+    }
+
+    /// 按 cpal 设备名称确定 ID（同名设备视为第一个）
+    fn cpal(device: Device) -> anyhow::Result<Self> {
+        let id = audio::device_id_of(&device).context("无法读取设备名称")?;
+        Ok(Self::Cpal { device, id })
+    }
+
+    /// 稳定 ID（设备监视用），文件音频源没有 ID
+    fn id(&self) -> Option<String> {
+        match self {
+            Self::Cpal { id, .. } => Some(id.clone()),
+            Self::File { .. } => None,
+            #[cfg(target_os = "linux")]
+            Self::Pulse { id, .. } => Some(id.clone()),
+        }
+    }
 
-    // 构建音频输入流
-    let err_fn = |err| eprintln!("❌ 音频错误：{}", err);
-    let stream_config: StreamConfig = default_input_config.clone().into();
+    /// 是否是单个应用的播放流
+    fn is_application(&self) -> bool {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Pulse {
+                target: audio::pulse::PulseTarget::SinkInput { .. },
+                ..
+            } => true,
+            _ => false,
+        }
+    }
+
+    fn name(&self) -> anyhow::Result<String> {
+        match self {
+            Self::Cpal { device, .. } => Ok(device.name()?),
+            Self::File { config, .. } => Ok(std::path::Path::new(&config.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| config.path.clone())),
+            #[cfg(target_os = "linux")]
+            Self::Pulse { name, .. } => Ok(name.clone()),
+        }
+    }
 
-    let stream = match default_input_config.sample_format() {
+    /// 未指定来源时的默认值：环回设备为 system，文件为 file
+    fn default_source(&self) -> AudioSource {
+        match self {
+            Self::Cpal { device, .. } if audio::is_loopback_device(device).is_some() => {
+                AudioSource::System
+            }
+            Self::Cpal { .. } => AudioSource::Microphone,
+            Self::File { .. } => AudioSource::File,
+            #[cfg(target_os = "linux")]
+            Self::Pulse { .. } => AudioSource::System,
+        }
+    }
+
+    /// 输入流配置：未指定格式时使用设备默认配置
+    fn input_config(&self, format: Option<&InputFormat>) -> anyhow::Result<SupportedStreamConfig> {
+        match self {
+            Self::Cpal { device, .. } => audio::select_input_config(device, format),
+            Self::File {
+                decoder:
+                    FileDecoder::Wav {
+                        sample_rate,
+                        channels,
+                    },
+                ..
+            } => {
+                // WAV 不经转换直接读取，格式由文件头决定
+                if let Some(f) = format {
+                    if (f.sample_rate, f.channels) != (*sample_rate, *channels) {
+                        return Err(anyhow!(
+                            "WAV 文件格式为 {}Hz {}声道，不能指定输入格式",
+                            sample_rate,
+                            channels
+                        ));
+                    }
+                }
+                converted_input_config(None, *sample_rate, *channels)
+            }
+            Self::File {
+                decoder: FileDecoder::Ffmpeg,
+                ..
+            } => converted_input_config(
+                format,
+                file_source::DEFAULT_DECODE_SAMPLE_RATE,
+                file_source::DEFAULT_DECODE_CHANNELS,
+            ),
+            #[cfg(target_os = "linux")]
+            Self::Pulse {
+                sample_rate,
+                channels,
+                ..
+            } => converted_input_config(format, *sample_rate, *channels),
+        }
+    }
+}
+
+/// 由外部进程（parec / ffmpeg）转换格式的输入：任意采样率和声道数都可以，但只输出 f32
+fn converted_input_config(
+    format: Option<&InputFormat>,
+    sample_rate: u32,
+    channels: u16,
+) -> anyhow::Result<SupportedStreamConfig> {
+    if let Some(f) = format.and_then(|f| f.sample_format.as_deref()) {
+        if f != "f32" {
+            return Err(anyhow!("该音频源只支持 f32 采样格式"));
+        }
+    }
+    let (sample_rate, channels) = format
+        .map(|f| (f.sample_rate, f.channels))
+        .unwrap_or((sample_rate, channels));
+    Ok(SupportedStreamConfig::new(
+        channels,
+        cpal::SampleRate(sample_rate),
+        cpal::SupportedBufferSize::Unknown,
+        cpal::SampleFormat::F32,
+    ))
+}
+
+/// 已启动的输入流（cpal::Stream、文件回放或 parec 采集进程），丢弃即停止采集
+struct InputStream {
+    _handle: Box<dyn std::any::Any>,
+    /// 音频源已结束（文件读完），设备流始终为 false
+    finished: Arc<AtomicBool>,
+}
+
+impl InputStream {
+    fn new(handle: impl std::any::Any) -> Self {
+        Self {
+            _handle: Box::new(handle),
+            finished: Arc::default(),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+}
+
+/// 按进程、ID 或名称查找设备，如果都未指定则使用默认环回设备
+/// Linux 上优先使用 PulseAudio / PipeWire 默认输出设备的 monitor
+fn find_device(selector: &DeviceSelector) -> anyhow::Result<InputDevice> {
+    if let Some(file) = &selector.file {
+        let decoder = FileDecoder::detect(std::path::Path::new(&file.path))?;
+        return Ok(InputDevice::File {
+            config: file.clone(),
+            decoder,
+        });
+    }
+    if let Some(pid) = selector.pid {
+        return find_application(pid);
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(device) = find_pulse_device(selector) {
+        return Ok(device);
+    }
+
+    match selector {
+        DeviceSelector { id: Some(id), .. } => audio::find_device_by_id(id)
+            .map(|device| InputDevice::Cpal {
+                device,
+                id: id.clone(),
+            })
+            .ok_or_else(|| anyhow!("找不到设备: {}", id)),
+        DeviceSelector {
+            name: Some(name), ..
+        } => audio::find_device_by_name(name)
+            .ok_or_else(|| anyhow!("找不到设备: {}", name))
+            .and_then(InputDevice::cpal),
+        _ => audio::find_loopback_device()
+            .map_err(|e| {
+                // 将结构化错误转换为 JSON 字符串，前端可以解析
+                anyhow!(
+                    "{}",
+                    serde_json::to_string(&e).unwrap_or_else(|_| e.message.clone())
+                )
+            })
+            .and_then(InputDevice::cpal),
+    }
+}
+
+/// 按进程查找应用的音频输出
+#[cfg(target_os = "linux")]
+fn find_application(pid: u32) -> anyhow::Result<InputDevice> {
+    audio::pulse::find_sink_input_by_pid(pid)
+        .map(InputDevice::pulse_application)
+        .ok_or_else(|| anyhow!("进程 {} 当前没有播放音频", pid))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn find_application(pid: u32) -> anyhow::Result<InputDevice> {
+    Err(anyhow!("当前平台不支持按应用采集（进程 {}）", pid))
+}
+
+#[cfg(target_os = "linux")]
+fn find_pulse_device(selector: &DeviceSelector) -> Option<InputDevice> {
+    use audio::pulse;
+    match selector {
+        DeviceSelector { id: Some(id), .. } if pulse::is_device_id(id) => {
+            pulse::find_sink(id).map(InputDevice::pulse_monitor)
+        }
+        DeviceSelector { id: Some(id), .. } if pulse::is_application_id(id) => {
+            pulse::find_sink_input(id).map(InputDevice::pulse_application)
+        }
+        DeviceSelector { id: Some(_), .. } => None,
+        DeviceSelector {
+            name: Some(name), ..
+        } => pulse::find_sink(name).map(InputDevice::pulse_monitor),
+        _ => pulse::default_sink()
+            .inspect_err(|e| log::debug!("PulseAudio 不可用，使用 ALSA 环回设备: {}", e))
+            .map(InputDevice::pulse_monitor)
+            .ok(),
+    }
+}
+
+/// 创建采集回调使用的处理状态：按 CaptureConfig.stages 构建处理链
+fn new_recording_state(
+    audio_config: &audio::AudioConfig,
+    tx: mpsc::Sender<Vec<f32>>,
+    level_meter: LevelMeter,
+    vad: Option<Vad>,
+    recorder: Option<RecorderTap>,
+) -> anyhow::Result<audio::RecordingState> {
+    let overruns = Arc::<std::sync::atomic::AtomicU64>::default();
+    let chain = AudioChain::build(
+        audio_config,
+        ChainResources {
+            tx: tx.clone(),
+            level_meter: Some(level_meter),
+            vad,
+            recorder,
+            overruns: overruns.clone(),
+        },
+    )?;
+    Ok(audio::RecordingState {
+        sample_buffer: Vec::with_capacity(audio_config.frame_size * audio_config.channels as usize),
+        chain,
+        tx,
+        overruns,
+    })
+}
+
+/// 启动输入流：cpal 回调只把样本写入环形缓冲区，由 DSP 工作线程运行处理管线；
+/// 文件回放和 parec 读取线程本身不在实时音频线程上，直接运行处理管线
+fn start_input_stream(
+    device: &InputDevice,
+    input_config: &SupportedStreamConfig,
+    mut recording_state: audio::RecordingState,
+    audio_config: audio::AudioConfig,
+    errors: std::sync::mpsc::Sender<cpal::StreamError>,
+) -> anyhow::Result<InputStream> {
+    match device {
+        InputDevice::Cpal { device, .. } => {
+            let capacity = (input_config.sample_rate().0 as f32
+                * input_config.channels() as f32
+                * dsp_worker::RING_SECONDS) as usize;
+            let (worker, input) =
+                DspWorker::start(capacity, recording_state.overruns.clone(), move |data| {
+                    audio::process_audio_data(data, &mut recording_state, &audio_config)
+                });
+            let stream = build_input_stream(device, input_config, input, errors)?;
+            stream.play()?;
+            // 元组按顺序释放：先停止音频流，再让工作线程处理完剩余样本
+            Ok(InputStream::new((stream, worker)))
+        }
+        InputDevice::File { config, decoder } => {
+            let path = std::path::Path::new(&config.path);
+            let reader = match decoder {
+                FileDecoder::Wav { .. } => FileReader::wav(path)?,
+                FileDecoder::Ffmpeg => FileReader::ffmpeg(
+                    ffmpeg_command()?,
+                    path,
+                    input_config.sample_rate().0,
+                    input_config.channels(),
+                )?,
+            };
+            // 识别端队列已满时暂停读取，加速回放也不丢帧
+            let tx = recording_state.tx.clone();
+            let finished = Arc::new(AtomicBool::new(false));
+            let playback = {
+                let finished = finished.clone();
+                FilePlayback::start(
+                    reader,
+                    config.speed,
+                    move || tx.capacity() > 0,
+                    move |data| {
+                        audio::process_audio_data(data, &mut recording_state, &audio_config)
+                    },
+                    move |result| {
+                        match result {
+                            Ok(played) => info!("📄 文件回放完毕（{:.1}秒）", played.as_secs_f32()),
+                            Err(e) => warn!("❌ 文件解码失败: {}", e),
+                        }
+                        finished.store(true, Ordering::Relaxed);
+                    },
+                )
+            };
+            Ok(InputStream {
+                _handle: Box::new(playback),
+                finished,
+            })
+        }
+        #[cfg(target_os = "linux")]
+        InputDevice::Pulse { target, .. } => {
+            let capture = audio::pulse::PulseCapture::start(
+                target,
+                input_config.sample_rate().0,
+                input_config.channels(),
+                move |data| audio::process_audio_data(data, &mut recording_state, &audio_config),
+                move |reason| {
+                    warn!("❌ {}", reason);
+                    let _ = errors.send(cpal::StreamError::DeviceNotAvailable);
+                },
+            )?;
+            Ok(InputStream::new(capture))
+        }
+    }
+}
+
+/// ffmpeg sidecar 的命令（文件音频源解码用）
+fn ffmpeg_command() -> anyhow::Result<std::process::Command> {
+    use tauri_plugin_shell::ShellExt;
+    let app = app_state::get_app_handle().ok_or_else(|| anyhow!("AppHandle 未初始化"))?;
+    let ffmpeg = app
+        .shell()
+        .sidecar("ffmpeg")
+        .map_err(|e| anyhow!("FFmpeg sidecar 错误: {e}"))?;
+    Ok(ffmpeg.into())
+}
+
+/// 按设备的采样格式构建音频输入流（回调中只写入环形缓冲区）
+fn build_input_stream(
+    device: &Device,
+    input_config: &SupportedStreamConfig,
+    input: CallbackInput,
+    errors: std::sync::mpsc::Sender<cpal::StreamError>,
+) -> anyhow::Result<cpal::Stream> {
+    // 错误交给采集线程的设备监视器处理（设备不可用时切换或停止）
+    let err_fn = move |err| {
+        let _ = errors.send(err);
+    };
+    let stream_config: StreamConfig = input_config.clone().into();
+
+    let stream = match input_config.sample_format() {
         cpal::SampleFormat::F32 => device.build_input_stream(
             &stream_config,
-            move |data: &[f32], _: &InputCallbackInfo| {
-                audio::process_audio_data(data, &mut recording_state, &audio_config);
-            },
+            move |data: &[f32], _: &InputCallbackInfo| input.push(data),
             err_fn,
             None,
         ),
         cpal::SampleFormat::I16 => device.build_input_stream(
             &stream_config,
-            move |data: &[i16], _: &_| {
-                audio::process_audio_data(data, &mut recording_state, &audio_config);
-            },
+            move |data: &[i16], _: &_| input.push(data),
             err_fn,
             None,
         ),
         cpal::SampleFormat::U16 => device.build_input_stream(
             &stream_config,
-            move |data: &[u16], _: &_| {
-                audio::process_audio_data(data, &mut recording_state, &audio_config);
-            },
+            move |data: &[u16], _: &_| input.push(data),
             err_fn,
             None,
         ),
         cpal::SampleFormat::I32 => device.build_input_stream(
             &stream_config,
-            move |data: &[i32], _: &_| {
-                audio::process_audio_data(data, &mut recording_state, &audio_config);
-            },
+            move |data: &[i32], _: &_| input.push(data),
             err_fn,
             None,
         ),
         cpal::SampleFormat::F64 => device.build_input_stream(
             &stream_config,
-            move |data: &[f64], _: &_| {
-                audio::process_audio_data(data, &mut recording_state, &audio_config);
-            },
+            move |data: &[f64], _: &_| input.push(data),
             err_fn,
             None,
         ),
-        fmt => {
-            return Err(anyhow!(
-                "不支持的采样格式：{:?}",
-                fmt
-            ))
-        }
+        fmt => return Err(anyhow!("不支持的采样格式：{:?}", fmt)),
     }?;
+    Ok(stream)
+}
 
-    stream.play()?;
-    info!("✅ 音频流已启动，等待停止指令...");
-
-    // 等待停止信号
-    // 注意：这里使用 thread::sleep 而非 tokio::time::sleep 是因为：
-    // 1. cpal::Stream 不是 Send，无法跨 await 点或移动到其他线程
-    // 2. 必须在创建 stream 的同一线程中持有它直到停止
-    // 3. 100ms 的轮询间隔对性能影响很小
-    while IS_RECORDING.load(Ordering::Relaxed) {
-        thread::sleep(Duration::from_millis(100));
-    }
-
-    info!("⏹️  收到停止信号，正在清理资源...");
-
-    // 停止音频流
-    drop(stream);
-
-    // 保存 WAV 文件（仅在 debug 模式）
-    #[cfg(debug_assertions)]
-    {
-        info!("💾 保存调试音频文件...");
-        utils::file::save_wav_writer(original_writer)?;
-        utils::file::save_wav_writer(verification_writer)?;
-        info!("✅ 调试文件已保存到 {}", output_dir);
-    }
-
-    info!("✅ 音频捕获已完全停止");
-    Ok(())
+/// 创建 VAD：状态变化时推送 vad-state 事件，被丢弃的静音计入会话时间轴，PauseAsr 模式下暂停/恢复识别会话
+fn build_vad(config: &VadConfig, sample_rate: u32, session_id: &str, pauser: SessionPauser) -> Vad {
+    let session_id = session_id.to_string();
+    let pause_asr = config.mode == VadMode::PauseAsr;
+    let mut asr_paused = false;
+    Vad::new(config.clone(), sample_rate).with_listener(Box::new(move |event| {
+        let (state, level_db) = match event {
+            VadEvent::SpeechStart {
+                level_db,
+                skipped_samples,
+            } => {
+                pauser.skip(skipped_samples);
+                if pause_asr {
+                    pauser.resume();
+                }
+                asr_paused = false;
+                (VadState::Speech, level_db)
+            }
+            VadEvent::SpeechEnd { level_db } => (VadState::Silence, level_db),
+            VadEvent::PauseAsr { level_db } => {
+                pauser.pause();
+                asr_paused = true;
+                (VadState::Silence, level_db)
+            }
+        };
+        let payload = VadStateEvent {
+            session_id: Some(session_id.clone()),
+            state,
+            asr_paused,
+            level_db,
+        };
+        if let Err(err) = app_state::emit_event(VAD_STATE_EVENT, &payload) {
+            warn!("发送 VAD 状态到前端失败: {}", err);
+        }
+    }))
 }
 
-fn init_resampler(audio_config: &audio::AudioConfig, resample_ratio: f64) -> SincFixedIn<f32> {
-    // 初始化重采样器（针对低延迟优化）
-    // 参数说明：
-    // - resample_ratio: 重采样比例 (输出/输入采样率)
-    // - max_resample_ratio_relative: 最大相对比率，限制动态调整范围（如果不使用动态调整，影响内存分配）
-    //   当前设置为 1.2，允许比率在 resample_ratio * 1.2 和 resample_ratio / 1.2 之间
-    //   由于我们使用固定比率，1.2 足够且节省内存（原 2.0）
-    // - sinc_len: 越小越快但质量略降，128 是平衡点（原256）
-    // - interpolation: Linear 速度更快，Cubic 质量更高但慢
-    // - oversampling_factor: 越小越快但质量略降，80 是平衡点（原160）
-    // - f_cutoff: 越小越保守，0.95 更激进但质量略降
-    let resampler: SincFixedIn<f32> = SincFixedIn::<f32>::new(
-        resample_ratio, // 重采样比例, 必须大于0
-        1.2,            // 最大相对比率：降低到 1.2 节省内存（原 2.0），因为不使用动态调整
-        SincInterpolationParameters {
-            sinc_len: 128, // 降低：256->128，提高速度（质量仍可接受）
-            interpolation: rubato::SincInterpolationType::Linear, // Linear：速度快（已优化）
-            oversampling_factor: 80, // 降低：160->80，平衡速度和质量
-            f_cutoff: 0.95, // 提高：0.6->0.95，减少过度滤波
-            window: WindowFunction::BlackmanHarris, // 保持高质量窗口函数
-        },
-        audio_config.frame_size,        // 输入数据的帧大小
-        audio_config.channels as usize, // 输入/输出的通道数
+/// 创建电平表：约 20Hz 推送 audio-level 事件；collector 非空时同时保存每次的电平（设备预览汇总用）
+fn build_level_meter(
+    sample_rate: u32,
+    session_id: &str,
+    collector: Option<Arc<std::sync::Mutex<Vec<AudioLevel>>>>,
+) -> LevelMeter {
+    let session_id = session_id.to_string();
+    LevelMeter::new(
+        sample_rate,
+        Box::new(move |level| {
+            if let Some(collector) = &collector {
+                if let Ok(mut levels) = collector.lock() {
+                    levels.push(level.clone());
+                }
+            }
+            let payload = AudioLevelEvent {
+                session_id: Some(session_id.clone()),
+                level,
+            };
+            if let Err(err) = app_state::emit_event(AUDIO_LEVEL_EVENT, &payload) {
+                log::debug!("发送电平事件失败: {}", err);
+            }
+        }),
     )
-    .context("无法创建重采样器")
-    .unwrap();
-    resampler
 }
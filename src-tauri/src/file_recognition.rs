@@ -1,9 +1,12 @@
-use crate::asr::config::AsrProviderConfig;
+use crate::asr::batch::{self, BatchItemProgress, BatchSnapshot, Concurrency, OutputFormat};
+use crate::asr::config::{AsrProviderConfig, CloudAsrConfig};
 use crate::asr::events::AsrResultEvent;
-use crate::asr::provider::{CloudAsrProvider, LocalAsrProvider};
+use crate::asr::file_job::{self, FileJob};
+use crate::asr::provider::{cloud, CloudAsrProvider, LocalAsrProvider};
 use crate::asr::AsrProvider;
-use log::info;
-use std::path::Path;
+use log::{error, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 /// 音频文件识别：OSS 上传 → Fun-ASR → 写 SRT → 返回完整结果列表
 #[tauri::command]
@@ -14,7 +17,7 @@ pub async fn recognize_file(
 ) -> Result<Vec<AsrResultEvent>, String> {
     info!("开始文件识别: {} → {}", input_path, output_path);
     let provider: Box<dyn AsrProvider> = match config {
-        AsrProviderConfig::Cloud(c) => Box::new(CloudAsrProvider::new(c)),
+        AsrProviderConfig::Cloud(c) => Box::new(CloudAsrProvider::new(*c)),
         AsrProviderConfig::Local(c) => Box::new(LocalAsrProvider::new(c)),
     };
     provider
@@ -22,3 +25,104 @@ pub async fn recognize_file(
         .await
         .map_err(|e| e.to_string())
 }
+
+/// 取消运行中的云端文件识别任务（job_id 来自 file-asr-progress 事件）
+#[tauri::command]
+pub fn cancel_file_job(job_id: String) -> Result<(), String> {
+    if file_job::cancel(&job_id) {
+        Ok(())
+    } else {
+        Err(format!("任务 {} 不存在或已结束", job_id))
+    }
+}
+
+/// 批量识别：加入文件、目录或通配符（如 /录音/*.mp4），输出为 <文件名>.srt 或 <文件名>.json，
+/// output_dir 为空时写在输入文件旁边；进度通过 batch-asr-item / batch-asr-progress 事件推送
+#[tauri::command]
+pub async fn enqueue_batch(
+    config: AsrProviderConfig,
+    inputs: Vec<String>,
+    output_dir: Option<String>,
+    format: Option<OutputFormat>,
+) -> Result<Vec<BatchItemProgress>, String> {
+    let output_dir = output_dir.filter(|d| !d.is_empty()).map(PathBuf::from);
+    batch::enqueue(
+        &inputs,
+        output_dir.as_deref(),
+        format.unwrap_or_default(),
+        config,
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_batch_queue() -> BatchSnapshot {
+    batch::snapshot()
+}
+
+/// 暂停批量识别（运行中的文件会继续完成）
+#[tauri::command]
+pub fn pause_batch() {
+    batch::pause();
+}
+
+#[tauri::command]
+pub fn resume_batch() {
+    batch::resume();
+}
+
+/// 按 item_ids 的顺序调整待处理条目，未列出的排在后面
+#[tauri::command]
+pub fn reorder_batch(item_ids: Vec<String>) {
+    batch::reorder(&item_ids);
+}
+
+/// 设置云端 / 本地各自同时处理的文件数
+#[tauri::command]
+pub fn set_batch_concurrency(concurrency: Concurrency) {
+    batch::set_concurrency(concurrency);
+}
+
+/// 清除已完成和失败的条目
+#[tauri::command]
+pub fn clear_finished_batch() {
+    batch::clear_finished();
+}
+
+/// 启用任务持久化，读取上次退出时未完成的文件识别任务和批量识别队列；
+/// 任务记录不含密钥，云端任务等前端调用 restore_file_jobs 提供当前配置后再继续
+pub fn load_file_jobs(data_dir: PathBuf) {
+    let mut pending = match file_job::init(data_dir.join("file_jobs")) {
+        Ok(pending) => pending,
+        Err(err) => {
+            warn!("⚠️ 文件识别任务持久化不可用: {:#}", err);
+            Vec::new()
+        }
+    };
+    // 批量队列中的云端任务由队列接回，完成后才能更新对应条目
+    batch::init(data_dir.join("batch_queue.json"), &mut pending);
+    *PARKED.lock().unwrap_or_else(|e| e.into_inner()) = pending;
+}
+
+/// 等待前端配置的恢复任务
+static PARKED: Mutex<Vec<FileJob>> = Mutex::new(Vec::new());
+
+/// 前端读取配置后调用：用当前配置中的密钥继续未完成的云端文件识别任务和批量条目
+#[tauri::command]
+pub fn restore_file_jobs(config: CloudAsrConfig) {
+    file_job::set_credentials(&config);
+    let parked = std::mem::take(&mut *PARKED.lock().unwrap_or_else(|e| e.into_inner()));
+    for mut job in parked {
+        info!(
+            "♻️ 恢复文件识别任务 {}（{:?}）: {}",
+            job.id, job.stage, job.input_path
+        );
+        job.config.restore_secrets(&config);
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = cloud::run_job(job).await {
+                error!("恢复的文件识别任务失败: {:#}", err);
+            }
+        });
+    }
+    batch::schedule();
+}
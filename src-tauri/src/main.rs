@@ -90,8 +90,13 @@ pub fn main() {
             test_logs,
             write_text_file,
             audio_capture::get_audio_devices,
+            audio_capture::probe_audio_device,
             audio_capture::start_audio_capture,
             audio_capture::stop_audio_capture,
+            audio_capture::list_capture_sessions,
+            audio_capture::start_capture_session,
+            audio_capture::start_file_session,
+            audio_capture::stop_capture_session,
             video_subtitle::get_ffmpeg_version,
             video_subtitle::check_ffmpeg_subtitle_support,
             video_subtitle::select_video,
@@ -101,7 +106,16 @@ pub fn main() {
             video_subtitle::start_video_asr,
             video_subtitle::export_video_with_subtitles,
             video_subtitle::translate_subtitles,
-            file_recognition::recognize_file
+            file_recognition::recognize_file,
+            file_recognition::cancel_file_job,
+            file_recognition::enqueue_batch,
+            file_recognition::get_batch_queue,
+            file_recognition::pause_batch,
+            file_recognition::resume_batch,
+            file_recognition::reorder_batch,
+            file_recognition::set_batch_concurrency,
+            file_recognition::clear_finished_batch,
+            file_recognition::restore_file_jobs
         ])
         // 设置应用启动后的回调
         .setup(|app| {
@@ -111,6 +125,12 @@ pub fn main() {
             logger::attach_app_handle(app.handle().clone());
             log::info!("Tauri 应用启动成功");
 
+            // 读取未完成的云端文件识别任务和批量识别队列（等前端提供配置后继续）
+            match app.path().app_data_dir() {
+                Ok(dir) => file_recognition::load_file_jobs(dir),
+                Err(e) => log::warn!("无法获取应用数据目录，文件识别任务不会持久化: {}", e),
+            }
+
             // macOS：将字幕悬浮窗转换为 NSPanel
             #[cfg(target_os = "macos")]
             {
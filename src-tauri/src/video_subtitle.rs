@@ -153,7 +153,7 @@ pub async fn start_video_asr(
 
     let temp_srt = std::env::temp_dir().join("vocosphere_video_asr.srt");
     let provider: Box<dyn AsrProvider> = match config {
-        AsrProviderConfig::Cloud(c) => Box::new(CloudAsrProvider::new(c)),
+        AsrProviderConfig::Cloud(c) => Box::new(CloudAsrProvider::new(*c)),
         AsrProviderConfig::Local(c) => Box::new(LocalAsrProvider::new(c)),
     };
     provider
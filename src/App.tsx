@@ -1,3 +1,4 @@
+import { invoke } from '@tauri-apps/api/core'
 import { useEffect } from 'react'
 import { Routes, Route } from 'react-router-dom'
 import { Toaster } from 'sonner'
@@ -9,7 +10,7 @@ import LogsPage from './pages/LogsPage'
 import ModelConfigPage from './pages/ModelConfigPage'
 import SubtitleSettingsPage from './pages/SubtitleSettingsPage'
 import VideoSubtitlePage from './pages/VideoSubtitlePage'
-import { useAsrStore } from './store/asrStore'
+import { toCloudAsrConfig, useAsrStore } from './store/asrStore'
 import { useEnvironmentStore } from './store/environmentStore'
 
 const App = () => {
@@ -22,6 +23,15 @@ const App = () => {
     useAsrStore.getState().refreshAudioDevices()
   }, [isTauriEnv])
 
+  // 上次退出时未完成的云端文件识别任务不保存密钥，用当前配置继续
+  useEffect(() => {
+    if (!isTauriEnv) return
+    const { cloud } = useAsrStore.getState().fullConfig
+    invoke('restore_file_jobs', { config: toCloudAsrConfig(cloud) }).catch((err) =>
+      console.error('恢复文件识别任务失败', err)
+    )
+  }, [isTauriEnv])
+
   return (
     <>
       <Routes>
@@ -12,7 +12,7 @@ import {
 
 import { useEnvironment } from './EnvironmentContext'
 
-import type { AsrModelConfig } from '../types/asr'
+import type { AsrModelConfig, WordTiming } from '../types/asr'
 
 export interface AsrResultMessage {
   sentence_id: number
@@ -22,13 +22,29 @@ export interface AsrResultMessage {
   is_final: boolean
   kind: 'transcription' | 'translation'
   lang?: string | null
+  /// 并发捕获时所属的会话 ID 与音频来源
+  session_id?: string
+  source?: 'microphone' | 'system' | 'file'
+  /// 文件识别的说话人（从 0 开始）、音轨与字级时间戳
+  speaker_id?: number
+  channel_id?: number
+  words?: WordTiming[]
 }
 
 export interface AudioDevice {
+  id: string
   name: string
-  /// "microphone" | "loopback"
-  device_type: 'microphone' | 'loopback'
+  host_api: string
+  /// "microphone" | "loopback" | "application"
+  device_type: 'microphone' | 'loopback' | 'application'
   is_default: boolean
+  pid?: number | null
+  supported_configs: {
+    channels: number
+    min_sample_rate: number
+    max_sample_rate: number
+    sample_format: string
+  }[]
 }
 
 export interface DeviceError {
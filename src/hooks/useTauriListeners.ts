@@ -1,7 +1,7 @@
 import { listen } from '@tauri-apps/api/event'
 import { useEffect } from 'react'
 
-import { useAsrStore, type AsrResultMessage } from '@/store/asrStore'
+import { useAsrStore, type AsrErrorMessage, type AsrResultMessage } from '@/store/asrStore'
 import { useEnvironmentStore } from '@/store/environmentStore'
 import { useLogsStore, type LogMessage } from '@/store/logsStore'
 
@@ -13,8 +13,12 @@ export const useAsrListener = () => {
     const unlisten = listen<AsrResultMessage>('asr-result', (event) => {
       useAsrStore.getState().addAsrResult(event.payload)
     })
+    const unlistenError = listen<AsrErrorMessage>('asr-error', (event) => {
+      useAsrStore.getState().handleAsrError(event.payload)
+    })
     return () => {
       unlisten.then((fn) => fn())
+      unlistenError.then((fn) => fn())
     }
   }, [isTauriEnv])
 }
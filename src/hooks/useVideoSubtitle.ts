@@ -3,9 +3,15 @@ import { listen } from '@tauri-apps/api/event'
 import { save } from '@tauri-apps/plugin-dialog'
 import { useCallback, useEffect, useRef, useState } from 'react'
 
-import { ASR_FULL_CONFIG_KEY, DEFAULT_OSS_CONFIG, useAsrStore } from '@/store/asrStore'
+import {
+  ASR_FULL_CONFIG_KEY,
+  isStorageReady,
+  toCloudAsrConfig,
+  useAsrStore,
+} from '@/store/asrStore'
 import { useEnvironmentStore } from '@/store/environmentStore'
 
+import type { FileAsrProgress, FileJobStage } from '@/types/asr'
 import type { RefObject } from 'react'
 
 // ── 类型定义 ─────────────────────────────────────────────────────────────────
@@ -20,6 +26,14 @@ export interface SubtitleItem {
 
 export type ExportFormat = 'original' | 'translated' | 'bilingual'
 
+/** 云端识别任务各阶段的提示文案（结束阶段由调用方给出结果提示） */
+const FILE_JOB_STAGE_LABEL: Partial<Record<FileJobStage, string>> = {
+  uploading: '正在上传文件…',
+  submitted: '已提交云端识别任务…',
+  polling: '云端识别中',
+  downloading: '正在下载识别结果…',
+}
+
 // ── 错误信息本地化 ────────────────────────────────────────────────────────────
 
 function localizeError(err: unknown, prefix = ''): string {
@@ -72,7 +86,7 @@ export function srtTimeToMs(time: string): number {
 
 export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
   const isTauriEnv = useEnvironmentStore((s) => s.isTauriEnv)
-  const ossConfig = useAsrStore((s) => s.fullConfig.cloud.oss ?? DEFAULT_OSS_CONFIG)
+  const cloudConfig = useAsrStore((s) => s.fullConfig.cloud)
   const provider = useAsrStore((s) => s.fullConfig.fileRecProvider)
 
   const [videoPath, setVideoPath] = useState<string | null>(null)
@@ -87,6 +101,8 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
   const [exportProgress, setExportProgress] = useState(0)
   const [isPlaying, setIsPlaying] = useState(false)
   const [statusMsg, setStatusMsg] = useState('')
+  /** 正在运行的云端识别任务（来自 file-asr-progress 事件） */
+  const [recognizeJobId, setRecognizeJobId] = useState<string | null>(null)
 
   const wsRef = useRef<import('wavesurfer.js').default | null>(null)
   const seekingRef = useRef(false)
@@ -140,6 +156,43 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
     }
   }, [isTauriEnv])
 
+  // ── 云端识别进度事件监听 ────────────────────────────────────────────────────
+
+  useEffect(() => {
+    if (!isTauriEnv || !isRecognizing || !videoPath) return
+    let unlisten: (() => void) | null = null
+    listen<FileAsrProgress>('file-asr-progress', (e) => {
+      const { job_id, stage, input_path, polls, uploaded_bytes, total_bytes } = e.payload
+      if (input_path !== videoPath) return
+      const finished = stage === 'done' || stage === 'failed' || stage === 'cancelled'
+      setRecognizeJobId(finished ? null : job_id)
+      const label = FILE_JOB_STAGE_LABEL[stage]
+      if (!label) return
+      if (stage === 'polling') {
+        setStatusMsg(`${label}（第 ${polls} 次查询）`)
+      } else if (stage === 'uploading' && total_bytes > 0) {
+        setStatusMsg(`${label} ${Math.floor((uploaded_bytes * 100) / total_bytes)}%`)
+      } else {
+        setStatusMsg(label)
+      }
+    }).then((fn) => {
+      unlisten = fn
+    })
+    return () => {
+      unlisten?.()
+      setRecognizeJobId(null)
+    }
+  }, [isTauriEnv, isRecognizing, videoPath])
+
+  const cancelRecognition = useCallback(async () => {
+    if (!recognizeJobId) return
+    try {
+      await invoke('cancel_file_job', { jobId: recognizeJobId })
+    } catch (err) {
+      setStatusMsg(localizeError(err))
+    }
+  }, [recognizeJobId])
+
   // ── 播放/暂停控制 ───────────────────────────────────────────────────────────
 
   const togglePlay = useCallback(() => {
@@ -184,6 +237,7 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
       end_time: number | null
       text: string
       is_final: boolean
+      speaker_id?: number
     }
 
     const fullConfig = (() => {
@@ -195,17 +249,10 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
       }
     })()
 
-    // 云端模式：OSS 预校验
-    if (provider === 'cloud') {
-      if (
-        !ossConfig.oss_endpoint ||
-        !ossConfig.oss_bucket ||
-        !ossConfig.oss_key_id ||
-        !ossConfig.oss_key_secret
-      ) {
-        setStatusMsg('请先在「模型」页 → 文件识别（OSS）配置 OSS 参数')
-        return
-      }
+    // 云端模式：对象存储预校验
+    if (provider === 'cloud' && !isStorageReady(cloudConfig)) {
+      setStatusMsg('请先在「模型」页 → 文件识别存储 配置存储参数')
+      return
     }
 
     // 本地模式：识别模型路径校验
@@ -257,14 +304,7 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
         }
         asrConfig = {
           type: 'cloud',
-          streaming: recognition,
-          oss: {
-            endpoint: ossConfig.oss_endpoint,
-            bucket: ossConfig.oss_bucket,
-            access_key_id: ossConfig.oss_key_id,
-            access_key_secret: ossConfig.oss_key_secret,
-          },
-          file_asr_api_key: recognition.server_config?.api_key ?? '',
+          ...toCloudAsrConfig({ ...cloudConfig, recognition, fileAsr: fullConfig?.cloud?.fileAsr }),
         }
       }
 
@@ -286,7 +326,8 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
           id: idx,
           beginMs: e.begin_time,
           endMs: e.end_time ?? e.begin_time + 2000,
-          text: e.text,
+          // 开启说话人分离时与 SRT 一致标注说话人
+          text: e.speaker_id != null ? `[说话人${e.speaker_id + 1}] ${e.text}` : e.text,
         }))
 
       setSubtitles(items)
@@ -297,7 +338,7 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
       setIsExtracting(false)
       setIsRecognizing(false)
     }
-  }, [isTauriEnv, ossConfig, provider])
+  }, [isTauriEnv, cloudConfig, provider])
 
   // ── 导出视频（含格式选择：原文/译文/双语） ────────────────────────────────────
 
@@ -486,6 +527,8 @@ export function useVideoSubtitle(videoRef: RefObject<HTMLVideoElement | null>) {
     exportProgress,
     isPlaying,
     statusMsg,
+    recognizeJobId,
+    cancelRecognition,
     updateSubtitle,
     removeSubtitle,
     seekToMs,
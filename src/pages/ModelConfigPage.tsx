@@ -9,8 +9,11 @@ import {
   DEFAULT_GUMMY_RECOGNITION,
   DEFAULT_GUMMY_TRANSLATION,
   DEFAULT_LOCAL_CONFIG,
+  DEFAULT_LOCAL_HTTP_CONFIG,
   DEFAULT_OSS_CONFIG,
   DEFAULT_PARAFORMER,
+  DEFAULT_S3_CONFIG,
+  isStorageReady,
   useAsrStore,
 } from '@/store/asrStore'
 
@@ -18,9 +21,12 @@ import type {
   AsrFullConfig,
   AsrProvider,
   GummyConfig,
+  LocalHttpConfig,
   LocalModelConfig,
   OssConfig,
   ParaformerConfig,
+  S3Config,
+  StorageBackend,
 } from '@/types/asr'
 
 // ── 常量 ─────────────────────────────────────────────────────────────────────
@@ -37,6 +43,22 @@ const LANGUAGE_OPTIONS = [
   { label: '俄语', value: 'ru' },
 ]
 
+const STORAGE_OPTIONS: { label: string; value: StorageBackend }[] = [
+  { label: '阿里云 OSS', value: 'oss' },
+  { label: 'S3 兼容存储（AWS S3、MinIO）', value: 's3' },
+  { label: 'DashScope 临时存储', value: 'dashscope' },
+  { label: '本地 HTTP 目录', value: 'local_http' },
+]
+
+const STORAGE_DESCRIPTIONS: Record<StorageBackend, string> = {
+  oss: '文件以私有权限上传至 OSS，通过限时签名链接交给识别服务读取，识别完成后自动删除。',
+  s3: '文件以 SigV4 签名上传至 S3 兼容存储，通过限时签名链接交给识别服务读取，识别完成后自动删除。',
+  dashscope:
+    '使用百炼提供的临时存储，无需自备存储，仅需文件识别 API Key；文件 48 小时后自动过期，适合较小的文件。',
+  local_http:
+    '文件复制到本机由 HTTP 服务对外提供的目录，识别服务通过访问地址读取，识别完成后自动删除。访问地址必须可从公网访问。',
+}
+
 const TARGET_LANGUAGE_OPTIONS = LANGUAGE_OPTIONS.filter((o) => o.value !== 'auto')
 
 const WHISPER_MODELS = [
@@ -635,6 +657,7 @@ const ModelConfigPage = () => {
   const realtimeTransProvider = useAsrStore((s) => s.fullConfig.realtimeTransProvider ?? 'cloud')
   const fileRecProvider = useAsrStore((s) => s.fullConfig.fileRecProvider ?? 'cloud')
   const fileTransProvider = useAsrStore((s) => s.fullConfig.fileTransProvider ?? 'cloud')
+  const cloudConfig = useAsrStore((s) => s.fullConfig.cloud)
 
   // 模型配置
   const [gummyRec, setGummyRec] = useState<GummyConfig>(() => {
@@ -654,6 +677,13 @@ const ModelConfigPage = () => {
   const [ossConfig, setOssConfig] = useState<OssConfig>(
     () => loadSaved().cloud.oss ?? DEFAULT_OSS_CONFIG
   )
+  const [storage, setStorage] = useState<StorageBackend>(() => loadSaved().cloud.storage ?? 'oss')
+  const [s3Config, setS3Config] = useState<S3Config>(
+    () => loadSaved().cloud.s3 ?? DEFAULT_S3_CONFIG
+  )
+  const [localHttpConfig, setLocalHttpConfig] = useState<LocalHttpConfig>(
+    () => loadSaved().cloud.localHttp ?? DEFAULT_LOCAL_HTTP_CONFIG
+  )
   const [localRec, setLocalRec] = useState<LocalModelConfig>(
     () => loadSaved().local?.recognition ?? DEFAULT_LOCAL_CONFIG.recognition
   )
@@ -759,6 +789,32 @@ const ModelConfigPage = () => {
     [ossConfig, saveCloud]
   )
 
+  const handleStorage = useCallback(
+    (next: StorageBackend) => {
+      setStorage(next)
+      saveCloud({ storage: next })
+    },
+    [saveCloud]
+  )
+
+  const handleS3 = useCallback(
+    (updates: Partial<S3Config>) => {
+      const next = { ...s3Config, ...updates }
+      setS3Config(next)
+      saveCloud({ s3: next })
+    },
+    [s3Config, saveCloud]
+  )
+
+  const handleLocalHttp = useCallback(
+    (updates: Partial<LocalHttpConfig>) => {
+      const next = { ...localHttpConfig, ...updates }
+      setLocalHttpConfig(next)
+      saveCloud({ localHttp: next })
+    },
+    [localHttpConfig, saveCloud]
+  )
+
   const handleLocalRec = useCallback(
     (c: LocalModelConfig) => {
       setLocalRec(c)
@@ -781,11 +837,8 @@ const ModelConfigPage = () => {
 
   const basename = (path: string) => (path ? (path.split(/[\\/]/).pop() ?? path) : '未配置')
 
-  const ossReady =
-    ossConfig.oss_endpoint &&
-    ossConfig.oss_bucket &&
-    ossConfig.oss_key_id &&
-    ossConfig.oss_key_secret
+  const storageReady = isStorageReady(cloudConfig)
+  const storageName = STORAGE_OPTIONS.find((o) => o.value === storage)?.label ?? 'OSS'
 
   const slotLabel = (provider: AsrProvider, kind: 'rec' | 'trans') => {
     if (provider === 'local') {
@@ -793,7 +846,7 @@ const ModelConfigPage = () => {
       return `本地 · ${name}`
     }
     if (kind === 'rec') {
-      return ossReady ? '云端 · OSS + DashScope' : '云端 · OSS 未配置'
+      return storageReady ? `云端 · ${storageName} + DashScope` : `云端 · ${storageName} 未配置`
     }
     return '云端 · Gummy'
   }
@@ -938,52 +991,168 @@ const ModelConfigPage = () => {
                       ]}
                     />
                     <div className="border border-slate-200/80 rounded-2xl p-5 bg-slate-50/40">
-                      <h3 className="text-sm font-semibold text-slate-700 mb-1">
-                        文件识别存储（OSS）
-                      </h3>
+                      <h3 className="text-sm font-semibold text-slate-700 mb-1">文件识别存储</h3>
                       <p className="text-xs text-slate-500 mb-4">
-                        视频字幕云端识别需要先将文件上传至 OSS，识别完成后自动删除。
+                        视频字幕云端识别需要先将文件上传到识别服务可访问的存储。
+                        {STORAGE_DESCRIPTIONS[storage]}
                       </p>
-                      <div className="grid grid-cols-2 gap-4">
-                        <div>
-                          <label className={labelCls}>OSS Endpoint</label>
-                          <input
-                            value={ossConfig.oss_endpoint}
-                            onChange={(e) => handleOss({ oss_endpoint: e.target.value })}
-                            placeholder="oss-cn-beijing.aliyuncs.com"
-                            className={inputCls}
-                          />
-                        </div>
-                        <div>
-                          <label className={labelCls}>Bucket</label>
-                          <input
-                            value={ossConfig.oss_bucket}
-                            onChange={(e) => handleOss({ oss_bucket: e.target.value })}
-                            placeholder="my-bucket"
-                            className={inputCls}
-                          />
+                      <div className="mb-4 max-w-72">
+                        <label className={labelCls}>存储类型</label>
+                        <CustomSelect
+                          value={storage}
+                          onChange={(v) => handleStorage(v as StorageBackend)}
+                          options={STORAGE_OPTIONS}
+                        />
+                      </div>
+                      {storage === 'oss' && (
+                        <div className="grid grid-cols-2 gap-4">
+                          <div>
+                            <label className={labelCls}>OSS Endpoint</label>
+                            <input
+                              value={ossConfig.oss_endpoint}
+                              onChange={(e) => handleOss({ oss_endpoint: e.target.value })}
+                              placeholder="oss-cn-beijing.aliyuncs.com"
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Bucket</label>
+                            <input
+                              value={ossConfig.oss_bucket}
+                              onChange={(e) => handleOss({ oss_bucket: e.target.value })}
+                              placeholder="my-bucket"
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Access Key ID</label>
+                            <input
+                              value={ossConfig.oss_key_id}
+                              onChange={(e) => handleOss({ oss_key_id: e.target.value })}
+                              placeholder="LTAI..."
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Access Key Secret</label>
+                            <input
+                              type="password"
+                              value={ossConfig.oss_key_secret}
+                              onChange={(e) => handleOss({ oss_key_secret: e.target.value })}
+                              placeholder="••••••••"
+                              autoComplete="off"
+                              className={`${inputCls} font-mono`}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>签名链接有效期（秒）</label>
+                            <input
+                              type="number"
+                              min={60}
+                              value={ossConfig.oss_presign_expires_secs ?? 3600}
+                              onChange={(e) =>
+                                handleOss({
+                                  oss_presign_expires_secs: Number(e.target.value) || 3600,
+                                })
+                              }
+                              className={inputCls}
+                            />
+                          </div>
                         </div>
-                        <div>
-                          <label className={labelCls}>Access Key ID</label>
-                          <input
-                            value={ossConfig.oss_key_id}
-                            onChange={(e) => handleOss({ oss_key_id: e.target.value })}
-                            placeholder="LTAI..."
-                            className={inputCls}
-                          />
+                      )}
+                      {storage === 's3' && (
+                        <div className="grid grid-cols-2 gap-4">
+                          <div>
+                            <label className={labelCls}>Endpoint</label>
+                            <input
+                              value={s3Config.endpoint}
+                              onChange={(e) => handleS3({ endpoint: e.target.value })}
+                              placeholder="http://127.0.0.1:9000"
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Region</label>
+                            <input
+                              value={s3Config.region}
+                              onChange={(e) => handleS3({ region: e.target.value })}
+                              placeholder="us-east-1"
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Bucket</label>
+                            <input
+                              value={s3Config.bucket}
+                              onChange={(e) => handleS3({ bucket: e.target.value })}
+                              placeholder="my-bucket"
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>签名链接有效期（秒）</label>
+                            <input
+                              type="number"
+                              min={60}
+                              value={s3Config.presign_expires_secs ?? 3600}
+                              onChange={(e) =>
+                                handleS3({ presign_expires_secs: Number(e.target.value) || 3600 })
+                              }
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Access Key ID</label>
+                            <input
+                              value={s3Config.access_key_id}
+                              onChange={(e) => handleS3({ access_key_id: e.target.value })}
+                              placeholder="AKIA..."
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>Secret Access Key</label>
+                            <input
+                              type="password"
+                              value={s3Config.secret_access_key}
+                              onChange={(e) => handleS3({ secret_access_key: e.target.value })}
+                              placeholder="••••••••"
+                              autoComplete="off"
+                              className={`${inputCls} font-mono`}
+                            />
+                          </div>
+                          <div className="col-span-2">
+                            <ToggleRow
+                              label="Path-style 地址"
+                              hint="使用 endpoint/bucket/key 形式的地址，MinIO 等自建服务通常需要开启"
+                              checked={s3Config.path_style}
+                              onChange={(v) => handleS3({ path_style: v })}
+                            />
+                          </div>
                         </div>
-                        <div>
-                          <label className={labelCls}>Access Key Secret</label>
-                          <input
-                            type="password"
-                            value={ossConfig.oss_key_secret}
-                            onChange={(e) => handleOss({ oss_key_secret: e.target.value })}
-                            placeholder="••••••••"
-                            autoComplete="off"
-                            className={`${inputCls} font-mono`}
-                          />
+                      )}
+                      {storage === 'local_http' && (
+                        <div className="grid grid-cols-2 gap-4">
+                          <div>
+                            <label className={labelCls}>服务目录</label>
+                            <input
+                              value={localHttpConfig.directory}
+                              onChange={(e) => handleLocalHttp({ directory: e.target.value })}
+                              placeholder="/srv/www/asr"
+                              className={inputCls}
+                            />
+                          </div>
+                          <div>
+                            <label className={labelCls}>访问地址</label>
+                            <input
+                              value={localHttpConfig.base_url}
+                              onChange={(e) => handleLocalHttp({ base_url: e.target.value })}
+                              placeholder="https://files.example.com/asr"
+                              className={inputCls}
+                            />
+                          </div>
                         </div>
-                      </div>
+                      )}
                     </div>
                   </>
                 )}
@@ -3,7 +3,7 @@ import { useEffect, useMemo, useRef, useState } from 'react'
 
 import { Button } from '@/components/ui/button'
 import { msToSrtTime, srtTimeToMs, useVideoSubtitle } from '@/hooks/useVideoSubtitle'
-import { useAsrStore } from '@/store/asrStore'
+import { isStorageReady, useAsrStore } from '@/store/asrStore'
 import { useEnvironmentStore } from '@/store/environmentStore'
 
 import type { ExportFormat, SubtitleItem } from '@/hooks/useVideoSubtitle'
@@ -97,7 +97,7 @@ function SubtitleRow({ item, index, isActive, onSeek, onUpdate, onRemove }: Subt
 
 const VideoSubtitlePage = () => {
   const isTauriEnv = useEnvironmentStore((s) => s.isTauriEnv)
-  const ossConfig = useAsrStore((s) => s.fullConfig.cloud.oss)
+  const cloudConfig = useAsrStore((s) => s.fullConfig.cloud)
   const videoRef = useRef<HTMLVideoElement>(null)
   const waveContainerRef = useRef<HTMLDivElement>(null)
   const subtitleListRef = useRef<HTMLDivElement>(null)
@@ -116,6 +116,8 @@ const VideoSubtitlePage = () => {
     exportProgress,
     isPlaying,
     statusMsg,
+    recognizeJobId,
+    cancelRecognition,
     updateSubtitle,
     removeSubtitle,
     seekToMs,
@@ -169,12 +171,7 @@ const VideoSubtitlePage = () => {
         ? '导出中…'
         : null
 
-  const ossReady =
-    ossConfig &&
-    ossConfig.oss_endpoint.length > 0 &&
-    ossConfig.oss_bucket.length > 0 &&
-    ossConfig.oss_key_id.length > 0 &&
-    ossConfig.oss_key_secret.length > 0
+  const storageReady = isStorageReady(cloudConfig)
 
   return (
     <div className="w-full space-y-4">
@@ -189,12 +186,12 @@ const VideoSubtitlePage = () => {
         </div>
       )}
 
-      {/* OSS 未配置提示 */}
-      {!ossReady && (
+      {/* 文件识别存储未配置提示 */}
+      {!storageReady && (
         <div className="flex items-start gap-3 rounded-xl border border-blue-200/80 bg-blue-50/60 px-4 py-3 text-sm text-blue-700">
           <AlertTriangle size={16} className="mt-0.5 shrink-0 text-blue-500" />
           <span>
-            请先在「模型」页 → 云端 → 文件识别（OSS）配置 OSS 参数，否则无法进行视频字幕识别。
+            请先在「模型」页 → 云端 → 文件识别存储 配置存储参数，否则无法进行视频字幕识别。
           </span>
         </div>
       )}
@@ -349,8 +346,13 @@ const VideoSubtitlePage = () => {
           </div>
 
           {subtitles.length === 0 ? (
-            <div className="flex-1 flex items-center justify-center text-sm text-slate-400">
+            <div className="flex-1 flex flex-col items-center justify-center gap-2 text-sm text-slate-400">
               {isRecognizing ? '识别中，请稍候…' : '导入视频后自动识别字幕'}
+              {isRecognizing && recognizeJobId && (
+                <Button size="sm" variant="outline" onClick={cancelRecognition}>
+                  取消识别
+                </Button>
+              )}
             </div>
           ) : (
             <div ref={subtitleListRef} className="flex-1 overflow-y-auto">
@@ -8,8 +8,11 @@ import type {
   AsrFullConfig,
   GummyConfig,
   LocalConfig,
+  LocalHttpConfig,
   OssConfig,
   ParaformerConfig,
+  S3Config,
+  WordTiming,
 } from '@/types/asr'
 
 export interface AsrResultMessage {
@@ -20,12 +23,48 @@ export interface AsrResultMessage {
   is_final: boolean
   kind: 'transcription' | 'translation'
   lang?: string | null
+  /// 并发捕获时所属的会话 ID 与音频来源
+  session_id?: string
+  source?: 'microphone' | 'system' | 'file'
+  /// 文件识别的说话人（从 0 开始）、音轨与字级时间戳
+  speaker_id?: number
+  channel_id?: number
+  words?: WordTiming[]
+}
+
+export interface AsrErrorMessage {
+  kind:
+    | 'auth_failed'
+    | 'network_unreachable'
+    | 'tls'
+    | 'protocol'
+    | 'task_failed'
+    | 'quota_exceeded'
+    | 'config'
+    | 'inference'
+  code?: string
+  message: string
+  retryable: boolean
+  display: string
+}
+
+export interface SupportedInputConfig {
+  channels: number
+  min_sample_rate: number
+  max_sample_rate: number
+  sample_format: string
 }
 
 export interface AudioDevice {
+  /** 稳定标识，可区分同名设备；启动采集时作为 deviceId 传入 */
+  id: string
   name: string
-  device_type: 'microphone' | 'loopback'
+  host_api: string
+  device_type: 'microphone' | 'loopback' | 'application'
   is_default: boolean
+  /** application 类型设备对应的进程 ID */
+  pid?: number | null
+  supported_configs: SupportedInputConfig[]
 }
 
 export interface DeviceError {
@@ -48,6 +87,60 @@ export const DEFAULT_OSS_CONFIG: OssConfig = {
   oss_key_secret: '',
 }
 
+export const DEFAULT_S3_CONFIG: S3Config = {
+  endpoint: '',
+  region: 'us-east-1',
+  bucket: '',
+  access_key_id: '',
+  secret_access_key: '',
+  path_style: true,
+}
+
+export const DEFAULT_LOCAL_HTTP_CONFIG: LocalHttpConfig = {
+  directory: '',
+  base_url: '',
+}
+
+/** 当前选择的文件识别存储是否已填写必填项 */
+export function isStorageReady(cloud: AsrFullConfig['cloud']): boolean {
+  switch (cloud.storage ?? 'oss') {
+    case 's3': {
+      const s3 = cloud.s3 ?? DEFAULT_S3_CONFIG
+      return !!(s3.endpoint && s3.bucket && s3.access_key_id && s3.secret_access_key)
+    }
+    case 'dashscope':
+      return !!cloud.recognition.server_config?.api_key
+    case 'local_http': {
+      const local = cloud.localHttp ?? DEFAULT_LOCAL_HTTP_CONFIG
+      return !!(local.directory && local.base_url)
+    }
+    default: {
+      const oss = cloud.oss ?? DEFAULT_OSS_CONFIG
+      return !!(oss.oss_endpoint && oss.oss_bucket && oss.oss_key_id && oss.oss_key_secret)
+    }
+  }
+}
+
+/** 转为后端 CloudAsrConfig（文件识别、恢复云端任务时传给 Tauri 命令） */
+export function toCloudAsrConfig(cloud: AsrFullConfig['cloud']) {
+  const oss = cloud.oss ?? DEFAULT_OSS_CONFIG
+  return {
+    streaming: cloud.recognition,
+    storage: cloud.storage ?? 'oss',
+    oss: {
+      endpoint: oss.oss_endpoint,
+      bucket: oss.oss_bucket,
+      access_key_id: oss.oss_key_id,
+      access_key_secret: oss.oss_key_secret,
+      presign_expires_secs: oss.oss_presign_expires_secs,
+    },
+    s3: cloud.s3 ?? DEFAULT_S3_CONFIG,
+    local_http: cloud.localHttp ?? DEFAULT_LOCAL_HTTP_CONFIG,
+    file_asr_api_key: cloud.recognition.server_config?.api_key ?? '',
+    file_asr: cloud.fileAsr,
+  }
+}
+
 const DEFAULT_SERVER = { ws_url: 'wss://dashscope.aliyuncs.com/api-ws/v1/inference/', api_key: '' }
 
 const DEFAULT_GUMMY_RECOGNITION: GummyConfig = {
@@ -119,6 +212,7 @@ interface AsrStore {
   setCaptureMode: (mode: CaptureMode) => void
   setCaptureTarget: (target: CaptureTarget) => void
   addAsrResult: (result: AsrResultMessage) => void
+  handleAsrError: (error: AsrErrorMessage) => void
   clearAsrResults: () => void
   refreshAudioDevices: () => Promise<void>
   handleStartAudioCapture: () => Promise<void>
@@ -217,6 +311,14 @@ export const useAsrStore = create<AsrStore>()((set, get) => ({
     })
   },
 
+  handleAsrError: (error) => {
+    if (error.retryable) {
+      set({ audioStatus: `${error.display}（正在重连...）` })
+    } else {
+      set({ isCapturing: false, audioStatus: `错误: ${error.display}` })
+    }
+  },
+
   clearAsrResults: () => set({ asrResults: [] }),
 
   refreshAudioDevices: async () => {
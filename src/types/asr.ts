@@ -48,6 +48,115 @@ export interface OssConfig {
   oss_bucket: string
   oss_key_id: string
   oss_key_secret: string
+  /** 签名 URL 有效期（秒），缺省 3600 */
+  oss_presign_expires_secs?: number
+}
+
+/** 文件识别使用的对象存储 */
+export type StorageBackend = 'oss' | 's3' | 'dashscope' | 'local_http'
+
+/** S3 兼容存储（AWS S3、MinIO 等） */
+export interface S3Config {
+  /** 服务地址（含协议） */
+  endpoint: string
+  region: string
+  bucket: string
+  access_key_id: string
+  secret_access_key: string
+  /** path-style 地址，MinIO 等自建服务通常需要开启 */
+  path_style: boolean
+  presign_expires_secs?: number
+}
+
+/** 本地 HTTP 服务目录：文件复制到 directory，识别服务通过 base_url 读取 */
+export interface LocalHttpConfig {
+  directory: string
+  base_url: string
+}
+
+/** Fun-ASR 文件识别参数（未设置的字段不发送） */
+export interface FileAsrConfig {
+  diarization_enabled: boolean
+  /** 说话人数量参考值（2-100） */
+  speaker_count?: number
+  language_hints?: string[]
+  vocabulary_id?: string
+  /** 识别的音轨，从 0 开始 */
+  channel_id?: number[]
+  disfluency_removal_enabled: boolean
+}
+
+/** 字/词级时间戳（毫秒） */
+export interface WordTiming {
+  begin_time: number
+  end_time: number
+  text: string
+  punctuation?: string
+}
+
+/** 云端文件识别任务阶段 */
+export type FileJobStage =
+  | 'uploading'
+  | 'submitted'
+  | 'polling'
+  | 'downloading'
+  | 'done'
+  | 'failed'
+  | 'cancelled'
+
+/** file-asr-progress 事件负载 */
+export interface FileAsrProgress {
+  job_id: string
+  stage: FileJobStage
+  input_path: string
+  output_path: string
+  /** 上传进度（字节） */
+  uploaded_bytes: number
+  total_bytes: number
+  task_status?: string
+  polls: number
+  error?: string
+}
+
+/** 批量识别输出格式：<文件名>.srt 或 <文件名>.json */
+export type BatchOutputFormat = 'srt' | 'json'
+
+export type BatchItemStatus = 'pending' | 'running' | 'done' | 'failed'
+
+/** batch-asr-item 事件负载 */
+export interface BatchItemProgress {
+  item_id: string
+  input_path: string
+  output_path: string
+  provider: AsrProvider
+  status: BatchItemStatus
+  /** 云端条目对应的文件识别任务，可用于 cancel_file_job */
+  job_id?: string
+  sentences: number
+  error?: string
+}
+
+/** batch-asr-progress 事件负载 */
+export interface BatchProgress {
+  paused: boolean
+  total: number
+  pending: number
+  running: number
+  done: number
+  failed: number
+}
+
+/** 云端 / 本地各自同时处理的文件数 */
+export interface BatchConcurrency {
+  cloud: number
+  local: number
+}
+
+/** get_batch_queue 返回值 */
+export interface BatchSnapshot {
+  concurrency: BatchConcurrency
+  progress: BatchProgress
+  items: BatchItemProgress[]
 }
 
 export interface AsrFullConfig {
@@ -65,7 +174,13 @@ export interface AsrFullConfig {
     recognition: AsrModelConfig
     /** 语音翻译模型：Gummy（translation_enabled 固定 true） */
     translation: GummyConfig
+    /** 文件识别对象存储，缺省为 OSS */
+    storage?: StorageBackend
     /** 文件识别 OSS 配置 */
     oss: OssConfig
+    s3?: S3Config
+    localHttp?: LocalHttpConfig
+    /** 文件识别 Fun-ASR 参数 */
+    fileAsr?: FileAsrConfig
   }
 }